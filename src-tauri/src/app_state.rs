@@ -1,10 +1,18 @@
+use std::sync::Arc;
+
 use parking_lot::Mutex;
 
 use crate::{
     autoclicker::AutoClickerState,
+    input_hook::InputHook,
+    kill_switch::KillSwitch,
     macro_player::MacroPlaybackState,
+    macro_queue::QueueEngine,
+    macro_store::MacroStore,
     overlay::OverlayRuntimeState,
     recorder::RecorderState,
+    scheduler::Scheduler,
+    types::PlaybackLimits,
 };
 
 #[derive(Default)]
@@ -12,5 +20,12 @@ pub struct AppState {
     pub recorder: Mutex<RecorderState>,
     pub autoclicker: Mutex<AutoClickerState>,
     pub macro_player: Mutex<MacroPlaybackState>,
+    pub queue: Mutex<QueueEngine>,
     pub overlay: Mutex<OverlayRuntimeState>,
+    pub macro_store: Mutex<MacroStore>,
+    pub input_hook: InputHook,
+    pub kill_switch: Arc<KillSwitch>,
+    pub emergency_hotkey: Mutex<Option<String>>,
+    pub playback_limits: Mutex<PlaybackLimits>,
+    pub scheduler: Scheduler,
 }
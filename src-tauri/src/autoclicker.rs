@@ -7,11 +7,15 @@ use std::{
     time::Duration,
 };
 
-use enigo::{Enigo, MouseButton as EnigoMouseButton, MouseControllable};
 use rand::{thread_rng, Rng};
 use tauri::{Emitter, State, Window};
 
-use crate::{app_state::AppState, types::AutoClickerRequest};
+use crate::{
+    app_state::AppState,
+    input_sink::{EnigoSink, InputSink},
+    kill_switch::{arm_kill_switch, MarkedSink},
+    types::{AutoClickerRequest, MouseButton},
+};
 
 #[derive(Default)]
 pub struct AutoClickerState {
@@ -36,40 +40,32 @@ pub fn start_autoclicker(
     autoclicker.stop_flag = Some(stop_flag.clone());
     autoclicker.active = true;
 
+    let kill_flag = stop_flag.clone();
+    let kill_guard = arm_kill_switch(&state, &window, "autoclicker", move || {
+        kill_flag.store(true, Ordering::Relaxed);
+    });
+    let injection_log = state.kill_switch.injection_log();
+
     let interval = config.interval_ms.max(5);
     let jitter = config.jitter_ms.unwrap_or(0);
-    let button = config.button.unwrap_or_else(|| "left".to_string());
+    let button = config.button.unwrap_or(MouseButton::Left);
     let burst = config.burst;
     let window_clone = window.clone();
 
     let handle = thread::spawn(move || {
-        let mut enigo = Enigo::new();
-        let mut rng = thread_rng();
-        let mut clicks_sent = 0u32;
-
-        loop {
-            if stop_flag.load(Ordering::Relaxed) {
-                break;
-            }
-
-            enigo.mouse_click(parse_mouse_button(&button));
-            clicks_sent += 1;
-            let _ = window_clone.emit("autoclicker://tick", clicks_sent);
-
-            if let Some(max_clicks) = burst {
-                if clicks_sent >= max_clicks {
-                    break;
-                }
-            }
-
-            let jitter_offset = if jitter == 0 {
-                0
-            } else {
-                rng.gen_range(0..=jitter)
-            };
-
-            thread::sleep(Duration::from_millis(interval + jitter_offset));
-        }
+        let _kill_guard = kill_guard;
+        let mut sink = MarkedSink::new(EnigoSink::default(), injection_log);
+        let clicks_sent = run_autoclicker(
+            &mut sink,
+            button,
+            interval,
+            jitter,
+            burst,
+            &stop_flag,
+            |clicks| {
+                let _ = window_clone.emit("autoclicker://tick", clicks);
+            },
+        );
 
         let _ = window_clone.emit("autoclicker://done", clicks_sent);
     });
@@ -80,32 +76,110 @@ pub fn start_autoclicker(
 
 #[tauri::command]
 pub fn stop_autoclicker(state: State<'_, AppState>) -> Result<(), String> {
-    let handle = {
-        let mut autoclicker = state.autoclicker.lock();
+    let handle = signal_autoclicker_stop(&mut state.autoclicker.lock())?;
 
-        if !autoclicker.active {
-            return Err("Autoclicker is not running".into());
+    if let Some(handle) = handle {
+        let _ = handle.join();
+    }
+
+    Ok(())
+}
+
+/// Raises the stop flag of a running autoclicker and hands back its thread.
+pub(crate) fn signal_autoclicker_stop(
+    autoclicker: &mut AutoClickerState,
+) -> Result<Option<thread::JoinHandle<()>>, String> {
+    if !autoclicker.active {
+        return Err("Autoclicker is not running".into());
+    }
+
+    if let Some(flag) = autoclicker.stop_flag.take() {
+        flag.store(true, Ordering::Relaxed);
+    }
+
+    autoclicker.active = false;
+    Ok(autoclicker.handle.take())
+}
+
+/// Clicks `button` every `interval_ms` (plus up to `jitter_ms` of random delay)
+/// until `stop_flag` is raised or `burst` clicks have been sent. Returns the
+/// number of clicks injected.
+pub fn run_autoclicker<S: InputSink, F: FnMut(u32)>(
+    sink: &mut S,
+    button: MouseButton,
+    interval_ms: u64,
+    jitter_ms: u64,
+    burst: Option<u32>,
+    stop_flag: &AtomicBool,
+    mut on_tick: F,
+) -> u32 {
+    let mut rng = thread_rng();
+    let mut clicks_sent = 0u32;
+
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            break;
         }
 
-        if let Some(flag) = autoclicker.stop_flag.take() {
-            flag.store(true, Ordering::Relaxed);
+        sink.mouse_click(button);
+        clicks_sent += 1;
+        on_tick(clicks_sent);
+
+        if let Some(max_clicks) = burst {
+            if clicks_sent >= max_clicks {
+                break;
+            }
         }
 
-        autoclicker.active = false;
-        autoclicker.handle.take()
-    };
+        let jitter_offset = if jitter_ms == 0 {
+            0
+        } else {
+            rng.gen_range(0..=jitter_ms)
+        };
 
-    if let Some(handle) = handle {
-        let _ = handle.join();
+        thread::sleep(Duration::from_millis(interval_ms + jitter_offset));
     }
 
-    Ok(())
+    clicks_sent
 }
 
-fn parse_mouse_button(button: &str) -> EnigoMouseButton {
-    match button {
-        "right" => EnigoMouseButton::Right,
-        "middle" => EnigoMouseButton::Middle,
-        _ => EnigoMouseButton::Left,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_sink::{RecordingSink, SinkEvent};
+
+    #[test]
+    fn burst_sends_that_many_clicks_at_the_interval() {
+        let mut sink = RecordingSink::default();
+        let mut ticks = Vec::new();
+        let stop_flag = AtomicBool::new(false);
+        let sent = run_autoclicker(&mut sink, MouseButton::Right, 15, 0, Some(3), &stop_flag, |clicks| {
+            ticks.push(clicks)
+        });
+
+        assert_eq!(sent, 3);
+        assert_eq!(ticks, vec![1, 2, 3]);
+        let click = [SinkEvent::MouseDown(MouseButton::Right), SinkEvent::MouseUp(MouseButton::Right)];
+        assert_eq!(sink.events(), vec![click.to_vec(); 3].concat());
+
+        let downs: Vec<Duration> = sink
+            .inputs()
+            .iter()
+            .filter(|input| matches!(input.event, SinkEvent::MouseDown(_)))
+            .map(|input| input.at)
+            .collect();
+        for pair in downs.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(15));
+        }
+    }
+
+    #[test]
+    fn raised_stop_flag_sends_nothing() {
+        let mut sink = RecordingSink::default();
+        let stop_flag = AtomicBool::new(true);
+        let sent = run_autoclicker(&mut sink, MouseButton::Left, 5, 0, None, &stop_flag, |_| {});
+
+        assert_eq!(sent, 0);
+        assert!(sink.events().is_empty());
     }
 }
@@ -0,0 +1,297 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, TimeZone, Timelike};
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead `next_after` looks before deciding an expression never
+/// matches, e.g. `0 0 31 2 *`.
+const SEARCH_DAYS: u32 = 366 * 5;
+
+/// A five-field cron expression: minute, hour, day of month, month and day of
+/// week. Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`)
+/// and comma-separated lists; months and weekdays also take three-letter
+/// names, and both 0 and 7 mean Sunday. As in Vixie cron, a day fires when it
+/// matches either day field if both are restricted, and both fields otherwise;
+/// a field starting with `*`, such as `*/2`, does not count as restricted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron expression `{expression}` must have five fields: minute hour day month weekday"
+            ));
+        };
+
+        let weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES, "day of week")?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], "minute")?,
+            hours: parse_field(hour, 0, 23, &[], "hour")?,
+            days: parse_field(day, 1, 31, &[], "day of month")?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, "month")?,
+            weekdays: (weekdays | weekdays >> 7) & 0x7f,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// The first whole minute after `after` that matches, in the time zone of
+    /// `after`. Wall-clock times skipped by a daylight saving change never
+    /// match and times repeated by one match once.
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let zone = after.timezone();
+        let start = after.naive_local() + ChronoDuration::minutes(1);
+        let mut date = start.date();
+        let mut from = (start.hour(), start.minute());
+
+        for _ in 0..SEARCH_DAYS {
+            if self.matches_date(date) {
+                for hour in from.0..24 {
+                    if self.hours & (1 << hour) == 0 {
+                        continue;
+                    }
+                    let first_minute = if hour == from.0 { from.1 } else { 0 };
+                    for minute in first_minute..60 {
+                        if self.minutes & (1 << minute) == 0 {
+                            continue;
+                        }
+                        let naive = date.and_hms_opt(hour, minute, 0)?;
+                        if let Some(time) = zone.from_local_datetime(&naive).earliest() {
+                            if time > after {
+                                return Some(time);
+                            }
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            from = (0, 0);
+        }
+        None
+    }
+}
+
+/// Parses one field into a bit set of the values it selects.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], label: &str) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step `{step}` in {label} field `{field}`"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, min, max, names, label)?,
+                parse_value(end, min, max, names, label)?,
+            )
+        } else {
+            let value = parse_value(range, min, max, names, label)?;
+            // `5/15` means "from 5, every 15".
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            return Err(format!("range `{range}` in {label} field runs backwards"));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str], label: &str) -> Result<u32, String> {
+    let parsed = match names.iter().position(|name| name.eq_ignore_ascii_case(value)) {
+        Some(index) => index as u32 + min,
+        None => value
+            .parse::<u32>()
+            .map_err(|_| format!("invalid {label} `{value}`"))?,
+    };
+    if parsed < min || parsed > max {
+        return Err(format!("{label} `{value}` is outside {min}-{max}"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDateTime, Utc};
+
+    use super::*;
+
+    /// Central European time as it ran in 2024: UTC+1, and UTC+2 from
+    /// 31 March 02:00 to 27 October 03:00 local time.
+    #[derive(Debug, Clone, Copy)]
+    struct Berlin;
+
+    impl Berlin {
+        fn summer_time(utc: &NaiveDateTime) -> bool {
+            let starts = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let ends = NaiveDate::from_ymd_opt(2024, 10, 27).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            (starts..ends).contains(utc)
+        }
+
+        fn offset(hours: i32) -> FixedOffset {
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for Berlin {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Berlin
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(12, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            // Summer time first: the earlier of two readings of a repeated time.
+            let valid: Vec<FixedOffset> = [2, 1]
+                .into_iter()
+                .map(Berlin::offset)
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+                .collect();
+            match valid[..] {
+                [] => MappedLocalTime::None,
+                [offset] => MappedLocalTime::Single(offset),
+                [earlier, later, ..] => MappedLocalTime::Ambiguous(earlier, later),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Berlin::offset(if Berlin::summer_time(utc) { 2 } else { 1 })
+        }
+    }
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0).unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        CronSchedule::parse(expression).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        // 2024-01-01 was a Monday.
+        let new_year = utc(1, 1, 0, 0);
+        let cases = [
+            ("* * * * *", utc(1, 1, 0, 1)),
+            ("30 9 * * *", utc(1, 1, 9, 30)),
+            // Names, in any case, and 7 for Sunday.
+            ("0 12 * * sat", utc(1, 6, 12, 0)),
+            ("0 0 * * SUN", utc(1, 7, 0, 0)),
+            ("0 0 * * 7", utc(1, 7, 0, 0)),
+            ("0 0 1 Feb *", utc(2, 1, 0, 0)),
+            ("0 0 * mar-apr mon-fri", utc(3, 1, 0, 0)),
+            // Ranges, steps and lists.
+            ("*/15 * * * *", utc(1, 1, 0, 15)),
+            ("5-10/2 3 * * *", utc(1, 1, 3, 5)),
+            ("0 9-17/4 * * *", utc(1, 1, 9, 0)),
+            ("10/20 * * * *", utc(1, 1, 0, 10)),
+            ("0 6,18 * * *", utc(1, 1, 6, 0)),
+            // Both day fields restricted: either one fires.
+            ("0 0 13 * fri", utc(1, 5, 0, 0)),
+            // A day field starting with `*` is not restricted: both must match.
+            ("0 0 */2 * *", utc(1, 3, 0, 0)),
+            ("0 0 */2 * mon", utc(1, 15, 0, 0)),
+            ("0 0 1 * */2", utc(2, 1, 0, 0)),
+        ];
+        for (expression, expected) in cases {
+            assert_eq!(next(expression, new_year), Some(expected), "{expression}");
+        }
+    }
+
+    #[test]
+    fn expressions_that_never_match_find_nothing() {
+        assert_eq!(next("0 0 31 2 *", utc(1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        let cases = [
+            ("* * * *", "cron expression `* * * *` must have five fields: minute hour day month weekday"),
+            ("60 * * * *", "minute `60` is outside 0-59"),
+            ("* * 0 * *", "day of month `0` is outside 1-31"),
+            ("5-1 * * * *", "range `5-1` in minute field runs backwards"),
+            ("*/0 * * * *", "invalid step `0` in minute field `*/0`"),
+            ("0 0 * * funday", "invalid day of week `funday`"),
+            ("0 0 * smarch *", "invalid month `smarch`"),
+        ];
+        for (expression, message) in cases {
+            assert_eq!(CronSchedule::parse(expression), Err(message.to_string()), "{expression}");
+        }
+    }
+
+    #[test]
+    fn skips_times_lost_to_the_spring_gap() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let after = Berlin.with_ymd_and_hms(2024, 3, 30, 12, 0, 0).unwrap();
+
+        let next = schedule.next_after(after).unwrap();
+        assert_eq!(next, Berlin.with_ymd_and_hms(2024, 4, 1, 2, 30, 0).unwrap());
+
+        let every_minute = CronSchedule::parse("* * * * *").unwrap();
+        let before_gap = Berlin.with_ymd_and_hms(2024, 3, 31, 1, 59, 0).unwrap();
+        let after_gap = every_minute.next_after(before_gap).unwrap();
+        assert_eq!(after_gap, Berlin.with_ymd_and_hms(2024, 3, 31, 3, 0, 0).unwrap());
+        assert_eq!(after_gap - before_gap, ChronoDuration::minutes(1));
+    }
+
+    #[test]
+    fn fires_once_in_the_autumn_overlap() {
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let after = Berlin.with_ymd_and_hms(2024, 10, 27, 0, 0, 0).unwrap();
+
+        let first = schedule.next_after(after).unwrap();
+        assert_eq!(first, Berlin.with_ymd_and_hms(2024, 10, 27, 2, 30, 0).earliest().unwrap());
+        assert_eq!(first.offset().local_minus_utc(), 2 * 3600);
+
+        let tomorrow = Berlin.with_ymd_and_hms(2024, 10, 28, 2, 30, 0).unwrap();
+        assert_eq!(schedule.next_after(first), Some(tomorrow));
+        // Not again when the clock reads 02:30 the second time.
+        let repeated_two = Berlin.with_ymd_and_hms(2024, 10, 27, 2, 0, 0).latest().unwrap();
+        assert_eq!(repeated_two.offset().local_minus_utc(), 3600);
+        assert_eq!(schedule.next_after(repeated_two), Some(tomorrow));
+    }
+}
@@ -0,0 +1,400 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::types::{KeyStroke, MacroEvent, MacroEventKind};
+
+/// Schema version written by this build. Bump it together with a new entry in
+/// `MIGRATIONS` whenever the on-disk layout changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+type Migration = fn(Value, &mut Vec<String>) -> Result<Value, String>;
+
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1];
+
+/// Versioned contents of `macroarc.macros.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MacroDocument {
+    pub schema_version: u32,
+    pub macros: Vec<StoredMacro>,
+}
+
+impl Default for MacroDocument {
+    fn default() -> Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            macros: Vec::new(),
+        }
+    }
+}
+
+/// A saved macro, field-for-field with the frontend `MacroSequence`. Fields
+/// this build does not know about are carried in `extra` so they survive a
+/// load/save round-trip.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMacro {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub accent: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "default_loop_count")]
+    pub loop_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playback_speed: Option<f64>,
+    pub events: Vec<StoredMacroEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scroll_delta_mode: Option<ScrollDeltaMode>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl StoredMacro {
+    pub fn macro_events(&self) -> Vec<MacroEvent> {
+        self.events.iter().map(StoredMacroEvent::to_macro_event).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMacroEvent {
+    pub id: String,
+    pub offset_ms: u64,
+    pub kind: MacroEventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl StoredMacroEvent {
+    pub fn to_macro_event(&self) -> MacroEvent {
+        MacroEvent {
+            offset_ms: self.offset_ms,
+            kind: self.kind.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollDeltaMode {
+    Legacy,
+    Native,
+}
+
+#[derive(Debug)]
+pub struct MigrationOutcome {
+    pub document: MacroDocument,
+    pub from_version: u32,
+    pub warnings: Vec<String>,
+}
+
+impl MigrationOutcome {
+    pub fn migrated(&self) -> bool {
+        self.from_version != CURRENT_SCHEMA_VERSION
+    }
+}
+
+pub fn parse_document(raw: &str) -> Result<MigrationOutcome, String> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|error| format!("macro document is not valid JSON: {error}"))?;
+    migrate_document(value)
+}
+
+pub fn serialize_document(document: &MacroDocument) -> Result<String, String> {
+    serde_json::to_string_pretty(document).map_err(|error| format!("failed to serialize macro document: {error}"))
+}
+
+/// Runs every migration between the document's version and
+/// `CURRENT_SCHEMA_VERSION`, then decodes the result.
+pub fn migrate_document(value: Value) -> Result<MigrationOutcome, String> {
+    let mut warnings = Vec::new();
+    let (value, from_version) = upgrade_document_value(value, &mut warnings)?;
+    decode_document(value, from_version, warnings)
+}
+
+/// Applies the migrations without decoding, returning the upgraded JSON and
+/// the version it started at.
+pub fn upgrade_document_value(mut value: Value, warnings: &mut Vec<String>) -> Result<(Value, u32), String> {
+    let from_version = detect_schema_version(&value)?;
+    if from_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "macro document schema_version {from_version} is newer than the supported version {CURRENT_SCHEMA_VERSION}"
+        ));
+    }
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from_version as usize) {
+        value = migration(value, warnings)
+            .map_err(|error| format!("migration from schema_version {version} failed: {error}"))?;
+    }
+    Ok((value, from_version))
+}
+
+pub(crate) fn decode_document(
+    value: Value,
+    from_version: u32,
+    warnings: Vec<String>,
+) -> Result<MigrationOutcome, String> {
+    let document: MacroDocument =
+        serde_json::from_value(value).map_err(|error| format!("invalid macro document: {error}"))?;
+    Ok(MigrationOutcome {
+        document,
+        from_version,
+        warnings,
+    })
+}
+
+/// Version 0 is the unversioned bare array the frontend used to write.
+pub fn detect_schema_version(value: &Value) -> Result<u32, String> {
+    match value {
+        Value::Array(_) => Ok(0),
+        Value::Object(map) => map
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .map(|version| version as u32)
+            .ok_or_else(|| "macro document is missing a numeric schema_version".to_string()),
+        _ => Err("macro document must be an array or an object".into()),
+    }
+}
+
+/// v0 -> v1: wraps the bare macro array in a versioned envelope and folds in
+/// the fixes the frontend used to apply on every load:
+/// - captured macros without `scrollDeltaMode: "native"` stored inverted
+///   scroll deltas; they are flipped and marked native,
+/// - offsets are rounded and clamped to non-negative integers.
+///
+/// Key labels that do not parse load as unrecognized keys, as they would in a
+/// v1 document. The `"unknown"` mouse button has no v1 form, so a document
+/// using it fails to migrate rather than losing the event.
+fn migrate_v0_to_v1(value: Value, warnings: &mut Vec<String>) -> Result<Value, String> {
+    let Value::Array(macros) = value else {
+        return Err("expected a bare macro array".into());
+    };
+
+    let mut upgraded = Vec::with_capacity(macros.len());
+    for mut entry in macros {
+        let Some(macro_map) = entry.as_object_mut() else {
+            return Err("macro entry is not an object".into());
+        };
+        let name = macro_map
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("untitled")
+            .to_string();
+
+        let needs_scroll_flip = macro_map.get("scrollDeltaMode").and_then(Value::as_str) != Some("native")
+            && macro_map
+                .get("tags")
+                .and_then(Value::as_array)
+                .is_some_and(|tags| tags.iter().any(|tag| tag.as_str() == Some("capture")));
+
+        if let Some(Value::Array(events)) = macro_map.get_mut("events") {
+            if let Some(index) = events.iter().position(is_unknown_button_event) {
+                return Err(format!("event {index} of \"{name}\" uses the unknown mouse button"));
+            }
+
+            let unparseable = events.iter().filter(|event| is_unparseable_key_event(event)).count();
+            if unparseable > 0 {
+                warnings.push(format!(
+                    "{unparseable} key event(s) in \"{name}\" have an unrecognized key label and will not play"
+                ));
+            }
+
+            let mut flipped = false;
+            for event in events.iter_mut() {
+                normalize_legacy_offset(event);
+                if needs_scroll_flip {
+                    flipped |= invert_scroll(event);
+                }
+            }
+            if flipped {
+                macro_map.insert("scrollDeltaMode".into(), Value::from("native"));
+            }
+        }
+
+        upgraded.push(entry);
+    }
+
+    let mut document = Map::new();
+    document.insert("schema_version".into(), Value::from(1));
+    document.insert("macros".into(), Value::Array(upgraded));
+    Ok(Value::Object(document))
+}
+
+fn is_unknown_button_event(event: &Value) -> bool {
+    let Some(kind) = event.get("kind") else {
+        return false;
+    };
+    matches!(kind.get("type").and_then(Value::as_str), Some("mouse-down" | "mouse-up"))
+        && kind.get("button").and_then(Value::as_str) == Some("unknown")
+}
+
+fn is_unparseable_key_event(event: &Value) -> bool {
+    let Some(kind) = event.get("kind") else {
+        return false;
+    };
+    matches!(kind.get("type").and_then(Value::as_str), Some("key-down" | "key-up"))
+        && kind
+            .get("key")
+            .and_then(Value::as_str)
+            .is_none_or(|label| KeyStroke::parse(label).is_err())
+}
+
+fn normalize_legacy_offset(event: &mut Value) {
+    if let Some(offset) = event.get("offsetMs").and_then(Value::as_f64) {
+        let rounded = offset.max(0.0).round() as u64;
+        event["offsetMs"] = Value::from(rounded);
+    }
+}
+
+fn invert_scroll(event: &mut Value) -> bool {
+    let Some(kind) = event.get_mut("kind").and_then(Value::as_object_mut) else {
+        return false;
+    };
+    if kind.get("type").and_then(Value::as_str) != Some("scroll") {
+        return false;
+    }
+    for axis in ["delta_x", "delta_y"] {
+        if let Some(delta) = kind.get(axis).and_then(Value::as_i64) {
+            kind.insert(axis.into(), Value::from(-delta));
+        }
+    }
+    true
+}
+
+fn default_loop_count() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::types::{KeyCode, MouseButton};
+
+    fn legacy_macro(tags: &[&str], events: Value) -> Value {
+        json!([{ "id": "m1", "name": "Legacy", "accent": "#fff", "tags": tags, "loopCount": 1, "events": events }])
+    }
+
+    fn legacy_event(id: &str, offset_ms: f64, kind: Value) -> Value {
+        json!({ "id": id, "offsetMs": offset_ms, "kind": kind, "createdAt": 1 })
+    }
+
+    fn parse(value: &Value) -> MigrationOutcome {
+        parse_document(&value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn flips_legacy_scroll_deltas_of_captured_macros() {
+        let scroll = json!({ "type": "scroll", "delta_x": 2, "delta_y": -3 });
+        let outcome = parse(&legacy_macro(&["capture"], json!([legacy_event("e1", 0.0, scroll)])));
+
+        assert!(outcome.migrated());
+        let stored = &outcome.document.macros[0];
+        assert_eq!(stored.scroll_delta_mode, Some(ScrollDeltaMode::Native));
+        assert_eq!(stored.events[0].kind, MacroEventKind::Scroll { delta_x: -2, delta_y: 3 });
+    }
+
+    #[test]
+    fn keeps_scroll_deltas_of_other_macros() {
+        let scroll = json!({ "type": "scroll", "delta_x": 0, "delta_y": -3 });
+        let outcome = parse(&legacy_macro(&["edited"], json!([legacy_event("e1", 0.0, scroll)])));
+
+        let stored = &outcome.document.macros[0];
+        assert_eq!(stored.scroll_delta_mode, None);
+        assert_eq!(stored.events[0].kind, MacroEventKind::Scroll { delta_x: 0, delta_y: -3 });
+    }
+
+    #[test]
+    fn keeps_key_events_whose_label_does_not_parse() {
+        let events = json!([
+            legacy_event("e1", 0.0, json!({ "type": "key-down", "key": "Hyper" })),
+            legacy_event("e2", 5.0, json!({ "type": "key-down", "key": "Ctrl+S" })),
+        ]);
+        let outcome = parse(&legacy_macro(&[], events));
+
+        let keys: Vec<&MacroEventKind> = outcome.document.macros[0].events.iter().map(|event| &event.kind).collect();
+        assert_eq!(
+            keys,
+            [
+                &MacroEventKind::KeyDown { key: KeyStroke::unrecognized("Hyper".into()) },
+                &MacroEventKind::KeyDown { key: KeyStroke::parse("Ctrl+S").unwrap() },
+            ]
+        );
+        assert_eq!(
+            outcome.warnings,
+            ["1 key event(s) in \"Legacy\" have an unrecognized key label and will not play"]
+        );
+    }
+
+    #[test]
+    fn refuses_to_migrate_the_unknown_mouse_button() {
+        let events = json!([
+            legacy_event("e1", 0.0, json!({ "type": "mouse-down", "button": "left" })),
+            legacy_event("e2", 5.0, json!({ "type": "mouse-up", "button": "unknown" })),
+        ]);
+        let error = parse_document(&legacy_macro(&[], events).to_string()).unwrap_err();
+
+        assert_eq!(
+            error,
+            "migration from schema_version 0 failed: event 1 of \"Legacy\" uses the unknown mouse button"
+        );
+    }
+
+    #[test]
+    fn rounds_fractional_offsets() {
+        let click = json!({ "type": "mouse-down", "button": "left" });
+        let events = json!([legacy_event("e1", 12.4, click.clone()), legacy_event("e2", 12.6, click)]);
+        let outcome = parse(&legacy_macro(&[], events));
+
+        let offsets: Vec<u64> = outcome.document.macros[0].events.iter().map(|event| event.offset_ms).collect();
+        assert_eq!(offsets, [12, 13]);
+    }
+
+    #[test]
+    fn current_documents_round_trip() {
+        let raw = json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
+            "macros": [{
+                "id": "m1",
+                "name": "Saved",
+                "accent": "#0af",
+                "tags": ["capture"],
+                "loopCount": 3,
+                "scrollDeltaMode": "native",
+                "pinned": true,
+                "events": [
+                    { "id": "e1", "offsetMs": 0, "kind": { "type": "key-down", "key": "Shift+!", "code": "1" } },
+                    { "id": "e2", "offsetMs": 8, "kind": { "type": "mouse-up", "button": "x1" }, "note": "kept" },
+                ],
+            }],
+        });
+        let first = parse(&raw);
+        assert!(!first.migrated());
+        let stored = &first.document.macros[0];
+        assert_eq!(stored.extra.get("pinned"), Some(&Value::Bool(true)));
+        let MacroEventKind::KeyDown { key } = &stored.events[0].kind else {
+            panic!("expected a key-down");
+        };
+        assert_eq!((key.code, key.text.as_deref()), (KeyCode::Char('1'), Some("!")));
+        assert_eq!(stored.events[1].kind, MacroEventKind::MouseUp { button: MouseButton::X1 });
+
+        let serialized = serialize_document(&first.document).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&serialized).unwrap(), raw);
+        let second = parse_document(&serialized).unwrap();
+        assert_eq!(serialize_document(&second.document).unwrap(), serialized);
+    }
+}
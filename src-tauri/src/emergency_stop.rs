@@ -0,0 +1,98 @@
+use std::thread;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::{
+    app_state::AppState,
+    autoclicker::signal_autoclicker_stop,
+    macro_player::signal_macro_player_stop,
+    recorder::finish_recording,
+    types::EmergencyStopReport,
+};
+
+/// Panic hotkey registered at startup. It is handled entirely in Rust, so it
+/// works while the webview is frozen or hidden behind overlay windows.
+pub const DEFAULT_EMERGENCY_STOP_HOTKEY: &str = "CommandOrControl+Alt+Shift+S";
+
+/// Stops macro playback, the autoclicker and any recording straight through
+/// `AppState`, and drops scheduled runs waiting for the player so none start
+/// once it is free. Stop flags are raised before this returns; waiting for the
+/// worker threads to release their held inputs happens on a background
+/// thread, after which `macro://emergency-stop` is emitted with the report.
+pub fn emergency_stop(app: &AppHandle) -> EmergencyStopReport {
+    let state = app.state::<AppState>();
+
+    let waiting_runs_dropped = state.scheduler.clear_waiting(app);
+    let (playback_stopped, player_handles) = {
+        let mut player = state.macro_player.lock();
+        (player.is_busy(), signal_macro_player_stop(&mut player))
+    };
+    let autoclicker_handle = signal_autoclicker_stop(&mut state.autoclicker.lock()).ok();
+    let recording = finish_recording(&mut state.recorder.lock());
+
+    let report = EmergencyStopReport {
+        playback_stopped,
+        autoclicker_stopped: autoclicker_handle.is_some(),
+        recording_stopped: recording.is_some(),
+        waiting_runs_dropped,
+    };
+
+    if report.recording_stopped {
+        let _ = app.emit("macro://status", "recording-stopped");
+    }
+
+    let app = app.clone();
+    let payload = report.clone();
+    thread::spawn(move || {
+        for handle in player_handles.into_iter().chain(autoclicker_handle.flatten()) {
+            let _ = handle.join();
+        }
+        let _ = app.emit("macro://emergency-stop", payload);
+    });
+
+    report
+}
+
+/// Binds `hotkey` to `emergency_stop`, replacing the previous binding.
+/// `None` leaves the emergency stop without a hotkey.
+pub fn register_emergency_hotkey(app: &AppHandle, hotkey: Option<&str>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut current = state.emergency_hotkey.lock();
+    let shortcuts = app.global_shortcut();
+
+    if let Some(previous) = current.take() {
+        shortcuts
+            .unregister(previous.as_str())
+            .map_err(|error| format!("failed to unregister emergency stop hotkey `{previous}`: {error}"))?;
+    }
+
+    if let Some(hotkey) = hotkey {
+        shortcuts
+            .on_shortcut(hotkey, |app, _, event| {
+                if event.state() == ShortcutState::Pressed {
+                    emergency_stop(app);
+                }
+            })
+            .map_err(|error| format!("failed to register emergency stop hotkey `{hotkey}`: {error}"))?;
+        *current = Some(hotkey.to_string());
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn trigger_emergency_stop(app: AppHandle) -> EmergencyStopReport {
+    emergency_stop(&app)
+}
+
+#[tauri::command]
+pub fn get_emergency_stop_hotkey(state: State<'_, AppState>) -> Option<String> {
+    state.emergency_hotkey.lock().clone()
+}
+
+#[tauri::command]
+pub fn set_emergency_stop_hotkey(app: AppHandle, hotkey: Option<String>) -> Result<(), String> {
+    let hotkey = hotkey.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+    register_emergency_hotkey(&app, hotkey.as_deref())
+}
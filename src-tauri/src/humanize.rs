@@ -0,0 +1,263 @@
+use std::f64::consts::TAU;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    input_sink::InputSink,
+    types::{DelayJitter, Humanize, KeyCode, MacroEvent, MacroEventKind, MouseButton},
+};
+
+/// Applies `Humanize` settings during one playback. All randomness comes from
+/// a single seeded generator, so the same seed, events and options vary a run
+/// the same way every time.
+pub struct Humanizer {
+    settings: Humanize,
+    rng: StdRng,
+    cursor: Option<(i32, i32)>,
+}
+
+impl Humanizer {
+    pub fn new(settings: Humanize, seed: u64) -> Self {
+        Self {
+            settings,
+            rng: StdRng::seed_from_u64(seed),
+            cursor: None,
+        }
+    }
+
+    /// Varied offsets for one loop of `events`, given their `base` offsets as
+    /// returned by `loop_offsets`.
+    pub fn loop_offsets(&mut self, events: &[MacroEvent], base: &[u64]) -> Vec<u64> {
+        let mut keys: Vec<KeyCode> = Vec::new();
+        let mut buttons: Vec<MouseButton> = Vec::new();
+        let mut previous_base = 0u64;
+        let mut at = 0u64;
+
+        events
+            .iter()
+            .zip(base)
+            .map(|(event, &offset)| {
+                let gap = offset - previous_base;
+                previous_base = offset;
+
+                let mut noise = if gap > 0 { self.delay_noise() } else { 0.0 };
+                let releases = match &event.kind {
+                    // By code alone: a key released after its modifiers is
+                    // recorded with fewer of them than it went down with.
+                    MacroEventKind::KeyDown { key } => {
+                        keys.push(key.code);
+                        false
+                    }
+                    MacroEventKind::KeyUp { key } => take(&mut keys, &key.code),
+                    MacroEventKind::MouseDown { button } => {
+                        buttons.push(*button);
+                        false
+                    }
+                    MacroEventKind::MouseUp { button } => take(&mut buttons, button),
+                    _ => false,
+                };
+                if releases && self.settings.hold_variance_ms > 0.0 {
+                    noise += gaussian(&mut self.rng) * self.settings.hold_variance_ms;
+                }
+
+                at += (gap as f64 + noise).round().max(0.0) as u64;
+                at
+            })
+            .collect()
+    }
+
+    fn delay_noise(&mut self) -> f64 {
+        match self.settings.delay {
+            Some(DelayJitter::Gaussian { std_dev_ms }) => gaussian(&mut self.rng) * std_dev_ms,
+            Some(DelayJitter::Uniform { range_ms }) if range_ms > 0.0 => self.rng.gen_range(-range_ms..=range_ms),
+            _ => 0.0,
+        }
+    }
+
+    /// Called with every event just before it is injected. Before a button is
+    /// pressed, nudges the cursor to a random point within
+    /// `click_radius_px` of the last recorded position. Returns whether it
+    /// moved the cursor.
+    pub fn before_inject<S: InputSink>(&mut self, sink: &mut S, kind: &MacroEventKind) -> bool {
+        match kind {
+            MacroEventKind::MouseMove { x, y } => self.cursor = Some((*x, *y)),
+            MacroEventKind::MouseDown { .. } if self.settings.click_radius_px > 0 => {
+                let Some((x, y)) = self.cursor else {
+                    return false;
+                };
+                // Uniform over the disc rather than bunched at its centre.
+                let radius = f64::from(self.settings.click_radius_px) * self.rng.gen::<f64>().sqrt();
+                let angle = self.rng.gen_range(0.0..TAU);
+                let dx = (radius * angle.cos()).round() as i32;
+                let dy = (radius * angle.sin()).round() as i32;
+                if (dx, dy) != (0, 0) {
+                    sink.mouse_move_to(x + dx, y + dy);
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Removes `item` from `held`, returning whether it was there.
+fn take<T: PartialEq>(held: &mut Vec<T>, item: &T) -> bool {
+    match held.iter().position(|candidate| candidate == item) {
+        Some(index) => {
+            held.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// A standard normal sample, by the Box-Muller transform.
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let v: f64 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (TAU * v).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input_sink::{RecordingSink, SinkEvent},
+        types::KeyStroke,
+    };
+
+    fn at(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
+    }
+
+    fn clicks() -> Vec<MacroEvent> {
+        (0..20u64)
+            .flat_map(|index| {
+                let x = index as i32 * 10;
+                [
+                    at(index * 40, MacroEventKind::MouseMove { x, y: 100 }),
+                    at(index * 40 + 5, MacroEventKind::MouseDown { button: MouseButton::Left }),
+                    at(index * 40 + 25, MacroEventKind::MouseUp { button: MouseButton::Left }),
+                ]
+            })
+            .collect()
+    }
+
+    fn settings() -> Humanize {
+        Humanize {
+            delay: Some(DelayJitter::Gaussian { std_dev_ms: 30.0 }),
+            hold_variance_ms: 20.0,
+            click_radius_px: 4,
+        }
+    }
+
+    /// Offsets for two loops of `events` and the cursor nudges made along the way.
+    fn vary(events: &[MacroEvent], settings: Humanize, seed: u64) -> (Vec<Vec<u64>>, RecordingSink) {
+        let base: Vec<u64> = events.iter().map(|event| event.offset_ms).collect();
+        let mut humanizer = Humanizer::new(settings, seed);
+        let mut sink = RecordingSink::default();
+        let loops = (0..2)
+            .map(|_| {
+                for event in events {
+                    humanizer.before_inject(&mut sink, &event.kind);
+                }
+                humanizer.loop_offsets(events, &base)
+            })
+            .collect();
+        (loops, sink)
+    }
+
+    #[test]
+    fn the_same_seed_varies_a_run_the_same_way() {
+        let events = clicks();
+        let (offsets, sink) = vary(&events, settings(), 7);
+        let (again, sink_again) = vary(&events, settings(), 7);
+        assert_eq!(offsets, again);
+        assert_eq!(sink.events(), sink_again.events());
+        assert!(!sink.events().is_empty());
+        assert_ne!(offsets[0], offsets[1], "every loop is varied anew");
+    }
+
+    #[test]
+    fn different_seeds_vary_a_run_differently() {
+        let events = clicks();
+        let (offsets, sink) = vary(&events, settings(), 7);
+        let (other, other_sink) = vary(&events, settings(), 8);
+        assert_ne!(offsets, other);
+        assert_ne!(sink.events(), other_sink.events());
+    }
+
+    #[test]
+    fn offsets_never_go_backwards() {
+        let events = clicks();
+        let wild = Humanize {
+            delay: Some(DelayJitter::Uniform { range_ms: 500.0 }),
+            hold_variance_ms: 500.0,
+            click_radius_px: 0,
+        };
+        for seed in 0..50 {
+            let (offsets, _) = vary(&events, wild, seed);
+            for offsets in offsets {
+                assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]), "seed {seed}: {offsets:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_events_recorded_together_together() {
+        let events = [
+            at(0, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            at(0, MacroEventKind::MouseUp { button: MouseButton::Left }),
+            at(50, MacroEventKind::Scroll { delta_x: 0, delta_y: 1 }),
+            at(50, MacroEventKind::Scroll { delta_x: 0, delta_y: 1 }),
+        ];
+        let jitter = Humanize {
+            delay: Some(DelayJitter::Gaussian { std_dev_ms: 10.0 }),
+            ..Humanize::default()
+        };
+        let (offsets, _) = vary(&events, jitter, 3);
+        assert_eq!(offsets[0][0], offsets[0][1]);
+        assert_eq!(offsets[0][2], offsets[0][3]);
+    }
+
+    #[test]
+    fn varies_the_hold_of_a_key_released_with_other_modifiers() {
+        let key = |label: &str| KeyStroke::parse(label).unwrap();
+        let events = [
+            at(0, MacroEventKind::KeyDown { key: key("Shift+A") }),
+            at(100, MacroEventKind::KeyUp { key: key("A") }),
+            at(200, MacroEventKind::KeyUp { key: key("B") }),
+        ];
+        let hold = Humanize {
+            hold_variance_ms: 20.0,
+            ..Humanize::default()
+        };
+        let (offsets, _) = vary(&events, hold, 1);
+        let offsets = &offsets[0];
+        assert_ne!(offsets[1], 100, "the release of A is held longer or shorter");
+        assert_eq!(offsets[2] - offsets[1], 100, "B was never pressed, so nothing is varied");
+    }
+
+    #[test]
+    fn nudges_clicks_within_the_radius_of_the_last_move() {
+        let mut humanizer = Humanizer::new(settings(), 11);
+        let mut sink = RecordingSink::default();
+        let press = MacroEventKind::MouseDown { button: MouseButton::Left };
+        assert!(!humanizer.before_inject(&mut sink, &press), "no position recorded yet");
+
+        humanizer.before_inject(&mut sink, &MacroEventKind::MouseMove { x: 500, y: 300 });
+        for _ in 0..100 {
+            humanizer.before_inject(&mut sink, &press);
+        }
+        let nudges = sink.events();
+        assert!(nudges.len() > 50, "{nudges:?}");
+        for nudge in nudges {
+            let SinkEvent::MouseMove { x, y } = nudge else {
+                panic!("unexpected {nudge:?}");
+            };
+            let (dx, dy) = (f64::from(x - 500), f64::from(y - 300));
+            assert!(dx.hypot(dy) <= 4.5, "({x}, {y})");
+        }
+    }
+}
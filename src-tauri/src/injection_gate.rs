@@ -0,0 +1,142 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{
+    input_sink::{HeldInputs, InputSink},
+    macro_player::PlaybackControl,
+    types::{KeyCode, MouseButton},
+};
+
+/// How often a sink waiting at the gate checks whether its playback stopped.
+const STOP_POLL: Duration = Duration::from_millis(5);
+
+/// Lets one playback channel at a time hold keys or buttons down, so combos
+/// injected by different channels never interleave. Input that does not
+/// start a combo waits only while another channel is in the middle of one.
+#[derive(Default)]
+pub struct InjectionGate {
+    holder: Mutex<Option<u64>>,
+    released: Condvar,
+    next_id: AtomicU64,
+}
+
+impl InjectionGate {
+    fn register(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Waits until no other sink holds the gate, then takes it if `take` is
+    /// set. Gives up waiting once `control` is stopped, so a stopping
+    /// playback can always release what it holds.
+    fn enter(&self, id: u64, take: bool, control: &PlaybackControl) {
+        let mut holder = self.holder.lock();
+        while holder.is_some_and(|current| current != id) && !control.is_stopped() {
+            self.released.wait_for(&mut holder, STOP_POLL);
+        }
+        if take && holder.is_none() {
+            *holder = Some(id);
+        }
+    }
+
+    fn leave(&self, id: u64) {
+        let mut holder = self.holder.lock();
+        if *holder == Some(id) {
+            *holder = None;
+            self.released.notify_all();
+        }
+    }
+}
+
+/// Sink wrapper that passes everything through an `InjectionGate`, holding
+/// the gate from the first key or button it presses until it has let go of
+/// all of them.
+pub struct GatedSink<S: InputSink> {
+    inner: S,
+    gate: Arc<InjectionGate>,
+    id: u64,
+    control: Arc<PlaybackControl>,
+    held: HeldInputs,
+}
+
+impl<S: InputSink> GatedSink<S> {
+    pub fn new(inner: S, gate: Arc<InjectionGate>, control: Arc<PlaybackControl>) -> Self {
+        let id = gate.register();
+        Self {
+            inner,
+            gate,
+            id,
+            control,
+            held: HeldInputs::default(),
+        }
+    }
+
+    fn enter(&self, take: bool) {
+        self.gate.enter(self.id, take, &self.control);
+    }
+
+    fn leave_if_idle(&self) {
+        if self.held.is_empty() {
+            self.gate.leave(self.id);
+        }
+    }
+}
+
+impl<S: InputSink> InputSink for GatedSink<S> {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.enter(false);
+        self.inner.mouse_move_to(x, y);
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        self.enter(true);
+        self.inner.mouse_down(button);
+        if !self.held.buttons.contains(&button) {
+            self.held.buttons.push(button);
+        }
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        self.enter(false);
+        self.inner.mouse_up(button);
+        self.held.buttons.retain(|held| *held != button);
+        self.leave_if_idle();
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        self.enter(true);
+        self.inner.key_down(key);
+        if !self.held.keys.contains(&key) {
+            self.held.keys.push(key);
+        }
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        self.enter(false);
+        self.inner.key_up(key);
+        self.held.keys.retain(|held| *held != key);
+        self.leave_if_idle();
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        self.enter(false);
+        self.inner.scroll(delta_x, delta_y);
+    }
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        self.enter(false);
+        self.inner.mouse_click(button);
+    }
+}
+
+impl<S: InputSink> Drop for GatedSink<S> {
+    fn drop(&mut self) {
+        self.gate.leave(self.id);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use parking_lot::Mutex;
+use rdev::Event as RdevEvent;
+
+use crate::input_source::{InputSource, RdevSource};
+
+type Subscriber = Arc<dyn Fn(&RdevEvent) + Send + Sync>;
+
+/// The process-wide OS input hook. `rdev::listen` can only be started once,
+/// so the recorder and the kill switch both subscribe to this one hook.
+#[derive(Default)]
+pub struct InputHook {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    started: Arc<AtomicBool>,
+}
+
+impl InputHook {
+    /// Adds `callback` to the subscribers. It stays subscribed for the life of
+    /// the process, so it should check for itself whether it is interested.
+    pub fn subscribe<F>(&self, callback: F)
+    where
+        F: Fn(&RdevEvent) + Send + Sync + 'static,
+    {
+        self.subscribers.lock().push(Arc::new(callback));
+    }
+
+    /// Starts the hook thread unless it is already running. `on_error` is
+    /// called if the hook fails, after which the next call tries again; it is
+    /// dropped unused on calls made while the hook runs.
+    pub fn start<E>(&self, on_error: E)
+    where
+        E: FnOnce(String) + Send + 'static,
+    {
+        self.start_with(RdevSource, on_error);
+    }
+
+    fn start_with<S, E>(&self, source: S, on_error: E)
+    where
+        S: InputSource + Send + 'static,
+        E: FnOnce(String) + Send + 'static,
+    {
+        if self.started.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let subscribers = self.subscribers.clone();
+        let started = self.started.clone();
+        thread::spawn(move || {
+            let result = source.listen(move |event: RdevEvent| {
+                let snapshot = subscribers.lock().clone();
+                for subscriber in snapshot {
+                    subscriber(&event);
+                }
+            });
+
+            if let Err(error) = result {
+                started.store(false, Ordering::Relaxed);
+                on_error(error);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use super::*;
+
+    struct FailingSource;
+
+    impl InputSource for FailingSource {
+        fn listen<F>(self, _callback: F) -> Result<(), String>
+        where
+            F: FnMut(RdevEvent) + 'static,
+        {
+            Err("no display".into())
+        }
+    }
+
+    #[test]
+    fn a_failed_hook_can_be_started_again() {
+        let hook = InputHook::default();
+        let (errors, received) = mpsc::channel();
+
+        let first = errors.clone();
+        hook.start_with(FailingSource, move |error| first.send(error).unwrap());
+        assert_eq!(received.recv_timeout(Duration::from_secs(5)).unwrap(), "no display");
+        assert!(!hook.started.load(Ordering::Relaxed));
+
+        hook.start_with(FailingSource, move |error| errors.send(error).unwrap());
+        assert_eq!(received.recv_timeout(Duration::from_secs(5)).unwrap(), "no display");
+    }
+}
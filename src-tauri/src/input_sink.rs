@@ -0,0 +1,426 @@
+#[cfg(test)]
+use std::time::{Duration, Instant};
+
+use enigo::{Enigo, Key, KeyboardControllable, MouseButton as EnigoMouseButton, MouseControllable};
+
+use crate::types::{KeyCode, MouseButton};
+
+pub trait InputSink {
+    fn mouse_move_to(&mut self, x: i32, y: i32);
+    fn mouse_down(&mut self, button: MouseButton);
+    fn mouse_up(&mut self, button: MouseButton);
+    fn key_down(&mut self, key: KeyCode);
+    fn key_up(&mut self, key: KeyCode);
+    fn scroll(&mut self, delta_x: i32, delta_y: i32);
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        self.mouse_down(button);
+        self.mouse_up(button);
+    }
+}
+
+impl<S: InputSink + ?Sized> InputSink for &mut S {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        (**self).mouse_move_to(x, y);
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        (**self).mouse_down(button);
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        (**self).mouse_up(button);
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        (**self).key_down(key);
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        (**self).key_up(key);
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        (**self).scroll(delta_x, delta_y);
+    }
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        (**self).mouse_click(button);
+    }
+}
+
+pub struct EnigoSink {
+    enigo: Enigo,
+}
+
+impl Default for EnigoSink {
+    fn default() -> Self {
+        Self { enigo: Enigo::new() }
+    }
+}
+
+impl InputSink for EnigoSink {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.enigo.mouse_move_to(x, y);
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        if let Some(button) = enigo_button(button) {
+            self.enigo.mouse_down(button);
+        }
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        if let Some(button) = enigo_button(button) {
+            self.enigo.mouse_up(button);
+        }
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        if let Some(key) = enigo_key(key) {
+            self.enigo.key_down(key);
+        }
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        if let Some(key) = enigo_key(key) {
+            self.enigo.key_up(key);
+        }
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        if delta_y != 0 {
+            self.enigo.mouse_scroll_y(delta_y);
+        }
+        if delta_x != 0 {
+            self.enigo.mouse_scroll_x(delta_x);
+        }
+    }
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        if let Some(button) = enigo_button(button) {
+            self.enigo.mouse_click(button);
+        }
+    }
+}
+
+fn enigo_button(button: MouseButton) -> Option<EnigoMouseButton> {
+    match button {
+        MouseButton::Left => Some(EnigoMouseButton::Left),
+        MouseButton::Right => Some(EnigoMouseButton::Right),
+        MouseButton::Middle => Some(EnigoMouseButton::Middle),
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        MouseButton::X1 => Some(EnigoMouseButton::Back),
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        MouseButton::X2 => Some(EnigoMouseButton::Forward),
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        MouseButton::X1 | MouseButton::X2 => None,
+    }
+}
+
+/// Whether `EnigoSink` can inject `code` on this platform.
+pub fn can_inject_key(code: KeyCode) -> bool {
+    enigo_key(code).is_some()
+}
+
+/// Maps a key onto the enigo key that injects it on this platform. Keys the
+/// backend cannot produce here yield `None` rather than a guessed substitute.
+fn enigo_key(code: KeyCode) -> Option<Key> {
+    let key = match code {
+        KeyCode::Enter => Key::Return,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Space => Key::Space,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Escape => Key::Escape,
+        KeyCode::CapsLock => Key::CapsLock,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        KeyCode::Insert => Key::Insert,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Up => Key::UpArrow,
+        KeyCode::Down => Key::DownArrow,
+        KeyCode::Left => Key::LeftArrow,
+        KeyCode::Right => Key::RightArrow,
+        KeyCode::Shift => Key::Shift,
+        KeyCode::Ctrl => Key::Control,
+        KeyCode::Alt => Key::Alt,
+        KeyCode::Meta => Key::Meta,
+        #[cfg(target_os = "macos")]
+        KeyCode::Fn => Key::Function,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        KeyCode::PrintScreen => Key::Print,
+        #[cfg(target_os = "linux")]
+        KeyCode::ScrollLock => Key::ScrollLock,
+        #[cfg(target_os = "windows")]
+        KeyCode::ScrollLock => Key::Scroll,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        KeyCode::Pause => Key::Pause,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        KeyCode::NumLock => Key::Numlock,
+        KeyCode::F(index) => return function_key(index),
+        KeyCode::Numpad(symbol) => Key::Layout(symbol),
+        KeyCode::Char(ch) => Key::Layout(ch),
+        KeyCode::Raw(code) => Key::Raw(u16::try_from(code).ok()?),
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn function_key(index: u8) -> Option<Key> {
+    let key = match index {
+        1 => Key::F1,
+        2 => Key::F2,
+        3 => Key::F3,
+        4 => Key::F4,
+        5 => Key::F5,
+        6 => Key::F6,
+        7 => Key::F7,
+        8 => Key::F8,
+        9 => Key::F9,
+        10 => Key::F10,
+        11 => Key::F11,
+        12 => Key::F12,
+        13 => Key::F13,
+        14 => Key::F14,
+        15 => Key::F15,
+        16 => Key::F16,
+        17 => Key::F17,
+        18 => Key::F18,
+        19 => Key::F19,
+        20 => Key::F20,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        21 => Key::F21,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        22 => Key::F22,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        23 => Key::F23,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        24 => Key::F24,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Sink that discards everything, for dry runs.
+#[derive(Debug, Default)]
+pub struct NullSink;
+
+impl InputSink for NullSink {
+    fn mouse_move_to(&mut self, _x: i32, _y: i32) {}
+
+    fn mouse_down(&mut self, _button: MouseButton) {}
+
+    fn mouse_up(&mut self, _button: MouseButton) {}
+
+    fn key_down(&mut self, _key: KeyCode) {}
+
+    fn key_up(&mut self, _key: KeyCode) {}
+
+    fn scroll(&mut self, _delta_x: i32, _delta_y: i32) {}
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkEvent {
+    MouseMove { x: i32, y: i32 },
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
+    Scroll { delta_x: i32, delta_y: i32 },
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct RecordedInput {
+    pub at: Duration,
+    pub event: SinkEvent,
+}
+
+/// In-memory sink that records every injected input together with the time it
+/// arrived, so playback and autoclicker runs can be inspected without a display.
+#[cfg(test)]
+pub struct RecordingSink {
+    started: Instant,
+    inputs: Vec<RecordedInput>,
+}
+
+#[cfg(test)]
+impl Default for RecordingSink {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            inputs: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl RecordingSink {
+    pub fn inputs(&self) -> &[RecordedInput] {
+        &self.inputs
+    }
+
+    pub fn events(&self) -> Vec<SinkEvent> {
+        self.inputs.iter().map(|input| input.event.clone()).collect()
+    }
+
+    fn push(&mut self, event: SinkEvent) {
+        self.inputs.push(RecordedInput {
+            at: self.started.elapsed(),
+            event,
+        });
+    }
+}
+
+#[cfg(test)]
+impl InputSink for RecordingSink {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.push(SinkEvent::MouseMove { x, y });
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        self.push(SinkEvent::MouseDown(button));
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        self.push(SinkEvent::MouseUp(button));
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        self.push(SinkEvent::KeyDown(key));
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        self.push(SinkEvent::KeyUp(key));
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        self.push(SinkEvent::Scroll { delta_x, delta_y });
+    }
+}
+
+/// Keys and mouse buttons that have been pressed but not released, in the
+/// order they went down.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeldInputs {
+    pub keys: Vec<KeyCode>,
+    pub buttons: Vec<MouseButton>,
+}
+
+impl HeldInputs {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.buttons.is_empty()
+    }
+
+    /// Adds the entries of `other` that are not already listed.
+    pub fn merge(&mut self, other: HeldInputs) {
+        for key in other.keys {
+            if !self.keys.contains(&key) {
+                self.keys.push(key);
+            }
+        }
+        for button in other.buttons {
+            if !self.buttons.contains(&button) {
+                self.buttons.push(button);
+            }
+        }
+    }
+
+    /// Key labels followed by button names, as reported to the frontend.
+    pub fn labels(&self) -> Vec<String> {
+        self.keys
+            .iter()
+            .map(KeyCode::label)
+            .chain(self.buttons.iter().map(|button| button.as_str().to_string()))
+            .collect()
+    }
+}
+
+/// Wraps another sink and remembers which keys and buttons it is holding, so
+/// they can be released when playback is interrupted. Anything still held
+/// when the wrapper is dropped, including during a panic, is released then.
+pub struct TrackingSink<S: InputSink> {
+    inner: S,
+    held: HeldInputs,
+}
+
+impl<S: InputSink> TrackingSink<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            held: HeldInputs::default(),
+        }
+    }
+
+    /// Releases everything still held, most recently pressed first, and
+    /// returns what was released.
+    pub fn release_all(&mut self) -> HeldInputs {
+        let held = std::mem::take(&mut self.held);
+        for button in held.buttons.iter().rev() {
+            self.inner.mouse_up(*button);
+        }
+        for key in held.keys.iter().rev() {
+            self.inner.key_up(*key);
+        }
+        held
+    }
+
+    /// Presses `held` again in its original order, e.g. after `release_all`.
+    pub fn press_all(&mut self, held: &HeldInputs) {
+        for key in &held.keys {
+            self.key_down(*key);
+        }
+        for button in &held.buttons {
+            self.mouse_down(*button);
+        }
+    }
+}
+
+impl<S: InputSink> Drop for TrackingSink<S> {
+    fn drop(&mut self) {
+        self.release_all();
+    }
+}
+
+impl<S: InputSink> InputSink for TrackingSink<S> {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.inner.mouse_move_to(x, y);
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        self.inner.mouse_down(button);
+        if !self.held.buttons.contains(&button) {
+            self.held.buttons.push(button);
+        }
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        self.inner.mouse_up(button);
+        self.held.buttons.retain(|held| *held != button);
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        self.inner.key_down(key);
+        if !self.held.keys.contains(&key) {
+            self.held.keys.push(key);
+        }
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        self.inner.key_up(key);
+        self.held.keys.retain(|held| *held != key);
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        self.inner.scroll(delta_x, delta_y);
+    }
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        self.inner.mouse_click(button);
+    }
+}
@@ -0,0 +1,66 @@
+use rdev::Event as RdevEvent;
+#[cfg(test)]
+use rdev::EventType;
+
+pub trait InputSource {
+    /// Feeds every captured event into `callback`. Blocks until the source is
+    /// exhausted or fails.
+    fn listen<F>(self, callback: F) -> Result<(), String>
+    where
+        F: FnMut(RdevEvent) + 'static;
+}
+
+/// Live OS hook backed by `rdev::listen`. Never returns unless the hook fails.
+#[derive(Default)]
+pub struct RdevSource;
+
+impl InputSource for RdevSource {
+    fn listen<F>(self, callback: F) -> Result<(), String>
+    where
+        F: FnMut(RdevEvent) + 'static,
+    {
+        rdev::listen(callback).map_err(|error| format!("{error:?}"))
+    }
+}
+
+/// Plays a fixed list of synthetic events, in order, then returns.
+#[cfg(test)]
+#[derive(Default)]
+pub struct ScriptedSource {
+    events: Vec<RdevEvent>,
+}
+
+#[cfg(test)]
+impl ScriptedSource {
+    pub fn push(mut self, event_type: EventType) -> Self {
+        self.events.push(synthetic_event(event_type, None));
+        self
+    }
+
+    pub fn push_named(mut self, event_type: EventType, name: &str) -> Self {
+        self.events.push(synthetic_event(event_type, Some(name.to_string())));
+        self
+    }
+}
+
+#[cfg(test)]
+impl InputSource for ScriptedSource {
+    fn listen<F>(self, mut callback: F) -> Result<(), String>
+    where
+        F: FnMut(RdevEvent) + 'static,
+    {
+        for event in self.events {
+            callback(event);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub fn synthetic_event(event_type: EventType, name: Option<String>) -> RdevEvent {
+    RdevEvent {
+        time: std::time::SystemTime::now(),
+        name,
+        event_type,
+    }
+}
@@ -0,0 +1,563 @@
+use std::fmt;
+
+use schemars::{
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec},
+    schema_for,
+};
+use serde_json::Value;
+
+use crate::{
+    document::{decode_document, upgrade_document_value, MacroDocument, MigrationOutcome},
+    types::{
+        AutoClickerRequest,
+        KeyStroke,
+        MacroEvent,
+        MacroEventKind,
+        MacroPlaybackRequest,
+        OverlayWindowDescriptor,
+        KEY_STROKE_FORMAT,
+    },
+};
+
+pub const MACRO_FILE_SCHEMA: &str = "macro-file";
+
+/// Names accepted by `export_json_schema`.
+pub const SCHEMA_NAMES: &[&str] = &[
+    MACRO_FILE_SCHEMA,
+    "macro-event",
+    "macro-event-kind",
+    "macro-playback-request",
+    "autoclicker-request",
+    "overlay-window-descriptor",
+];
+
+pub fn schema_by_name(name: &str) -> Option<RootSchema> {
+    let schema = match name {
+        MACRO_FILE_SCHEMA => macro_file_schema(),
+        "macro-event" => schema_for!(MacroEvent),
+        "macro-event-kind" => schema_for!(MacroEventKind),
+        "macro-playback-request" => schema_for!(MacroPlaybackRequest),
+        "autoclicker-request" => schema_for!(AutoClickerRequest),
+        "overlay-window-descriptor" => schema_for!(OverlayWindowDescriptor),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Schema for `macroarc.macros.json` and exported macro files.
+pub fn macro_file_schema() -> RootSchema {
+    let mut root = schema_for!(MacroDocument);
+    let metadata = root.schema.metadata();
+    metadata.title = Some("MacroArc macro library".into());
+    metadata.description = Some("A versioned list of macros as stored by MacroArc.".into());
+    root
+}
+
+/// Parses, migrates and validates an external macro file. Structural problems
+/// are reported with the JSON path they occur at, e.g.
+/// `$.macros[0].events[3].kind.button`.
+pub fn import_macro_file(raw: &str) -> Result<MigrationOutcome, String> {
+    let value: Value =
+        serde_json::from_str(raw).map_err(|error| format!("macro file is not valid JSON: {error}"))?;
+    let mut warnings = Vec::new();
+    let (value, from_version) = upgrade_document_value(value, &mut warnings)?;
+
+    let violations = validate_against(&macro_file_schema(), &value);
+    if !violations.is_empty() {
+        let details: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        return Err(format!("macro file failed validation:\n{}", details.join("\n")));
+    }
+    decode_document(value, from_version, warnings)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Checks `value` against the subset of draft-07 that schemars emits: `$ref`
+/// into `definitions`, `type`, `enum`, `const`, integer formats, numeric and
+/// length bounds, `items`, `properties`/`required`/`additionalProperties`
+/// and `allOf`/`anyOf`/`oneOf`.
+pub fn validate_against(root: &RootSchema, value: &Value) -> Vec<SchemaViolation> {
+    let validator = Validator { root };
+    let mut violations = Vec::new();
+    validator.check_object(&root.schema, value, "$", &mut violations);
+    violations
+}
+
+struct Validator<'a> {
+    root: &'a RootSchema,
+}
+
+impl Validator<'_> {
+    fn check(&self, schema: &Schema, value: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+        match schema {
+            Schema::Bool(true) => {}
+            Schema::Bool(false) => push(out, path, "is not allowed here"),
+            Schema::Object(object) => self.check_object(object, value, path, out),
+        }
+    }
+
+    fn check_object(&self, schema: &SchemaObject, value: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+        if let Some(reference) = &schema.reference {
+            match self.resolve(reference) {
+                Some(target) => self.check(target, value, path, out),
+                None => push(out, path, format!("schema reference `{reference}` cannot be resolved")),
+            }
+            return;
+        }
+
+        if let Some(types) = &schema.instance_type {
+            if !matches_type(types, value) {
+                push(out, path, format!("expected {}, found {}", describe_types(types), describe_value(value)));
+                return;
+            }
+        }
+        if let Some(expected) = &schema.const_value {
+            if value != expected {
+                push(out, path, format!("expected {expected}, found {value}"));
+            }
+        }
+        if let Some(allowed) = &schema.enum_values {
+            if !allowed.contains(value) {
+                push(out, path, format!("expected one of {}, found {value}", list_values(allowed)));
+            }
+        }
+        if let Some(format) = &schema.format {
+            check_format(format, value, path, out);
+        }
+
+        if let (Some(number), Some(actual)) = (&schema.number, value.as_f64()) {
+            if number.minimum.is_some_and(|minimum| actual < minimum) {
+                push(out, path, format!("must be at least {}", number.minimum.unwrap_or_default()));
+            }
+            if number.maximum.is_some_and(|maximum| actual > maximum) {
+                push(out, path, format!("must be at most {}", number.maximum.unwrap_or_default()));
+            }
+        }
+
+        if let (Some(string), Some(actual)) = (&schema.string, value.as_str()) {
+            let length = actual.chars().count() as u32;
+            if string.min_length.is_some_and(|minimum| length < minimum) {
+                push(out, path, "is too short");
+            }
+            if string.max_length.is_some_and(|maximum| length > maximum) {
+                push(out, path, "is too long");
+            }
+        }
+
+        if let (Some(array), Some(items)) = (&schema.array, value.as_array()) {
+            if array.min_items.is_some_and(|minimum| (items.len() as u32) < minimum) {
+                push(out, path, format!("must contain at least {} item(s)", array.min_items.unwrap_or_default()));
+            }
+            if array.max_items.is_some_and(|maximum| (items.len() as u32) > maximum) {
+                push(out, path, format!("must contain at most {} item(s)", array.max_items.unwrap_or_default()));
+            }
+            match &array.items {
+                Some(SingleOrVec::Single(item_schema)) => {
+                    for (index, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{path}[{index}]"), out);
+                    }
+                }
+                Some(SingleOrVec::Vec(item_schemas)) => {
+                    for (index, (item_schema, item)) in item_schemas.iter().zip(items).enumerate() {
+                        self.check(item_schema, item, &format!("{path}[{index}]"), out);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        if let (Some(object), Some(map)) = (&schema.object, value.as_object()) {
+            for required in &object.required {
+                if !map.contains_key(required) {
+                    push(out, path, format!("missing required property `{required}`"));
+                }
+            }
+            for (key, entry) in map {
+                let entry_path = property_path(path, key);
+                match object.properties.get(key) {
+                    Some(property_schema) => self.check(property_schema, entry, &entry_path, out),
+                    None => {
+                        if let Some(additional) = &object.additional_properties {
+                            if matches!(additional.as_ref(), Schema::Bool(false)) {
+                                push(out, &entry_path, "is not a recognised property");
+                            } else {
+                                self.check(additional, entry, &entry_path, out);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(subschemas) = &schema.subschemas {
+            if let Some(all_of) = &subschemas.all_of {
+                for branch in all_of {
+                    self.check(branch, value, path, out);
+                }
+            }
+            if let Some(any_of) = &subschemas.any_of {
+                self.check_alternatives(any_of, value, path, false, out);
+            }
+            if let Some(one_of) = &subschemas.one_of {
+                self.check_alternatives(one_of, value, path, true, out);
+            }
+        }
+    }
+
+    /// When no branch matches, the errors of the branch the value was most
+    /// plausibly aimed at are reported: the one whose tag property matches,
+    /// otherwise the only one whose `type` fits.
+    fn check_alternatives(
+        &self,
+        branches: &[Schema],
+        value: &Value,
+        path: &str,
+        exactly_one: bool,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let results: Vec<Vec<SchemaViolation>> = branches
+            .iter()
+            .map(|branch| {
+                let mut violations = Vec::new();
+                self.check(branch, value, path, &mut violations);
+                violations
+            })
+            .collect();
+
+        let passing = results.iter().filter(|violations| violations.is_empty()).count();
+        if passing == 1 || (passing > 1 && !exactly_one) {
+            return;
+        }
+        if passing > 1 {
+            push(out, path, "matches more than one allowed variant");
+            return;
+        }
+
+        let tagged: Vec<usize> = branches
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| self.tag_matches(branch, value) == Some(true))
+            .map(|(index, _)| index)
+            .collect();
+        if let [index] = tagged[..] {
+            out.extend(results[index].iter().cloned());
+            return;
+        }
+
+        if let Some((tag, expected)) = self.shared_tag(branches) {
+            if let Some(actual) = value.get(&tag) {
+                push(
+                    out,
+                    &property_path(path, &tag),
+                    format!("expected one of {}, found {actual}", list_values(&expected)),
+                );
+                return;
+            }
+        }
+
+        let plausible: Vec<usize> = branches
+            .iter()
+            .enumerate()
+            .filter(|(_, branch)| self.type_fits(branch, value))
+            .map(|(index, _)| index)
+            .collect();
+        if let [index] = plausible[..] {
+            out.extend(results[index].iter().cloned());
+            return;
+        }
+
+        push(out, path, "does not match any allowed variant");
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&Schema> {
+        let name = reference.strip_prefix("#/definitions/")?;
+        self.root.definitions.get(name)
+    }
+
+    fn object_of<'s>(&'s self, schema: &'s Schema) -> Option<&'s SchemaObject> {
+        match schema {
+            Schema::Object(object) => match &object.reference {
+                Some(reference) => self.resolve(reference).and_then(|target| self.object_of(target)),
+                None => Some(object),
+            },
+            Schema::Bool(_) => None,
+        }
+    }
+
+    /// The property pinned to a single string value, as serde's internally
+    /// tagged enums produce (`"type": { "enum": ["mouse-move"] }`).
+    fn tag_of(&self, schema: &Schema) -> Option<(String, Value)> {
+        let object = self.object_of(schema)?.object.as_ref()?;
+        object.properties.iter().find_map(|(name, property)| {
+            let property = self.object_of(property)?;
+            let pinned = match (&property.const_value, &property.enum_values) {
+                (Some(value), _) => value.clone(),
+                (None, Some(values)) if values.len() == 1 => values[0].clone(),
+                _ => return None,
+            };
+            object.required.contains(name).then(|| (name.clone(), pinned))
+        })
+    }
+
+    fn tag_matches(&self, schema: &Schema, value: &Value) -> Option<bool> {
+        let (name, pinned) = self.tag_of(schema)?;
+        Some(value.get(&name) == Some(&pinned))
+    }
+
+    fn shared_tag(&self, branches: &[Schema]) -> Option<(String, Vec<Value>)> {
+        let mut tag_name: Option<String> = None;
+        let mut expected = Vec::new();
+        for branch in branches {
+            let (name, pinned) = self.tag_of(branch)?;
+            if tag_name.get_or_insert_with(|| name.clone()) != &name {
+                return None;
+            }
+            expected.push(pinned);
+        }
+        tag_name.map(|name| (name, expected))
+    }
+
+    fn type_fits(&self, schema: &Schema, value: &Value) -> bool {
+        match self.object_of(schema) {
+            Some(object) => object
+                .instance_type
+                .as_ref()
+                .is_none_or(|types| matches_type(types, value)),
+            None => matches!(schema, Schema::Bool(true)),
+        }
+    }
+}
+
+fn check_format(format: &str, value: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+    if format == KEY_STROKE_FORMAT {
+        if let Some(label) = value.as_str() {
+            if let Err(error) = KeyStroke::parse(label) {
+                push(out, path, error);
+            }
+        }
+        return;
+    }
+
+    let (minimum, maximum): (i128, i128) = match format {
+        "uint8" => (0, u8::MAX.into()),
+        "uint16" => (0, u16::MAX.into()),
+        "uint32" => (0, u32::MAX.into()),
+        "uint64" | "uint" => (0, u64::MAX.into()),
+        "int8" => (i8::MIN.into(), i8::MAX.into()),
+        "int16" => (i16::MIN.into(), i16::MAX.into()),
+        "int32" => (i32::MIN.into(), i32::MAX.into()),
+        "int64" | "int" => (i64::MIN.into(), i64::MAX.into()),
+        _ => return,
+    };
+    let actual = value
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| value.as_u64().map(i128::from));
+    if let Some(actual) = actual {
+        if actual < minimum || actual > maximum {
+            push(out, path, format!("{actual} does not fit in {format}"));
+        }
+    }
+}
+
+fn matches_type(types: &SingleOrVec<InstanceType>, value: &Value) -> bool {
+    match types {
+        SingleOrVec::Single(instance_type) => matches_instance_type(instance_type, value),
+        SingleOrVec::Vec(instance_types) => instance_types
+            .iter()
+            .any(|instance_type| matches_instance_type(instance_type, value)),
+    }
+}
+
+fn matches_instance_type(instance_type: &InstanceType, value: &Value) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => value.is_i64() || value.is_u64(),
+    }
+}
+
+fn describe_types(types: &SingleOrVec<InstanceType>) -> String {
+    let names: Vec<&str> = match types {
+        SingleOrVec::Single(instance_type) => vec![instance_type_name(instance_type)],
+        SingleOrVec::Vec(instance_types) => instance_types.iter().map(instance_type_name).collect(),
+    };
+    names.join(" or ")
+}
+
+fn instance_type_name(instance_type: &InstanceType) -> &'static str {
+    match instance_type {
+        InstanceType::Null => "null",
+        InstanceType::Boolean => "boolean",
+        InstanceType::Object => "object",
+        InstanceType::Array => "array",
+        InstanceType::Number => "number",
+        InstanceType::String => "string",
+        InstanceType::Integer => "integer",
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".into(),
+        Value::Bool(_) => "boolean".into(),
+        Value::Number(number) if number.is_f64() => format!("number {number}"),
+        Value::Number(number) => format!("integer {number}"),
+        Value::String(_) => "string".into(),
+        Value::Array(_) => "array".into(),
+        Value::Object(_) => "object".into(),
+    }
+}
+
+fn list_values(values: &[Value]) -> String {
+    let rendered: Vec<String> = values.iter().map(Value::to_string).collect();
+    rendered.join(", ")
+}
+
+fn property_path(path: &str, key: &str) -> String {
+    let is_identifier = !key.is_empty()
+        && !key.starts_with(|ch: char| ch.is_ascii_digit())
+        && key.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    if is_identifier {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}[{}]", Value::from(key))
+    }
+}
+
+fn push(out: &mut Vec<SchemaViolation>, path: &str, message: impl Into<String>) {
+    out.push(SchemaViolation {
+        path: path.to_string(),
+        message: message.into(),
+    });
+}
+
+/// Returns the named schema (default: the macro file schema) as pretty JSON.
+/// Saving it is left to the caller, so the webview cannot pick a path to write.
+#[tauri::command]
+pub fn export_json_schema(name: Option<String>) -> Result<String, String> {
+    let name = name.unwrap_or_else(|| MACRO_FILE_SCHEMA.to_string());
+    let schema = schema_by_name(&name)
+        .ok_or_else(|| format!("unknown schema `{name}` (expected one of {})", SCHEMA_NAMES.join(", ")))?;
+    serde_json::to_string_pretty(&schema).map_err(|error| format!("failed to serialize schema: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document(events: Value) -> Value {
+        json!({
+            "schema_version": 1,
+            "macros": [{ "id": "m1", "name": "Saved", "events": events }],
+        })
+    }
+
+    fn event(id: &str, kind: Value) -> Value {
+        json!({ "id": id, "offsetMs": 0, "kind": kind })
+    }
+
+    fn violations(value: &Value) -> Vec<String> {
+        validate_against(&macro_file_schema(), value).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn accepts_a_valid_document() {
+        let events = json!([
+            event("e1", json!({ "type": "mouse-move", "x": 1, "y": -2 })),
+            event("e2", json!({ "type": "key-down", "key": "Ctrl+S" })),
+        ]);
+        assert_eq!(violations(&document(events)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_bad_events_at_their_path() {
+        let move_to = json!({ "type": "mouse-move", "x": 1, "y": 2 });
+        let cases = [
+            (
+                json!({ "id": "e1", "offsetMs": "soon", "kind": move_to }),
+                "$.macros[0].events[1].offsetMs: expected integer, found string",
+            ),
+            (
+                event("e1", json!({ "type": "mouse-move", "x": 1.5, "y": 2 })),
+                "$.macros[0].events[1].kind.x: expected integer, found number 1.5",
+            ),
+            (
+                event("e1", json!({ "type": "mouse-down", "button": "sideways" })),
+                "$.macros[0].events[1].kind.button: expected one of \"left\", \"right\", \"middle\", \"x1\", \"x2\", \
+                 found \"sideways\"",
+            ),
+            // oneOf: an unknown tag is reported on the tag itself...
+            (
+                event("e1", json!({ "type": "mouse-jump", "x": 1, "y": 2 })),
+                "$.macros[0].events[1].kind.type: expected one of \"mouse-move\", \"mouse-down\", \"mouse-up\", \
+                 \"key-down\", \"key-up\", \"scroll\", found \"mouse-jump\"",
+            ),
+            // ...a known tag by the errors of its own variant...
+            (
+                event("e1", json!({ "type": "mouse-move", "x": 1 })),
+                "$.macros[0].events[1].kind: missing required property `y`",
+            ),
+            (
+                event("e1", json!({ "type": "key-up", "key": "Ctrl+Nope" })),
+                "$.macros[0].events[1].kind.key: unknown key `Nope` in key label `Ctrl+Nope`",
+            ),
+            // ...and a value no variant could be meant for as a whole.
+            (
+                event("e1", json!("mouse-move")),
+                "$.macros[0].events[1].kind: does not match any allowed variant",
+            ),
+            (
+                json!({ "offsetMs": 0, "kind": move_to }),
+                "$.macros[0].events[1]: missing required property `id`",
+            ),
+        ];
+        for (bad, expected) in cases {
+            let scroll = event("e0", json!({ "type": "scroll", "delta_x": 0, "delta_y": 1 }));
+            assert_eq!(violations(&document(json!([scroll, bad]))), [expected]);
+        }
+    }
+
+    #[test]
+    fn reports_every_violation_in_document_order() {
+        let value = json!({
+            "schema_version": "1",
+            "macros": [
+                { "id": "m1", "events": [] },
+                { "id": "m2", "name": "Two", "scrollDeltaMode": "sideways", "events": {} },
+            ],
+        });
+        assert_eq!(
+            violations(&value),
+            [
+                "$.macros[0]: missing required property `name`",
+                "$.macros[1].events: expected array, found object",
+                "$.macros[1].scrollDeltaMode: expected one of \"legacy\", \"native\", found \"sideways\"",
+                "$.schema_version: expected integer, found string",
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_keys_that_are_not_identifiers() {
+        assert_eq!(property_path("$", "events"), "$.events");
+        assert_eq!(property_path("$", "delta-x"), "$[\"delta-x\"]");
+        assert_eq!(property_path("$", "1st"), "$[\"1st\"]");
+    }
+}
@@ -0,0 +1,273 @@
+//! X11 keysym names, shared by the xdotool exporter and the xmacro/xdotool
+//! importers.
+
+use crate::types::KeyCode;
+
+/// Named keysyms. The first entry for a key is the name it is exported as;
+/// later entries are accepted aliases.
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("Return", KeyCode::Enter),
+    ("KP_Enter", KeyCode::Enter),
+    ("Tab", KeyCode::Tab),
+    ("ISO_Left_Tab", KeyCode::Tab),
+    ("space", KeyCode::Space),
+    ("BackSpace", KeyCode::Backspace),
+    ("Escape", KeyCode::Escape),
+    ("Caps_Lock", KeyCode::CapsLock),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("Prior", KeyCode::PageUp),
+    ("Page_Up", KeyCode::PageUp),
+    ("Next", KeyCode::PageDown),
+    ("Page_Down", KeyCode::PageDown),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Shift_L", KeyCode::Shift),
+    ("Shift_R", KeyCode::Shift),
+    ("shift", KeyCode::Shift),
+    ("Control_L", KeyCode::Ctrl),
+    ("Control_R", KeyCode::Ctrl),
+    ("ctrl", KeyCode::Ctrl),
+    ("control", KeyCode::Ctrl),
+    ("Alt_L", KeyCode::Alt),
+    ("Alt_R", KeyCode::Alt),
+    ("ISO_Level3_Shift", KeyCode::Alt),
+    ("alt", KeyCode::Alt),
+    ("Super_L", KeyCode::Meta),
+    ("Super_R", KeyCode::Meta),
+    ("Meta_L", KeyCode::Meta),
+    ("Meta_R", KeyCode::Meta),
+    ("super", KeyCode::Meta),
+    ("meta", KeyCode::Meta),
+    ("Print", KeyCode::PrintScreen),
+    ("Scroll_Lock", KeyCode::ScrollLock),
+    ("Pause", KeyCode::Pause),
+    ("Num_Lock", KeyCode::NumLock),
+    ("KP_Add", KeyCode::Numpad('+')),
+    ("KP_Subtract", KeyCode::Numpad('-')),
+    ("KP_Multiply", KeyCode::Numpad('*')),
+    ("KP_Divide", KeyCode::Numpad('/')),
+];
+
+/// Keysym names of the printable ASCII characters that are not alphanumeric.
+const SYMBOL_KEYS: &[(char, &str)] = &[
+    ('`', "grave"),
+    ('-', "minus"),
+    ('=', "equal"),
+    ('[', "bracketleft"),
+    (']', "bracketright"),
+    (';', "semicolon"),
+    ('\'', "apostrophe"),
+    ('\\', "backslash"),
+    (',', "comma"),
+    ('.', "period"),
+    ('/', "slash"),
+    ('!', "exclam"),
+    ('@', "at"),
+    ('#', "numbersign"),
+    ('$', "dollar"),
+    ('%', "percent"),
+    ('^', "asciicircum"),
+    ('&', "ampersand"),
+    ('*', "asterisk"),
+    ('(', "parenleft"),
+    (')', "parenright"),
+    ('_', "underscore"),
+    ('+', "plus"),
+    ('{', "braceleft"),
+    ('}', "braceright"),
+    (':', "colon"),
+    ('"', "quotedbl"),
+    ('<', "less"),
+    ('>', "greater"),
+    ('?', "question"),
+    ('|', "bar"),
+    ('~', "asciitilde"),
+];
+
+/// Keysym name xdotool accepts for `code`, or `None` when X has no keysym
+/// for it.
+pub fn keysym_name(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::F(index) => Some(format!("F{index}")),
+        KeyCode::Numpad(digit) if digit.is_ascii_digit() => Some(format!("KP_{digit}")),
+        KeyCode::Char(ch) => Some(char_keysym_name(ch)),
+        KeyCode::Fn | KeyCode::Raw(_) | KeyCode::Unrecognized => None,
+        _ => NAMED_KEYS
+            .iter()
+            .find(|(_, key)| *key == code)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+fn char_keysym_name(ch: char) -> String {
+    if ch.is_ascii_alphanumeric() {
+        return ch.to_string();
+    }
+    match SYMBOL_KEYS.iter().find(|(symbol, _)| *symbol == ch) {
+        Some((_, name)) => name.to_string(),
+        None => format!("U{:04X}", ch as u32),
+    }
+}
+
+/// Resolves a keysym name (`Return`, `Control_L`, `a`, `exclam`, `U20AC`, ...)
+/// onto a key. Single characters map like typed text, so `A` is the `a` key;
+/// callers decide whether it needs Shift.
+pub fn key_from_keysym(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::from_char(ch));
+    }
+    if let Some((_, key)) = NAMED_KEYS.iter().find(|(candidate, _)| *candidate == name) {
+        return Some(*key);
+    }
+    if let Some((ch, _)) = SYMBOL_KEYS.iter().find(|(_, candidate)| *candidate == name) {
+        return Some(KeyCode::Char(*ch));
+    }
+    if let Some(index) = name.strip_prefix('F').and_then(|rest| rest.parse::<u8>().ok()) {
+        return (1..=24).contains(&index).then_some(KeyCode::F(index));
+    }
+    if let Some(digit) = name.strip_prefix("KP_").and_then(single_digit) {
+        return Some(KeyCode::Numpad(digit));
+    }
+    if let Some(value) = name.strip_prefix('U').and_then(|hex| u32::from_str_radix(hex, 16).ok()) {
+        return char::from_u32(value).map(KeyCode::from_char);
+    }
+    if let Some(value) = name.strip_prefix("0x").and_then(|hex| u32::from_str_radix(hex, 16).ok()) {
+        return key_from_keysym_value(value);
+    }
+    // Case-insensitive fallback for hand-written scripts (`ctrl`, `RETURN`).
+    NAMED_KEYS
+        .iter()
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+/// Resolves a numeric keysym, as written by `KeySymPress` lines.
+pub fn key_from_keysym_value(value: u32) -> Option<KeyCode> {
+    let key = match value {
+        0x20..=0x7e | 0xa0..=0xff => KeyCode::from_char(char::from_u32(value)?),
+        0x0100_0000..=0x0110_ffff => KeyCode::from_char(char::from_u32(value - 0x0100_0000)?),
+        0xff08 => KeyCode::Backspace,
+        0xff09 | 0xfe20 => KeyCode::Tab,
+        0xff0d | 0xff8d => KeyCode::Enter,
+        0xff13 => KeyCode::Pause,
+        0xff14 => KeyCode::ScrollLock,
+        0xff1b => KeyCode::Escape,
+        0xff50 => KeyCode::Home,
+        0xff51 => KeyCode::Left,
+        0xff52 => KeyCode::Up,
+        0xff53 => KeyCode::Right,
+        0xff54 => KeyCode::Down,
+        0xff55 => KeyCode::PageUp,
+        0xff56 => KeyCode::PageDown,
+        0xff57 => KeyCode::End,
+        0xff61 => KeyCode::PrintScreen,
+        0xff63 => KeyCode::Insert,
+        0xff7f => KeyCode::NumLock,
+        0xffaa => KeyCode::Numpad('*'),
+        0xffab => KeyCode::Numpad('+'),
+        0xffad => KeyCode::Numpad('-'),
+        0xffaf => KeyCode::Numpad('/'),
+        0xffb0..=0xffb9 => KeyCode::Numpad(char::from_digit(value - 0xffb0, 10)?),
+        0xffbe..=0xffd5 => KeyCode::F((value - 0xffbe + 1) as u8),
+        0xffe1 | 0xffe2 => KeyCode::Shift,
+        0xffe3 | 0xffe4 => KeyCode::Ctrl,
+        0xffe5 => KeyCode::CapsLock,
+        0xffe9 | 0xffea | 0xfe03 => KeyCode::Alt,
+        0xffe7 | 0xffe8 | 0xffeb | 0xffec => KeyCode::Meta,
+        0xffff => KeyCode::Delete,
+        _ => return None,
+    };
+    Some(key)
+}
+
+fn single_digit(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if ch.is_ascii_digit() => Some(ch),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_names_resolve_to_the_same_key() {
+        let mut codes: Vec<KeyCode> = NAMED_KEYS.iter().map(|(_, key)| *key).collect();
+        codes.extend(SYMBOL_KEYS.iter().map(|(ch, _)| KeyCode::Char(*ch)));
+        codes.extend(('a'..='z').chain('0'..='9').map(KeyCode::Char));
+        codes.extend(['é', '€'].map(KeyCode::Char));
+        codes.extend((1..=24).map(KeyCode::F));
+        codes.extend(('0'..='9').map(KeyCode::Numpad));
+        for code in codes {
+            let name = keysym_name(code).unwrap_or_else(|| panic!("{code:?}"));
+            assert_eq!(key_from_keysym(&name), Some(code), "{name}");
+        }
+    }
+
+    #[test]
+    fn names_keys_the_way_xdotool_does() {
+        let cases = [
+            (KeyCode::Enter, Some("Return")),
+            (KeyCode::Shift, Some("Shift_L")),
+            (KeyCode::Meta, Some("Super_L")),
+            (KeyCode::PageDown, Some("Next")),
+            (KeyCode::Char('"'), Some("quotedbl")),
+            (KeyCode::Char('{'), Some("braceleft")),
+            (KeyCode::Char('`'), Some("grave")),
+            (KeyCode::Char('€'), Some("U20AC")),
+            (KeyCode::Numpad('/'), Some("KP_Divide")),
+            (KeyCode::Numpad('4'), Some("KP_4")),
+            (KeyCode::Fn, None),
+            (KeyCode::Raw(57), None),
+            (KeyCode::Unrecognized, None),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(keysym_name(code).as_deref(), expected, "{code:?}");
+        }
+    }
+
+    #[test]
+    fn resolves_aliases_and_hand_written_names() {
+        let cases = [
+            ("KP_Enter", Some(KeyCode::Enter)),
+            ("Control_R", Some(KeyCode::Ctrl)),
+            ("ctrl", Some(KeyCode::Ctrl)),
+            ("RETURN", Some(KeyCode::Enter)),
+            ("A", Some(KeyCode::Char('a'))),
+            ("0x61", Some(KeyCode::Char('a'))),
+            ("0xff0d", Some(KeyCode::Enter)),
+            ("F25", None),
+            ("KP_10", None),
+            ("Hyper_L", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(key_from_keysym(name), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn resolves_numeric_keysyms() {
+        let cases = [
+            (0x41, Some(KeyCode::Char('a'))),
+            (0xe9, Some(KeyCode::Char('é'))),
+            (0x0100_20ac, Some(KeyCode::Char('€'))),
+            (0xffb7, Some(KeyCode::Numpad('7'))),
+            (0xffbe, Some(KeyCode::F(1))),
+            (0xffd5, Some(KeyCode::F(24))),
+            (0xffeb, Some(KeyCode::Meta)),
+            (0xfe03, Some(KeyCode::Alt)),
+            (0x1008ff13, None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(key_from_keysym_value(value), expected, "{value:#x}");
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use rdev::{Event as RdevEvent, EventType, Key as RdevKey};
+use tauri::{Emitter, State, Window};
+
+use crate::{
+    app_state::AppState,
+    input_sink::InputSink,
+    macro_validation::ScreenArea,
+    recorder::key_code_from_rdev,
+    types::{KeyCode, KillSwitchSettings, KillSwitchTripped, MouseButton, ScreenCorner},
+};
+
+/// How long an injected input waits for the hook to report it back before it
+/// is forgotten.
+const INJECTION_WINDOW: Duration = Duration::from_millis(500);
+
+/// Hook positions within this many pixels of an injected move count as that
+/// move.
+const MOVE_TOLERANCE_PX: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Injected {
+    Move { x: f64, y: f64 },
+    Key { code: KeyCode, pressed: bool },
+}
+
+/// Inputs MacroArc injected recently, so the hook can tell them apart from
+/// the user's own.
+#[derive(Debug, Default)]
+pub struct InjectionLog {
+    entries: Mutex<VecDeque<(Instant, Injected)>>,
+}
+
+impl InjectionLog {
+    fn note(&self, input: Injected) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        while entries.front().is_some_and(|(at, _)| now.duration_since(*at) > INJECTION_WINDOW) {
+            entries.pop_front();
+        }
+        entries.push_back((now, input));
+    }
+
+    /// Removes and reports the oldest recent entry `matches` accepts.
+    fn claim<P: Fn(&Injected) -> bool>(&self, matches: P) -> bool {
+        let now = Instant::now();
+        let mut entries = self.entries.lock();
+        let found = entries
+            .iter()
+            .position(|(at, input)| now.duration_since(*at) <= INJECTION_WINDOW && matches(input));
+        match found {
+            Some(index) => {
+                entries.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Sink wrapper that notes every cursor move and key it injects in an
+/// `InjectionLog` before passing it on.
+pub struct MarkedSink<S> {
+    inner: S,
+    log: Arc<InjectionLog>,
+}
+
+impl<S: InputSink> MarkedSink<S> {
+    pub fn new(inner: S, log: Arc<InjectionLog>) -> Self {
+        Self { inner, log }
+    }
+}
+
+impl<S: InputSink> InputSink for MarkedSink<S> {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.log.note(Injected::Move {
+            x: f64::from(x),
+            y: f64::from(y),
+        });
+        self.inner.mouse_move_to(x, y);
+    }
+
+    fn mouse_down(&mut self, button: MouseButton) {
+        self.inner.mouse_down(button);
+    }
+
+    fn mouse_up(&mut self, button: MouseButton) {
+        self.inner.mouse_up(button);
+    }
+
+    fn key_down(&mut self, key: KeyCode) {
+        self.log.note(Injected::Key {
+            code: key,
+            pressed: true,
+        });
+        self.inner.key_down(key);
+    }
+
+    fn key_up(&mut self, key: KeyCode) {
+        self.log.note(Injected::Key {
+            code: key,
+            pressed: false,
+        });
+        self.inner.key_up(key);
+    }
+
+    fn scroll(&mut self, delta_x: i32, delta_y: i32) {
+        self.inner.scroll(delta_x, delta_y);
+    }
+
+    fn mouse_click(&mut self, button: MouseButton) {
+        self.inner.mouse_click(button);
+    }
+}
+
+/// Why the kill switch fired.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KillReason {
+    MouseMoved { distance: f64 },
+    Escape,
+    Corner(ScreenCorner),
+}
+
+impl KillReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KillReason::MouseMoved { .. } => "mouse-moved",
+            KillReason::Escape => "escape",
+            KillReason::Corner(_) => "corner",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            KillReason::MouseMoved { distance } => format!("mouse moved {distance:.0} px by hand"),
+            KillReason::Escape => "Escape pressed".into(),
+            KillReason::Corner(corner) => format!("cursor pushed into the {} corner", corner.as_str()),
+        }
+    }
+}
+
+type TripHandler = Box<dyn Fn(KillReason) + Send + Sync>;
+
+struct Arming {
+    id: u64,
+    on_trip: TripHandler,
+}
+
+/// Watches real input while playback or the autoclicker runs and aborts them
+/// when the user takes over: moving the mouse further than the threshold,
+/// pressing Escape or pushing the cursor into the configured screen corner.
+/// Input found in the `InjectionLog` is ignored.
+#[derive(Default)]
+pub struct KillSwitch {
+    settings: Mutex<KillSwitchSettings>,
+    log: Arc<InjectionLog>,
+    armings: Mutex<Vec<Arming>>,
+    screen: Mutex<Option<ScreenArea>>,
+    anchor: Mutex<Option<(f64, f64)>>,
+    next_id: AtomicU64,
+    subscribed: AtomicBool,
+}
+
+impl KillSwitch {
+    pub fn settings(&self) -> KillSwitchSettings {
+        self.settings.lock().clone()
+    }
+
+    pub fn set_settings(&self, settings: KillSwitchSettings) {
+        *self.settings.lock() = settings;
+    }
+
+    pub fn injection_log(&self) -> Arc<InjectionLog> {
+        self.log.clone()
+    }
+
+    /// Calls `on_trip` the first time the switch fires while the returned
+    /// guard is alive. `screen` is the area whose corners count for the
+    /// corner fail-safe.
+    pub fn arm<F>(self: &Arc<Self>, screen: Option<ScreenArea>, on_trip: F) -> KillSwitchGuard
+    where
+        F: Fn(KillReason) + Send + Sync + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut armings = self.armings.lock();
+        if armings.is_empty() {
+            *self.anchor.lock() = None;
+        }
+        armings.push(Arming {
+            id,
+            on_trip: Box::new(on_trip),
+        });
+        *self.screen.lock() = screen;
+
+        KillSwitchGuard {
+            switch: self.clone(),
+            id,
+        }
+    }
+
+    pub fn is_armed(&self) -> bool {
+        !self.armings.lock().is_empty()
+    }
+
+    /// Feeds one event from the OS hook through the kill switch.
+    pub fn observe(&self, event: &RdevEvent) {
+        if !self.is_armed() {
+            return;
+        }
+
+        let settings = self.settings();
+        let reason = match event.event_type {
+            EventType::MouseMove { x, y } => self.observe_move(&settings, x, y),
+            EventType::KeyPress(key) => self.observe_key(&settings, key, true),
+            EventType::KeyRelease(key) => self.observe_key(&settings, key, false),
+            _ => None,
+        };
+
+        if let Some(reason) = reason {
+            self.trip(reason);
+        }
+    }
+
+    fn observe_move(&self, settings: &KillSwitchSettings, x: f64, y: f64) -> Option<KillReason> {
+        let injected = self.log.claim(|input| match input {
+            Injected::Move { x: target_x, y: target_y } => {
+                (target_x - x).abs() <= MOVE_TOLERANCE_PX && (target_y - y).abs() <= MOVE_TOLERANCE_PX
+            }
+            Injected::Key { .. } => false,
+        });
+        let mut anchor = self.anchor.lock();
+        if injected {
+            *anchor = Some((x, y));
+            return None;
+        }
+
+        if let (Some(corner), Some(screen)) = (settings.corner, *self.screen.lock()) {
+            if in_corner(&screen, corner, settings.corner_size_px, x, y) {
+                return Some(KillReason::Corner(corner));
+            }
+        }
+
+        let threshold = f64::from(settings.mouse_threshold_px?);
+        let (anchor_x, anchor_y) = *anchor.get_or_insert((x, y));
+        let distance = (x - anchor_x).hypot(y - anchor_y);
+        (distance > threshold).then_some(KillReason::MouseMoved { distance })
+    }
+
+    fn observe_key(&self, settings: &KillSwitchSettings, key: RdevKey, pressed: bool) -> Option<KillReason> {
+        let code = key_code_from_rdev(key);
+        let injected = self.log.claim(|input| {
+            *input
+                == Injected::Key {
+                    code,
+                    pressed,
+                }
+        });
+        (!injected && pressed && settings.escape && code == KeyCode::Escape).then_some(KillReason::Escape)
+    }
+
+    fn trip(&self, reason: KillReason) {
+        let armings = std::mem::take(&mut *self.armings.lock());
+        for arming in armings {
+            (arming.on_trip)(reason);
+        }
+    }
+
+    fn disarm(&self, id: u64) {
+        self.armings.lock().retain(|arming| arming.id != id);
+    }
+}
+
+/// Keeps a kill switch arming alive; dropping it disarms.
+pub struct KillSwitchGuard {
+    switch: Arc<KillSwitch>,
+    id: u64,
+}
+
+impl Drop for KillSwitchGuard {
+    fn drop(&mut self) {
+        self.switch.disarm(self.id);
+    }
+}
+
+fn in_corner(screen: &ScreenArea, corner: ScreenCorner, size: u32, x: f64, y: f64) -> bool {
+    let size = f64::from(size);
+    let left = f64::from(screen.x);
+    let top = f64::from(screen.y);
+    let right = left + f64::from(screen.width) - 1.0;
+    let bottom = top + f64::from(screen.height) - 1.0;
+
+    let near_left = x <= left + size;
+    let near_right = x >= right - size;
+    let near_top = y <= top + size;
+    let near_bottom = y >= bottom - size;
+
+    match corner {
+        ScreenCorner::TopLeft => near_top && near_left,
+        ScreenCorner::TopRight => near_top && near_right,
+        ScreenCorner::BottomLeft => near_bottom && near_left,
+        ScreenCorner::BottomRight => near_bottom && near_right,
+    }
+}
+
+/// Arms the kill switch for a run started from `window` if it is enabled,
+/// starting the OS hook on first use. `stop` aborts the run; `source` names it
+/// in the `macro://kill-switch` payload.
+pub(crate) fn arm_kill_switch<F>(
+    state: &AppState,
+    window: &Window,
+    source: &'static str,
+    stop: F,
+) -> Option<KillSwitchGuard>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    if !state.kill_switch.settings().enabled {
+        return None;
+    }
+
+    if !state.kill_switch.subscribed.swap(true, Ordering::Relaxed) {
+        let switch = state.kill_switch.clone();
+        state.input_hook.subscribe(move |event| switch.observe(event));
+    }
+    let error_window = window.clone();
+    state.input_hook.start(move |error| {
+        let _ = error_window.emit("macro://error", format!("Input hook error: {error}"));
+    });
+
+    let screen = window.primary_monitor().ok().flatten().map(|monitor| ScreenArea {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    });
+    let window = window.clone();
+    let guard = state.kill_switch.arm(screen, move |reason| {
+        stop();
+        let payload = KillSwitchTripped {
+            source: source.into(),
+            reason: reason.as_str().into(),
+            detail: reason.describe(),
+        };
+        let _ = window.emit("macro://kill-switch", payload);
+    });
+    Some(guard)
+}
+
+#[tauri::command]
+pub fn get_kill_switch_settings(state: State<'_, AppState>) -> KillSwitchSettings {
+    state.kill_switch.settings()
+}
+
+#[tauri::command]
+pub fn set_kill_switch_settings(state: State<'_, AppState>, settings: KillSwitchSettings) -> Result<(), String> {
+    if settings.mouse_threshold_px == Some(0) {
+        return Err("Kill switch mouse threshold must be at least 1 px".into());
+    }
+    state.kill_switch.set_settings(settings);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input_sink::NullSink,
+        input_source::{synthetic_event, InputSource, ScriptedSource},
+    };
+
+    const SCREEN: ScreenArea = ScreenArea {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    };
+
+    fn switch(settings: KillSwitchSettings) -> Arc<KillSwitch> {
+        let switch = Arc::new(KillSwitch::default());
+        switch.set_settings(KillSwitchSettings { enabled: true, ..settings });
+        switch
+    }
+
+    /// Arms `switch` and returns a guard plus the reasons it tripped for.
+    fn arm(switch: &Arc<KillSwitch>) -> (KillSwitchGuard, Arc<Mutex<Vec<KillReason>>>) {
+        let trips = Arc::new(Mutex::new(Vec::new()));
+        let recorded = trips.clone();
+        let guard = switch.arm(Some(SCREEN), move |reason| recorded.lock().push(reason));
+        (guard, trips)
+    }
+
+    fn feed(switch: &Arc<KillSwitch>, source: ScriptedSource) {
+        let observer = switch.clone();
+        source.listen(move |event| observer.observe(&event)).unwrap();
+    }
+
+    fn moved(x: f64, y: f64) -> EventType {
+        EventType::MouseMove { x, y }
+    }
+
+    #[test]
+    fn escape_by_hand_trips_once_while_armed() {
+        let switch = switch(KillSwitchSettings::default());
+        switch.observe(&synthetic_event(EventType::KeyPress(RdevKey::Escape), None));
+
+        let (_guard, trips) = arm(&switch);
+        feed(
+            &switch,
+            ScriptedSource::default()
+                .push(EventType::KeyRelease(RdevKey::Escape))
+                .push(EventType::KeyPress(RdevKey::KeyA))
+                .push(EventType::KeyPress(RdevKey::Escape))
+                .push(EventType::KeyPress(RdevKey::Escape)),
+        );
+        assert_eq!(*trips.lock(), [KillReason::Escape]);
+        assert!(!switch.is_armed(), "tripping disarms");
+    }
+
+    #[test]
+    fn injected_input_does_not_trip() {
+        let switch = switch(KillSwitchSettings {
+            mouse_threshold_px: Some(10),
+            ..KillSwitchSettings::default()
+        });
+        let (_guard, trips) = arm(&switch);
+        let mut sink = MarkedSink::new(NullSink, switch.injection_log());
+        sink.key_down(KeyCode::Escape);
+        sink.key_up(KeyCode::Escape);
+        sink.mouse_move_to(100, 100);
+        sink.mouse_move_to(500, 500);
+
+        feed(
+            &switch,
+            ScriptedSource::default()
+                .push(EventType::KeyPress(RdevKey::Escape))
+                .push(EventType::KeyRelease(RdevKey::Escape))
+                // The hook may report a position a pixel or two off.
+                .push(moved(101.5, 98.5))
+                .push(moved(500.0, 500.0)),
+        );
+        assert!(trips.lock().is_empty());
+
+        // Each injection is claimed once: a second Escape is the user's.
+        switch.observe(&synthetic_event(EventType::KeyPress(RdevKey::Escape), None));
+        assert_eq!(*trips.lock(), [KillReason::Escape]);
+    }
+
+    #[test]
+    fn moving_the_mouse_by_hand_trips_past_the_threshold() {
+        let switch = switch(KillSwitchSettings {
+            mouse_threshold_px: Some(10),
+            ..KillSwitchSettings::default()
+        });
+        let (_guard, trips) = arm(&switch);
+        MarkedSink::new(NullSink, switch.injection_log()).mouse_move_to(100, 100);
+
+        feed(
+            &switch,
+            ScriptedSource::default()
+                .push(moved(100.0, 100.0))
+                .push(moved(106.0, 108.0))
+                .push(moved(103.0, 100.0))
+                .push(moved(112.0, 100.0)),
+        );
+        assert_eq!(*trips.lock(), [KillReason::MouseMoved { distance: 12.0 }]);
+    }
+
+    #[test]
+    fn a_move_outside_the_tolerance_is_the_users() {
+        let switch = switch(KillSwitchSettings {
+            mouse_threshold_px: Some(3),
+            ..KillSwitchSettings::default()
+        });
+        let (_guard, trips) = arm(&switch);
+        let mut sink = MarkedSink::new(NullSink, switch.injection_log());
+        sink.mouse_move_to(100, 100);
+        sink.mouse_move_to(200, 100);
+
+        feed(&switch, ScriptedSource::default().push(moved(100.0, 100.0)).push(moved(204.0, 100.0)));
+        assert_eq!(*trips.lock(), [KillReason::MouseMoved { distance: 104.0 }]);
+    }
+
+    #[test]
+    fn pushing_into_the_configured_corner_trips() {
+        let switch = switch(KillSwitchSettings {
+            enabled: true,
+            mouse_threshold_px: None,
+            escape: false,
+            corner: Some(ScreenCorner::BottomRight),
+            corner_size_px: 4,
+        });
+        let (_guard, trips) = arm(&switch);
+
+        feed(
+            &switch,
+            ScriptedSource::default()
+                .push(EventType::KeyPress(RdevKey::Escape))
+                .push(moved(0.0, 0.0))
+                .push(moved(1914.0, 1079.0))
+                .push(moved(1915.0, 1075.0)),
+        );
+        assert_eq!(*trips.lock(), [KillReason::Corner(ScreenCorner::BottomRight)]);
+    }
+
+    #[test]
+    fn corners_are_measured_from_the_screen_edges() {
+        let screen = ScreenArea {
+            x: -1920,
+            y: 100,
+            width: 1920,
+            height: 1080,
+        };
+        let cases = [
+            (ScreenCorner::TopLeft, -1920.0, 100.0, true),
+            (ScreenCorner::TopLeft, -1915.0, 105.0, true),
+            (ScreenCorner::TopLeft, -1914.0, 100.0, false),
+            (ScreenCorner::TopRight, -1.0, 100.0, true),
+            (ScreenCorner::TopRight, -6.0, 100.0, true),
+            (ScreenCorner::TopRight, -7.0, 100.0, false),
+            (ScreenCorner::BottomLeft, -1920.0, 1179.0, true),
+            (ScreenCorner::BottomLeft, -1920.0, 1173.0, false),
+            (ScreenCorner::BottomRight, -1.0, 1179.0, true),
+            (ScreenCorner::BottomRight, -1.0, 100.0, false),
+        ];
+        for (corner, x, y, expected) in cases {
+            assert_eq!(in_corner(&screen, corner, 5, x, y), expected, "{corner:?} ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn dropping_the_guard_disarms() {
+        let switch = switch(KillSwitchSettings::default());
+        let (guard, trips) = arm(&switch);
+        let (_other, other_trips) = arm(&switch);
+        drop(guard);
+        assert!(switch.is_armed());
+
+        switch.observe(&synthetic_event(EventType::KeyPress(RdevKey::Escape), None));
+        assert!(trips.lock().is_empty());
+        assert_eq!(*other_trips.lock(), [KillReason::Escape]);
+    }
+}
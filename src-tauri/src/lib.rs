@@ -1,15 +1,75 @@
 mod types;
 mod autoclicker;
+mod cron;
+mod document;
+mod emergency_stop;
+mod humanize;
+mod injection_gate;
+mod input_sink;
+mod input_hook;
+mod input_source;
+mod json_schema;
+mod keysym;
+mod kill_switch;
+mod macro_export;
+mod macro_import;
 mod macro_player;
+mod macro_queue;
+mod macro_script;
+mod macro_store;
+mod macro_validation;
+mod mouse_path;
 mod overlay;
+mod playback_clock;
+mod playback_range;
 mod recorder;
+mod scheduler;
 mod app_state;
 
 use tauri::{Manager, State, WindowEvent};
 use tauri_plugin_global_shortcut::Builder as GlobalShortcutBuilder;
 
 pub use autoclicker::{start_autoclicker, stop_autoclicker};
-pub use macro_player::{play_macro, stop_macro_playback};
+pub use emergency_stop::{get_emergency_stop_hotkey, set_emergency_stop_hotkey, trigger_emergency_stop};
+pub use json_schema::export_json_schema;
+pub use kill_switch::{get_kill_switch_settings, set_kill_switch_settings};
+pub use macro_export::export_macro;
+pub use macro_import::import_recording_log;
+pub use macro_player::{
+    debug_continue_macro,
+    debug_run_to_offset,
+    debug_step_macro,
+    get_playback_channels,
+    get_playback_limits,
+    pause_macro_playback,
+    play_macro,
+    resume_macro_playback,
+    set_macro_breakpoints,
+    set_playback_limits,
+    stop_macro_playback,
+};
+pub use macro_queue::{
+    clear_queue,
+    dequeue_macro,
+    enqueue_macro,
+    get_queue_state,
+    reorder_queue,
+    set_queue_settings,
+    start_queue,
+    stop_queue,
+};
+pub use macro_script::{compile_macro_script, decompile_macro_script};
+pub use macro_store::{
+    delete_macro,
+    duplicate_macro,
+    get_macro,
+    import_macros,
+    list_macros,
+    macro_library_error,
+    rename_macro,
+    replace_macros,
+    save_macro,
+};
 pub use overlay::{
     close_overlay_window,
     disable_overlay_windows,
@@ -18,8 +78,12 @@ pub use overlay::{
     sync_overlay_windows,
 };
 pub use recorder::{start_recording, stop_recording};
+pub use scheduler::{delete_schedule, list_schedules, save_schedule};
 
 use app_state::AppState;
+use emergency_stop::{register_emergency_hotkey, DEFAULT_EMERGENCY_STOP_HOTKEY};
+use macro_store::MACROS_FILENAME;
+use scheduler::SCHEDULES_FILENAME;
 use types::FrontendStatus;
 
 #[tauri::command]
@@ -43,7 +107,37 @@ pub fn run() {
         .plugin(GlobalShortcutBuilder::new().build())
         .manage(AppState::default())
         .setup(|app| {
+            match app.path().app_local_data_dir() {
+                Ok(data_dir) => {
+                    let state = app.state::<AppState>();
+                    let opened = state.macro_store.lock().open(data_dir.join(MACROS_FILENAME));
+                    match opened {
+                        Ok(warnings) => {
+                            for warning in warnings {
+                                eprintln!("macro library: {warning}");
+                            }
+                        }
+                        Err(error) => eprintln!("macro library unavailable, changes will not be saved: {error}"),
+                    }
+                    if let Err(error) = state.scheduler.open(data_dir.join(SCHEDULES_FILENAME)) {
+                        eprintln!("schedules unavailable, changes will not be saved: {error}");
+                    }
+                }
+                Err(error) => {
+                    eprintln!("failed to resolve app data directory: {error}");
+                    app.state::<AppState>()
+                        .macro_store
+                        .lock()
+                        .set_open_error(format!("failed to resolve app data directory: {error}"));
+                }
+            }
+
             let app_handle = app.handle();
+            app.state::<AppState>().scheduler.start(app_handle.clone());
+            if let Err(error) = register_emergency_hotkey(app_handle, Some(DEFAULT_EMERGENCY_STOP_HOTKEY)) {
+                eprintln!("emergency stop hotkey unavailable: {error}");
+            }
+
             if let Some(window) = app.get_webview_window("main") {
                 let handle_clone = app_handle.clone();
                 window.on_window_event(move |event| match event {
@@ -60,6 +154,26 @@ pub fn run() {
             stop_recording,
             play_macro,
             stop_macro_playback,
+            get_playback_channels,
+            pause_macro_playback,
+            resume_macro_playback,
+            debug_step_macro,
+            debug_continue_macro,
+            debug_run_to_offset,
+            set_macro_breakpoints,
+            get_playback_limits,
+            set_playback_limits,
+            get_queue_state,
+            enqueue_macro,
+            dequeue_macro,
+            reorder_queue,
+            clear_queue,
+            set_queue_settings,
+            start_queue,
+            stop_queue,
+            list_schedules,
+            save_schedule,
+            delete_schedule,
             start_autoclicker,
             stop_autoclicker,
             app_status,
@@ -67,7 +181,26 @@ pub fn run() {
             disable_overlay_windows,
             sync_overlay_windows,
             resize_overlay_window,
-            close_overlay_window
+            close_overlay_window,
+            list_macros,
+            macro_library_error,
+            get_macro,
+            save_macro,
+            delete_macro,
+            duplicate_macro,
+            rename_macro,
+            replace_macros,
+            import_macros,
+            export_json_schema,
+            compile_macro_script,
+            decompile_macro_script,
+            export_macro,
+            import_recording_log,
+            get_kill_switch_settings,
+            set_kill_switch_settings,
+            trigger_emergency_stop,
+            get_emergency_stop_hotkey,
+            set_emergency_stop_hotkey
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,360 @@
+//! Converts recorded macros into standalone scripts for machines that do not
+//! run MacroArc. Keys are exported by `KeyCode`, the same way playback injects
+//! them, so modifiers come from their own key-down/key-up events.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    keysym::keysym_name,
+    types::{KeyCode, MacroEvent, MacroEventKind, MouseButton},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExportFormat {
+    /// Bash script driving `xdotool` (X11).
+    Xdotool,
+    /// AutoHotkey v2 script (Windows).
+    #[serde(alias = "ahk")]
+    AutoHotkey,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedScript {
+    pub script: String,
+    /// One entry per event that was skipped or approximated.
+    pub warnings: Vec<String>,
+}
+
+pub fn export_events(events: &[MacroEvent], format: ExportFormat) -> ExportedScript {
+    match format {
+        ExportFormat::Xdotool => export_xdotool(events),
+        ExportFormat::AutoHotkey => export_autohotkey(events),
+    }
+}
+
+pub fn export_xdotool(events: &[MacroEvent]) -> ExportedScript {
+    let mut lines = vec![
+        "#!/usr/bin/env bash".to_string(),
+        format!("# Exported from MacroArc: {} event(s).", events.len()),
+        "set -euo pipefail".to_string(),
+        String::new(),
+    ];
+    let mut warnings = Vec::new();
+
+    for (index, event, delay_ms) in with_delays(events) {
+        if delay_ms > 0 {
+            lines.push(format!("sleep {}.{:03}", delay_ms / 1000, delay_ms % 1000));
+        }
+        match &event.kind {
+            MacroEventKind::MouseMove { x, y } => lines.push(format!("xdotool mousemove -- {x} {y}")),
+            MacroEventKind::MouseDown { button } => {
+                lines.push(format!("xdotool mousedown {}", xdotool_button(*button)))
+            }
+            MacroEventKind::MouseUp { button } => lines.push(format!("xdotool mouseup {}", xdotool_button(*button))),
+            MacroEventKind::KeyDown { key } | MacroEventKind::KeyUp { key } => {
+                let action = if matches!(event.kind, MacroEventKind::KeyDown { .. }) {
+                    "keydown"
+                } else {
+                    "keyup"
+                };
+                match keysym_name(key.code) {
+                    Some(keysym) => lines.push(format!("xdotool {action} {keysym}")),
+                    None => warnings.push(unsupported_key(index, &key.label(), "xdotool")),
+                }
+            }
+            MacroEventKind::Scroll { delta_x, delta_y } => {
+                // Positive deltas scroll down/right, matching playback.
+                for (delta, negative, positive) in [(*delta_y, 4, 5), (*delta_x, 6, 7)] {
+                    if delta != 0 {
+                        let button = if delta < 0 { negative } else { positive };
+                        lines.push(format!("xdotool click --repeat {} {button}", delta.unsigned_abs()));
+                    }
+                }
+            }
+        }
+    }
+
+    finish(lines, warnings)
+}
+
+pub fn export_autohotkey(events: &[MacroEvent]) -> ExportedScript {
+    let mut lines = vec![
+        "#Requires AutoHotkey v2.0".to_string(),
+        format!("; Exported from MacroArc: {} event(s).", events.len()),
+        "SendMode \"Event\"".to_string(),
+        "CoordMode \"Mouse\", \"Screen\"".to_string(),
+        "SetKeyDelay -1".to_string(),
+        "SetMouseDelay -1".to_string(),
+        String::new(),
+    ];
+    let mut warnings = Vec::new();
+
+    for (index, event, delay_ms) in with_delays(events) {
+        if delay_ms > 0 {
+            lines.push(format!("Sleep {delay_ms}"));
+        }
+        match &event.kind {
+            MacroEventKind::MouseMove { x, y } => lines.push(format!("MouseMove {x}, {y}, 0")),
+            MacroEventKind::MouseDown { button } => {
+                lines.push(format!("Click \"{} Down\"", autohotkey_button(*button)))
+            }
+            MacroEventKind::MouseUp { button } => lines.push(format!("Click \"{} Up\"", autohotkey_button(*button))),
+            MacroEventKind::KeyDown { key } | MacroEventKind::KeyUp { key } => {
+                let action = if matches!(event.kind, MacroEventKind::KeyDown { .. }) {
+                    "down"
+                } else {
+                    "up"
+                };
+                match autohotkey_key(key.code) {
+                    Some(name) => lines.push(format!("Send \"{{{name} {action}}}\"")),
+                    None => warnings.push(unsupported_key(index, &key.label(), "AutoHotkey")),
+                }
+            }
+            MacroEventKind::Scroll { delta_x, delta_y } => {
+                for (delta, negative, positive) in [(*delta_y, "WheelUp", "WheelDown"), (*delta_x, "WheelLeft", "WheelRight")]
+                {
+                    if delta != 0 {
+                        let wheel = if delta < 0 { negative } else { positive };
+                        lines.push(format!("Click \"{wheel} {}\"", delta.unsigned_abs()));
+                    }
+                }
+            }
+        }
+    }
+
+    finish(lines, warnings)
+}
+
+/// Pairs every event with its index and the gap since the previous one.
+fn with_delays(events: &[MacroEvent]) -> impl Iterator<Item = (usize, &MacroEvent, u64)> {
+    let mut last_offset = 0u64;
+    events.iter().enumerate().map(move |(index, event)| {
+        let delay = event.offset_ms.saturating_sub(last_offset);
+        last_offset = event.offset_ms;
+        (index, event, delay)
+    })
+}
+
+fn finish(mut lines: Vec<String>, warnings: Vec<String>) -> ExportedScript {
+    lines.push(String::new());
+    ExportedScript {
+        script: lines.join("\n"),
+        warnings,
+    }
+}
+
+fn unsupported_key(index: usize, label: &str, tool: &str) -> String {
+    format!("event {index}: key `{label}` has no {tool} equivalent and was skipped")
+}
+
+fn xdotool_button(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Right => 3,
+        MouseButton::X1 => 8,
+        MouseButton::X2 => 9,
+    }
+}
+
+fn autohotkey_button(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "Left",
+        MouseButton::Right => "Right",
+        MouseButton::Middle => "Middle",
+        MouseButton::X1 => "X1",
+        MouseButton::X2 => "X2",
+    }
+}
+
+/// AutoHotkey v2 key name for `code`, escaped for use inside a `"..."` string.
+fn autohotkey_key(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Enter => "Enter",
+        KeyCode::Tab => "Tab",
+        KeyCode::Space => "Space",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Escape => "Escape",
+        KeyCode::CapsLock => "CapsLock",
+        KeyCode::Home => "Home",
+        KeyCode::End => "End",
+        KeyCode::PageUp => "PgUp",
+        KeyCode::PageDown => "PgDn",
+        KeyCode::Insert => "Insert",
+        KeyCode::Delete => "Delete",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Shift => "Shift",
+        KeyCode::Ctrl => "Ctrl",
+        KeyCode::Alt => "Alt",
+        KeyCode::Meta => "LWin",
+        KeyCode::PrintScreen => "PrintScreen",
+        KeyCode::ScrollLock => "ScrollLock",
+        KeyCode::Pause => "Pause",
+        KeyCode::NumLock => "NumLock",
+        KeyCode::F(index) => return Some(format!("F{index}")),
+        KeyCode::Numpad(symbol) => {
+            return Some(match symbol {
+                '+' => "NumpadAdd".into(),
+                '-' => "NumpadSub".into(),
+                '*' => "NumpadMult".into(),
+                '/' => "NumpadDiv".into(),
+                digit => format!("Numpad{digit}"),
+            })
+        }
+        KeyCode::Char('"') => "`\"",
+        KeyCode::Char('`') => "``",
+        KeyCode::Char(ch) => return Some(ch.to_string()),
+        KeyCode::Fn | KeyCode::Raw(_) | KeyCode::Unrecognized => return None,
+    };
+    Some(name.to_string())
+}
+
+#[tauri::command]
+pub fn export_macro(events: Vec<MacroEvent>, format: ExportFormat) -> ExportedScript {
+    export_events(&events, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::KeyStroke;
+
+    fn at(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
+    }
+
+    fn down(code: KeyCode) -> MacroEventKind {
+        MacroEventKind::KeyDown { key: KeyStroke::new(code) }
+    }
+
+    fn up(code: KeyCode) -> MacroEventKind {
+        MacroEventKind::KeyUp { key: KeyStroke::new(code) }
+    }
+
+    /// One of every event kind, with keys that need escaping and keys neither
+    /// tool can press.
+    fn events() -> Vec<MacroEvent> {
+        vec![
+            at(0, MacroEventKind::MouseMove { x: -20, y: 1080 }),
+            at(0, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            at(1500, MacroEventKind::MouseUp { button: MouseButton::Left }),
+            at(1500, MacroEventKind::MouseDown { button: MouseButton::X2 }),
+            at(1505, MacroEventKind::MouseUp { button: MouseButton::X2 }),
+            at(2000, down(KeyCode::Char('"'))),
+            at(2000, up(KeyCode::Char('"'))),
+            at(2000, down(KeyCode::Char('`'))),
+            at(2000, up(KeyCode::Char('`'))),
+            at(2000, down(KeyCode::Char('{'))),
+            at(2000, up(KeyCode::Char('}'))),
+            at(2000, down(KeyCode::Numpad('+'))),
+            at(2000, up(KeyCode::Numpad('7'))),
+            at(2000, down(KeyCode::Meta)),
+            at(2000, up(KeyCode::F(12))),
+            at(2000, down(KeyCode::Fn)),
+            at(2000, up(KeyCode::Raw(57))),
+            at(2000, MacroEventKind::KeyDown { key: KeyStroke::unrecognized("Hyper+Q".into()) }),
+            at(3000, MacroEventKind::Scroll { delta_x: -2, delta_y: 3 }),
+            at(3000, MacroEventKind::Scroll { delta_x: 1, delta_y: -1 }),
+        ]
+    }
+
+    #[test]
+    fn exports_every_event_kind_to_xdotool() {
+        let exported = export_events(&events(), ExportFormat::Xdotool);
+        let expected = "\
+#!/usr/bin/env bash
+# Exported from MacroArc: 20 event(s).
+set -euo pipefail
+
+xdotool mousemove -- -20 1080
+xdotool mousedown 1
+sleep 1.500
+xdotool mouseup 1
+xdotool mousedown 9
+sleep 0.005
+xdotool mouseup 9
+sleep 0.495
+xdotool keydown quotedbl
+xdotool keyup quotedbl
+xdotool keydown grave
+xdotool keyup grave
+xdotool keydown braceleft
+xdotool keyup braceright
+xdotool keydown KP_Add
+xdotool keyup KP_7
+xdotool keydown Super_L
+xdotool keyup F12
+sleep 1.000
+xdotool click --repeat 3 5
+xdotool click --repeat 2 6
+xdotool click --repeat 1 4
+xdotool click --repeat 1 7
+";
+        assert_eq!(exported.script, expected);
+        assert_eq!(
+            exported.warnings,
+            [
+                "event 15: key `Fn` has no xdotool equivalent and was skipped",
+                "event 16: key `Unknown(57)` has no xdotool equivalent and was skipped",
+                "event 17: key `Hyper+Q` has no xdotool equivalent and was skipped",
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_every_event_kind_to_autohotkey() {
+        let exported = export_events(&events(), ExportFormat::AutoHotkey);
+        let expected = r#"#Requires AutoHotkey v2.0
+; Exported from MacroArc: 20 event(s).
+SendMode "Event"
+CoordMode "Mouse", "Screen"
+SetKeyDelay -1
+SetMouseDelay -1
+
+MouseMove -20, 1080, 0
+Click "Left Down"
+Sleep 1500
+Click "Left Up"
+Click "X2 Down"
+Sleep 5
+Click "X2 Up"
+Sleep 495
+Send "{`" down}"
+Send "{`" up}"
+Send "{`` down}"
+Send "{`` up}"
+Send "{{ down}"
+Send "{} up}"
+Send "{NumpadAdd down}"
+Send "{Numpad7 up}"
+Send "{LWin down}"
+Send "{F12 up}"
+Sleep 1000
+Click "WheelDown 3"
+Click "WheelLeft 2"
+Click "WheelUp 1"
+Click "WheelRight 1"
+"#;
+        assert_eq!(exported.script, expected);
+        assert_eq!(
+            exported.warnings,
+            [
+                "event 15: key `Fn` has no AutoHotkey equivalent and was skipped",
+                "event 16: key `Unknown(57)` has no AutoHotkey equivalent and was skipped",
+                "event 17: key `Hyper+Q` has no AutoHotkey equivalent and was skipped",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_the_ahk_alias() {
+        let format: ExportFormat = serde_json::from_str("\"ahk\"").unwrap();
+        assert_eq!(format, ExportFormat::AutoHotkey);
+        let format: ExportFormat = serde_json::from_str("\"auto-hotkey\"").unwrap();
+        assert_eq!(format, ExportFormat::AutoHotkey);
+    }
+}
@@ -0,0 +1,774 @@
+//! Converts `xmacrorec2` recordings and xdotool scripts into macro events.
+//! Timing is rebuilt from the `Delay`/`sleep` lines and xdotool's own
+//! per-keystroke and per-click delays. Lines that cannot be represented are
+//! collected instead of aborting the import.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    keysym::{key_from_keysym, key_from_keysym_value},
+    types::{KeyCode, KeyStroke, MacroEvent, MacroEventKind, Modifiers, MouseButton},
+};
+
+/// xdotool's default `--delay` for `key` and `type`.
+const XDOTOOL_KEY_DELAY_MS: u64 = 12;
+/// xdotool's default `--delay` between repeated clicks.
+const XDOTOOL_CLICK_DELAY_MS: u64 = 100;
+
+const XDOTOOL_COMMANDS: &[&str] = &[
+    "mousemove",
+    "mousemove_relative",
+    "click",
+    "mousedown",
+    "mouseup",
+    "key",
+    "keydown",
+    "keyup",
+    "type",
+    "sleep",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordingFormat {
+    /// `xmacrorec2` output (`Delay`, `MotionNotify`, `ButtonPress`, `KeyStrPress`, ...).
+    Xmacro,
+    /// Shell script or log of `xdotool` invocations and `sleep` calls.
+    Xdotool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UntranslatedLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedRecording {
+    pub format: RecordingFormat,
+    pub events: Vec<MacroEvent>,
+    pub untranslated: Vec<UntranslatedLine>,
+}
+
+/// Guesses the format from the first line that looks like an xmacro command.
+pub fn detect_recording_format(source: &str) -> RecordingFormat {
+    let is_xmacro = source.lines().any(|line| {
+        matches!(
+            line.split_whitespace().next(),
+            Some("Delay" | "MotionNotify" | "ButtonPress" | "ButtonRelease" | "KeyStrPress" | "KeyStrRelease")
+        )
+    });
+    if is_xmacro {
+        RecordingFormat::Xmacro
+    } else {
+        RecordingFormat::Xdotool
+    }
+}
+
+pub fn import_recording(source: &str, format: Option<RecordingFormat>) -> ImportedRecording {
+    match format.unwrap_or_else(|| detect_recording_format(source)) {
+        RecordingFormat::Xmacro => import_xmacro(source),
+        RecordingFormat::Xdotool => import_xdotool(source),
+    }
+}
+
+pub fn import_xmacro(source: &str) -> ImportedRecording {
+    let mut timeline = Timeline::default();
+    let mut untranslated = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Err(reason) = translate_xmacro_line(&mut timeline, trimmed) {
+            untranslated.push(UntranslatedLine {
+                line: index + 1,
+                text: line.to_string(),
+                reason,
+            });
+        }
+    }
+
+    ImportedRecording {
+        format: RecordingFormat::Xmacro,
+        events: timeline.events,
+        untranslated,
+    }
+}
+
+fn translate_xmacro_line(timeline: &mut Timeline, line: &str) -> Result<(), String> {
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+
+    match command {
+        "Delay" => {
+            let [delay] = args[..] else {
+                return Err("expected `Delay <milliseconds>`".into());
+            };
+            timeline.wait(parse_number(delay)?);
+        }
+        "MotionNotify" => {
+            let [x, y] = args[..] else {
+                return Err("expected `MotionNotify <x> <y>`".into());
+            };
+            timeline.push(MacroEventKind::MouseMove {
+                x: parse_number(x)?,
+                y: parse_number(y)?,
+            });
+        }
+        "ButtonPress" | "ButtonRelease" => {
+            let [button] = args[..] else {
+                return Err(format!("expected `{command} <button>`"));
+            };
+            let button = parse_number(button)?;
+            if command == "ButtonPress" {
+                timeline.button_down(button)?;
+            } else {
+                timeline.button_up(button)?;
+            }
+        }
+        "KeyStrPress" | "KeyStrRelease" => {
+            let [name] = args[..] else {
+                return Err(format!("expected `{command} <keysym name>`"));
+            };
+            let code = key_from_keysym(name).ok_or_else(|| format!("unknown keysym `{name}`"))?;
+            if command == "KeyStrPress" {
+                timeline.key_down(code);
+            } else {
+                timeline.key_up(code);
+            }
+        }
+        "KeySymPress" | "KeySymRelease" => {
+            let [value] = args[..] else {
+                return Err(format!("expected `{command} <keysym>`"));
+            };
+            let keysym = match value.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| format!("invalid keysym `{value}`"))?,
+                None => parse_number(value)?,
+            };
+            let code = key_from_keysym_value(keysym).ok_or_else(|| format!("unsupported keysym {value}"))?;
+            if command == "KeySymPress" {
+                timeline.key_down(code);
+            } else {
+                timeline.key_up(code);
+            }
+        }
+        "KeyCodePress" | "KeyCodeRelease" => {
+            return Err("raw X keycodes depend on the recording machine's keymap; re-record with KeyStr events".into());
+        }
+        "String" => {
+            for ch in rest.chars() {
+                timeline.type_char(ch);
+            }
+        }
+        other => return Err(format!("unknown xmacro command `{other}`")),
+    }
+    Ok(())
+}
+
+pub fn import_xdotool(source: &str) -> ImportedRecording {
+    let mut timeline = Timeline::default();
+    let mut untranslated = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let mut report = |reason: String| {
+            untranslated.push(UntranslatedLine {
+                line: index + 1,
+                text: line.to_string(),
+                reason,
+            })
+        };
+        let commands = match shell_commands(line) {
+            Ok(commands) => commands,
+            Err(reason) => {
+                report(reason);
+                continue;
+            }
+        };
+        for words in commands {
+            let result = match words.first().map(String::as_str) {
+                Some("xdotool") => translate_xdotool(&mut timeline, &words[1..]),
+                Some("sleep") => match &words[1..] {
+                    [seconds] => parse_seconds(seconds).map(|delay| timeline.wait(delay)),
+                    _ => Err("expected `sleep <seconds>`".into()),
+                },
+                // Script boilerplate such as `set -euo pipefail`.
+                Some("set") => Ok(()),
+                Some(other) => Err(format!("`{other}` is not an xdotool or sleep command")),
+                None => Ok(()),
+            };
+            if let Err(reason) = result {
+                report(reason);
+                break;
+            }
+        }
+    }
+
+    ImportedRecording {
+        format: RecordingFormat::Xdotool,
+        events: timeline.events,
+        untranslated,
+    }
+}
+
+/// Runs the (possibly chained) xdotool commands in `words`.
+fn translate_xdotool(timeline: &mut Timeline, mut words: &[String]) -> Result<(), String> {
+    while let Some((command, rest)) = words.split_first() {
+        let (options, args) = split_options(rest);
+        let delay = |default: u64| -> Result<u64, String> {
+            options.value("delay").map_or(Ok(default), parse_number)
+        };
+
+        let consumed = match command.as_str() {
+            "mousemove" => {
+                if options.has("window") || options.has("polar") {
+                    return Err("window-relative and polar `mousemove` cannot be replayed".into());
+                }
+                let [x, y, ..] = args else {
+                    return Err("expected `mousemove <x> <y>`".into());
+                };
+                timeline.push(MacroEventKind::MouseMove {
+                    x: parse_number(x)?,
+                    y: parse_number(y)?,
+                });
+                2
+            }
+            "click" => {
+                let [button, ..] = args else {
+                    return Err("expected `click <button>`".into());
+                };
+                let button = parse_number(button)?;
+                let repeat: u32 = options.value("repeat").map_or(Ok(1), parse_number)?;
+                let gap = delay(XDOTOOL_CLICK_DELAY_MS)?;
+                for iteration in 0..repeat.max(1) {
+                    if iteration > 0 {
+                        timeline.wait(gap);
+                    }
+                    timeline.button_down(button)?;
+                    timeline.button_up(button)?;
+                }
+                1
+            }
+            "mousedown" | "mouseup" => {
+                let [button, ..] = args else {
+                    return Err(format!("expected `{command} <button>`"));
+                };
+                let button = parse_number(button)?;
+                if command == "mousedown" {
+                    timeline.button_down(button)?;
+                } else {
+                    timeline.button_up(button)?;
+                }
+                1
+            }
+            "key" | "keydown" | "keyup" => {
+                let sequences: Vec<&String> = args
+                    .iter()
+                    .take_while(|word| !XDOTOOL_COMMANDS.contains(&word.as_str()))
+                    .collect();
+                if sequences.is_empty() {
+                    return Err(format!("expected `{command} <keys>`"));
+                }
+                let repeat: u32 = options.value("repeat").map_or(Ok(1), parse_number)?;
+                let gap = delay(XDOTOOL_KEY_DELAY_MS)?;
+                let mut first = true;
+                for _ in 0..repeat.max(1) {
+                    for sequence in &sequences {
+                        if !first {
+                            timeline.wait(gap);
+                        }
+                        first = false;
+                        let keys = parse_key_sequence(sequence)?;
+                        match command.as_str() {
+                            "keydown" => keys.iter().for_each(|key| timeline.key_down(*key)),
+                            "keyup" => keys.iter().rev().for_each(|key| timeline.key_up(*key)),
+                            _ => {
+                                keys.iter().for_each(|key| timeline.key_down(*key));
+                                keys.iter().rev().for_each(|key| timeline.key_up(*key));
+                            }
+                        }
+                    }
+                }
+                sequences.len()
+            }
+            "type" => {
+                let gap = delay(XDOTOOL_KEY_DELAY_MS)?;
+                let mut first = true;
+                for ch in args.iter().flat_map(|word| word.chars()) {
+                    if !first {
+                        timeline.wait(gap);
+                    }
+                    first = false;
+                    timeline.type_char(ch);
+                }
+                args.len()
+            }
+            "sleep" => {
+                let [seconds, ..] = args else {
+                    return Err("expected `sleep <seconds>`".into());
+                };
+                timeline.wait(parse_seconds(seconds)?);
+                1
+            }
+            "mousemove_relative" => return Err("relative mouse movement cannot be replayed".into()),
+            other => return Err(format!("xdotool command `{other}` is not supported")),
+        };
+
+        words = &args[consumed.min(args.len())..];
+    }
+    Ok(())
+}
+
+/// `ctrl+shift+t` as the keys to hold, in order. An uppercase letter is
+/// pressed with Shift, as xdotool does.
+fn parse_key_sequence(sequence: &str) -> Result<Vec<KeyCode>, String> {
+    let mut keys = Vec::new();
+    for name in sequence.split('+').filter(|name| !name.is_empty()) {
+        let code = key_from_keysym(name).ok_or_else(|| format!("unknown keysym `{name}`"))?;
+        if name.len() == 1 && name.chars().all(|ch| ch.is_ascii_uppercase()) && !keys.contains(&KeyCode::Shift) {
+            keys.push(KeyCode::Shift);
+        }
+        keys.push(code);
+    }
+    if keys.is_empty() {
+        return Err(format!("empty key sequence `{sequence}`"));
+    }
+    Ok(keys)
+}
+
+struct XdotoolOptions<'a> {
+    entries: Vec<(&'a str, Option<&'a str>)>,
+}
+
+impl<'a> XdotoolOptions<'a> {
+    fn has(&self, name: &str) -> bool {
+        self.entries.iter().any(|(option, _)| *option == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&'a str> {
+        self.entries
+            .iter()
+            .find(|(option, _)| *option == name)
+            .and_then(|(_, value)| *value)
+    }
+}
+
+/// Splits leading `--option [value]` words from a command's arguments. A bare
+/// `--` ends the options so negative coordinates can follow.
+fn split_options(words: &[String]) -> (XdotoolOptions<'_>, &[String]) {
+    const WITH_VALUE: &[&str] = &["delay", "repeat", "window", "screen", "terminator", "file", "args"];
+
+    let mut entries = Vec::new();
+    let mut index = 0;
+    while let Some(word) = words.get(index) {
+        if word == "--" {
+            index += 1;
+            break;
+        }
+        let Some(name) = word.strip_prefix("--") else {
+            break;
+        };
+        index += 1;
+        let value = if WITH_VALUE.contains(&name) {
+            index += 1;
+            words.get(index - 1).map(String::as_str)
+        } else {
+            None
+        };
+        entries.push((name, value));
+    }
+    (XdotoolOptions { entries }, &words[index.min(words.len())..])
+}
+
+/// Splits a shell line into commands (at unquoted `;` and `&&`), each a list
+/// of words with quotes and escapes resolved. Comments are dropped.
+fn shell_commands(line: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut commands = vec![Vec::new()];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                if let Some(done) = word.take() {
+                    commands.last_mut().expect("never empty").push(done);
+                }
+            }
+            '#' if word.is_none() => break,
+            ';' | '&' => {
+                if ch == '&' && chars.next_if_eq(&'&').is_none() {
+                    return Err("background jobs (`&`) are not supported".into());
+                }
+                if let Some(done) = word.take() {
+                    commands.last_mut().expect("never empty").push(done);
+                }
+                commands.push(Vec::new());
+            }
+            '|' | '<' | '>' | '`' | '$' => {
+                return Err(format!("shell syntax `{ch}` is not supported"));
+            }
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(other) => current.push(other),
+                        None => return Err("unterminated single quote".into()),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => current.push(escaped),
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err("unterminated double quote".into()),
+                        },
+                        Some('$' | '`') => return Err("shell expansion inside quotes is not supported".into()),
+                        Some(other) => current.push(other),
+                        None => return Err("unterminated double quote".into()),
+                    }
+                }
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    word.get_or_insert_with(String::new).push(escaped);
+                }
+            }
+            other => word.get_or_insert_with(String::new).push(other),
+        }
+    }
+    if let Some(done) = word {
+        commands.last_mut().expect("never empty").push(done);
+    }
+    commands.retain(|words| !words.is_empty());
+    Ok(commands)
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("`{text}` is not a valid number"))
+}
+
+fn parse_seconds(text: &str) -> Result<u64, String> {
+    match text.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok((seconds * 1000.0).round() as u64),
+        _ => Err(format!("`{text}` is not a valid number of seconds")),
+    }
+}
+
+/// Accumulates events at the running offset and labels key strokes with the
+/// modifiers held at the time, the way the recorder does.
+#[derive(Default)]
+struct Timeline {
+    cursor_ms: u64,
+    held: Modifiers,
+    events: Vec<MacroEvent>,
+}
+
+impl Timeline {
+    fn wait(&mut self, delay_ms: u64) {
+        self.cursor_ms = self.cursor_ms.saturating_add(delay_ms);
+    }
+
+    fn push(&mut self, kind: MacroEventKind) {
+        self.events.push(MacroEvent {
+            offset_ms: self.cursor_ms,
+            kind,
+        });
+    }
+
+    fn stroke(&self, code: KeyCode) -> KeyStroke {
+        let mut modifiers = self.held;
+        match code {
+            KeyCode::Ctrl => modifiers.ctrl = false,
+            KeyCode::Shift => modifiers.shift = false,
+            KeyCode::Alt => modifiers.alt = false,
+            KeyCode::Meta => modifiers.meta = false,
+            _ => {}
+        }
+        KeyStroke {
+            code,
+            modifiers,
+            text: None,
+        }
+    }
+
+    fn set_held(&mut self, code: KeyCode, pressed: bool) {
+        match code {
+            KeyCode::Ctrl => self.held.ctrl = pressed,
+            KeyCode::Shift => self.held.shift = pressed,
+            KeyCode::Alt => self.held.alt = pressed,
+            KeyCode::Meta => self.held.meta = pressed,
+            _ => {}
+        }
+    }
+
+    fn key_down(&mut self, code: KeyCode) {
+        self.set_held(code, true);
+        let key = self.stroke(code);
+        self.push(MacroEventKind::KeyDown { key });
+    }
+
+    fn key_up(&mut self, code: KeyCode) {
+        let key = self.stroke(code);
+        self.set_held(code, false);
+        self.push(MacroEventKind::KeyUp { key });
+    }
+
+    fn type_char(&mut self, ch: char) {
+        let code = KeyCode::from_char(ch);
+        let needs_shift = ch.is_ascii_uppercase() && !self.held.shift;
+        if needs_shift {
+            self.key_down(KeyCode::Shift);
+        }
+        self.key_down(code);
+        self.key_up(code);
+        if needs_shift {
+            self.key_up(KeyCode::Shift);
+        }
+    }
+
+    /// X buttons 4-7 are wheel steps; a press scrolls one notch and the
+    /// matching release is ignored.
+    fn button_down(&mut self, button: u8) -> Result<(), String> {
+        if let Some((delta_x, delta_y)) = wheel_step(button) {
+            self.push(MacroEventKind::Scroll { delta_x, delta_y });
+            return Ok(());
+        }
+        let button = x_button(button)?;
+        self.push(MacroEventKind::MouseDown { button });
+        Ok(())
+    }
+
+    fn button_up(&mut self, button: u8) -> Result<(), String> {
+        if wheel_step(button).is_some() {
+            return Ok(());
+        }
+        let button = x_button(button)?;
+        self.push(MacroEventKind::MouseUp { button });
+        Ok(())
+    }
+}
+
+/// Positive deltas scroll down/right, matching playback.
+fn wheel_step(button: u8) -> Option<(i64, i64)> {
+    match button {
+        4 => Some((0, -1)),
+        5 => Some((0, 1)),
+        6 => Some((-1, 0)),
+        7 => Some((1, 0)),
+        _ => None,
+    }
+}
+
+fn x_button(button: u8) -> Result<MouseButton, String> {
+    match button {
+        1 => Ok(MouseButton::Left),
+        2 => Ok(MouseButton::Middle),
+        3 => Ok(MouseButton::Right),
+        8 => Ok(MouseButton::X1),
+        9 => Ok(MouseButton::X2),
+        other => Err(format!("mouse button {other} has no equivalent")),
+    }
+}
+
+#[tauri::command]
+pub fn import_recording_log(contents: String, format: Option<RecordingFormat>) -> ImportedRecording {
+    import_recording(&contents, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::macro_script::decompile_events;
+
+    fn words(commands: &[&[&str]]) -> Vec<Vec<String>> {
+        commands
+            .iter()
+            .map(|command| command.iter().map(|word| word.to_string()).collect())
+            .collect()
+    }
+
+    fn untranslated(recording: &ImportedRecording) -> Vec<(usize, &str)> {
+        recording
+            .untranslated
+            .iter()
+            .map(|line| (line.line, line.reason.as_str()))
+            .collect()
+    }
+
+    fn offsets(recording: &ImportedRecording) -> Vec<u64> {
+        recording.events.iter().map(|event| event.offset_ms).collect()
+    }
+
+    #[test]
+    fn splits_shell_lines_into_commands() {
+        let cases: [(&str, &[&[&str]]); 7] = [
+            ("xdotool type \"a b\" 'c d'", &[&["xdotool", "type", "a b", "c d"]]),
+            (
+                "sleep 1; xdotool key a && xdotool key b # done",
+                &[&["sleep", "1"], &["xdotool", "key", "a"], &["xdotool", "key", "b"]],
+            ),
+            ("xdotool type a\\ b it\\'s", &[&["xdotool", "type", "a b", "it's"]]),
+            (r#"xdotool type "say \"hi\" \\ \n""#, &[&["xdotool", "type", r#"say "hi" \ \n"#]]),
+            ("xdotool type 'no \\escapes'", &[&["xdotool", "type", "no \\escapes"]]),
+            ("xdotool type a#b", &[&["xdotool", "type", "a#b"]]),
+            ("   # only a comment", &[]),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(shell_commands(line), Ok(words(expected)), "{line}");
+        }
+
+        let errors = [
+            ("xdotool key a &", "background jobs (`&`) are not supported"),
+            ("xdotool type $HOME", "shell syntax `$` is not supported"),
+            ("xdotool getmouselocation | cut -f1", "shell syntax `|` is not supported"),
+            ("xdotool type \"$HOME\"", "shell expansion inside quotes is not supported"),
+            ("xdotool type \"abc", "unterminated double quote"),
+            ("xdotool type 'abc", "unterminated single quote"),
+        ];
+        for (line, expected) in errors {
+            assert_eq!(shell_commands(line), Err(expected.to_string()), "{line}");
+        }
+    }
+
+    #[test]
+    fn splits_leading_options_from_arguments() {
+        let line = words(&[&["--delay", "50", "--clearmodifiers", "--", "-5", "--10"]]).remove(0);
+        let (options, args) = split_options(&line);
+        assert_eq!(options.value("delay"), Some("50"));
+        assert!(options.has("clearmodifiers"));
+        assert_eq!(options.value("clearmodifiers"), None);
+        assert_eq!(args, ["-5", "--10"]);
+
+        let line = words(&[&["a", "--delay", "50"]]).remove(0);
+        let (options, args) = split_options(&line);
+        assert!(!options.has("delay"));
+        assert_eq!(args, ["a", "--delay", "50"]);
+
+        let line = words(&[&["--repeat"]]).remove(0);
+        let (options, args) = split_options(&line);
+        assert!(options.has("repeat"));
+        assert_eq!(options.value("repeat"), None);
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn runs_chained_xdotool_commands_in_order() {
+        let recording = import_xdotool(
+            "xdotool mousemove 10 20 click 3 keydown shift key a keyup shift sleep 0.1 mousedown 2 mouseup 2",
+        );
+        assert!(recording.untranslated.is_empty());
+        assert_eq!(
+            decompile_events(&recording.events),
+            "move 10,20\nclick right\npress Shift+A\nwait 100ms\nclick middle\n"
+        );
+    }
+
+    #[test]
+    fn spaces_repeated_keys_and_clicks_like_xdotool() {
+        let keys = import_xdotool("xdotool key a b\nxdotool type ab");
+        let key_gap = XDOTOOL_KEY_DELAY_MS;
+        assert_eq!(offsets(&keys), [0, 0, key_gap, key_gap, key_gap, key_gap, 2 * key_gap, 2 * key_gap]);
+
+        let clicks = import_xdotool("xdotool click --repeat 3 1");
+        let click_gap = XDOTOOL_CLICK_DELAY_MS;
+        assert_eq!(offsets(&clicks), [0, 0, click_gap, click_gap, 2 * click_gap, 2 * click_gap]);
+
+        let custom = import_xdotool("xdotool key --delay 40 --repeat 2 a");
+        assert_eq!(offsets(&custom), [0, 0, 40, 40]);
+    }
+
+    #[test]
+    fn maps_wheel_buttons_to_scroll_steps() {
+        let recording = import_xdotool("xdotool click 4 click 5 click 6 click 7 mousedown 4 mouseup 4\n\
+                                        xdotool click 10");
+        let scrolls: Vec<_> = recording.events.iter().map(|event| event.kind.clone()).collect();
+        let step = |delta_x, delta_y| MacroEventKind::Scroll { delta_x, delta_y };
+        assert_eq!(scrolls, [step(0, -1), step(0, 1), step(-1, 0), step(1, 0), step(0, -1)]);
+        assert_eq!(untranslated(&recording), [(2, "mouse button 10 has no equivalent")]);
+    }
+
+    #[test]
+    fn imports_an_xdotool_script() {
+        let script = r#"#!/usr/bin/env bash
+set -euo pipefail
+xdotool mousemove -- -5 300 click 1
+sleep 0.25
+xdotool key --delay 40 ctrl+l BackSpace
+xdotool type --delay 30 "Hi you"
+xdotool click --repeat 2 --delay 60 5
+xdotool mousemove_relative 10 10
+echo done
+xdotool mousemove --window 12 0 0
+xdotool key --repeat 2 Return; sleep 1
+"#;
+        let recording = import_recording(script, None);
+        assert_eq!(recording.format, RecordingFormat::Xdotool);
+        let expected = "move -5,300\nclick left\nwait 250ms\npress Ctrl+L\nwait 40ms\npress Backspace\n\
+                        press Shift+H\nwait 30ms\npress I\nwait 30ms\npress Space\nwait 30ms\npress Y\nwait 30ms\n\
+                        press O\nwait 30ms\npress U\nscroll 0,1\nwait 60ms\nscroll 0,1\npress Enter\nwait 12ms\n\
+                        press Enter\n";
+        assert_eq!(decompile_events(&recording.events), expected);
+        assert_eq!(
+            untranslated(&recording),
+            [
+                (8, "relative mouse movement cannot be replayed"),
+                (9, "`echo` is not an xdotool or sleep command"),
+                (10, "window-relative and polar `mousemove` cannot be replayed"),
+            ]
+        );
+        assert_eq!(recording.untranslated[1].text, "echo done");
+    }
+
+    #[test]
+    fn imports_an_xmacro_recording() {
+        let recording = "Delay 120
+MotionNotify 640 480
+ButtonPress 1
+Delay 80
+ButtonRelease 1
+ButtonPress 4
+ButtonRelease 4
+KeyStrPress Control_L
+KeyStrPress s
+KeyStrRelease s
+KeyStrRelease Control_L
+KeySymPress 0xff0d
+KeySymRelease 0xff0d
+String Ok
+KeyCodePress 36
+Delay soon
+ButtonPress 10
+KeyStrPress Hyper_L
+Warp 1 2
+";
+        let recording = import_recording(recording, None);
+        assert_eq!(recording.format, RecordingFormat::Xmacro);
+        assert_eq!(
+            decompile_events(&recording.events),
+            "wait 120ms\nmove 640,480\nmousedown left\nwait 80ms\nmouseup left\nscroll 0,-1\npress Ctrl+S\n\
+             type \"\\nOk\"\n"
+        );
+        assert_eq!(
+            untranslated(&recording),
+            [
+                (15, "raw X keycodes depend on the recording machine's keymap; re-record with KeyStr events"),
+                (16, "`soon` is not a valid number"),
+                (17, "mouse button 10 has no equivalent"),
+                (18, "unknown keysym `Hyper_L`"),
+                (19, "unknown xmacro command `Warp`"),
+            ]
+        );
+    }
+}
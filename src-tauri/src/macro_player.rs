@@ -1,24 +1,327 @@
 use std::{
+    collections::{BTreeSet, HashMap},
+    panic::{self, AssertUnwindSafe},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use enigo::{Enigo, Key, KeyboardControllable, MouseButton as EnigoMouseButton, MouseControllable};
+use parking_lot::Mutex;
 use tauri::{Emitter, State, Window};
 
 use crate::{
     app_state::AppState,
-    types::{MacroEventKind, MacroPlaybackRequest, MacroPlaybackStatus},
+    injection_gate::{GatedSink, InjectionGate},
+    input_sink::{EnigoSink, HeldInputs, InputSink, NullSink, TrackingSink},
+    humanize::Humanizer,
+    kill_switch::{arm_kill_switch, MarkedSink},
+    macro_validation::{validate_events, MacroIssue, ScreenArea},
+    mouse_path::interpolate_moves,
+    playback_clock::{scaled_offset, wait_until, LatenessStats, PlaybackClock, WaitOutcome, MAX_SLEEP_SLICE},
+    playback_range::{select_range, PlaybackEvents, PlaybackRange},
+    types::{
+        Humanize,
+        KeyCode,
+        MacroDebugHalt,
+        MacroDryRun,
+        MacroEvent,
+        MacroEventKind,
+        MacroPlaybackProgress,
+        MacroPlaybackRequest,
+        MacroPlaybackStatus,
+        PlaybackChannelStatus,
+        PlaybackLimits,
+        PlaybackTiming,
+        ScheduledMacroEvent,
+    },
 };
 
+/// Channel for playbacks that name neither a channel nor a context id.
+pub const DEFAULT_CHANNEL: &str = "default";
+
+/// Playbacks in progress, one per named channel. Channels play at the same
+/// time; their injection is serialized through `gate`.
 #[derive(Default)]
 pub struct MacroPlaybackState {
-    pub(crate) stop_flag: Option<Arc<AtomicBool>>,
-    pub(crate) handle: Option<thread::JoinHandle<()>>,
+    channels: HashMap<String, PlaybackChannel>,
+    gate: Arc<InjectionGate>,
+}
+
+pub(crate) struct PlaybackChannel {
+    pub(crate) control: Arc<PlaybackControl>,
+    pub(crate) handle: thread::JoinHandle<()>,
+    context_id: Option<String>,
+}
+
+impl PlaybackChannel {
+    fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+}
+
+impl MacroPlaybackState {
+    /// Whether any channel is playing.
+    pub(crate) fn is_busy(&self) -> bool {
+        self.channels.values().any(PlaybackChannel::is_running)
+    }
+
+    pub(crate) fn gate(&self) -> Arc<InjectionGate> {
+        self.gate.clone()
+    }
+
+    pub(crate) fn channel(&self, name: &str) -> Option<&PlaybackChannel> {
+        self.channels.get(name)
+    }
+
+    pub(crate) fn insert(
+        &mut self,
+        name: String,
+        control: Arc<PlaybackControl>,
+        handle: thread::JoinHandle<()>,
+        context_id: Option<String>,
+    ) {
+        self.channels.retain(|_, channel| channel.is_running());
+        self.channels.insert(
+            name,
+            PlaybackChannel {
+                control,
+                handle,
+                context_id,
+            },
+        );
+    }
+
+    /// The control of the playback on `channel`, or of the only playback in
+    /// progress when no channel is named.
+    fn running_control(&self, channel: Option<&str>) -> Result<&Arc<PlaybackControl>, String> {
+        let running = |channel: &&PlaybackChannel| channel.is_running();
+        let found = match channel {
+            Some(name) => self.channels.get(name).filter(running),
+            None => {
+                let mut playing = self.channels.values().filter(running);
+                let only = playing.next();
+                if playing.next().is_some() {
+                    return Err("Several channels are playing; name one".into());
+                }
+                only
+            }
+        };
+        match (found, channel) {
+            (Some(channel), _) => Ok(&channel.control),
+            (None, Some(name)) => Err(format!("Nothing is playing on channel `{name}`")),
+            (None, None) => Err("No macro playback in progress".into()),
+        }
+    }
+
+    fn statuses(&self) -> Vec<PlaybackChannelStatus> {
+        let mut statuses: Vec<PlaybackChannelStatus> = self
+            .channels
+            .iter()
+            .filter(|(_, channel)| channel.is_running())
+            .map(|(name, channel)| PlaybackChannelStatus {
+                channel: name.clone(),
+                context_id: channel.context_id.clone(),
+                state: channel.control.state().into(),
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.channel.cmp(&b.channel));
+        statuses
+    }
+}
+
+/// Flags shared between the command handlers and the player thread.
+#[derive(Debug, Default)]
+pub struct PlaybackControl {
+    stop: AtomicBool,
+    paused: AtomicBool,
+    debugger: Option<Mutex<DebugSession>>,
+}
+
+/// How a debug session decides where to halt next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugRunMode {
+    /// Halt before every event.
+    Step,
+    /// Halt only at breakpoints.
+    Continue,
+    /// Halt at breakpoints or before the first event recorded at or after
+    /// this offset, whichever comes first.
+    RunToOffset(u64),
+}
+
+/// Breakpoints and run mode of a debug playback. Event indices and offsets
+/// refer to the caller's event list as recorded, before any range selection;
+/// events playback adds itself never halt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSession {
+    pub breakpoints: BTreeSet<usize>,
+    pub mode: DebugRunMode,
+    halted: bool,
+}
+
+impl DebugSession {
+    /// A session that halts before the first event.
+    pub fn new(breakpoints: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            breakpoints: breakpoints.into_iter().collect(),
+            mode: DebugRunMode::Step,
+            halted: false,
+        }
+    }
+
+    fn should_halt(&self, event_index: usize, offset_ms: u64) -> bool {
+        match self.mode {
+            DebugRunMode::Step => true,
+            DebugRunMode::Continue => self.breakpoints.contains(&event_index),
+            DebugRunMode::RunToOffset(target) => offset_ms >= target || self.breakpoints.contains(&event_index),
+        }
+    }
+}
+
+impl PlaybackControl {
+    /// Control for a debug playback that starts halted before the first event.
+    pub fn debugging(session: DebugSession) -> Self {
+        Self {
+            debugger: Some(Mutex::new(session)),
+            ..Default::default()
+        }
+    }
+
+    /// Lets a halted debug playback carry on under `mode`. Returns `false`
+    /// for playbacks that are not in debug mode.
+    pub fn debug_resume(&self, mode: DebugRunMode) -> bool {
+        let Some(debugger) = &self.debugger else {
+            return false;
+        };
+        let mut session = debugger.lock();
+        session.mode = mode;
+        session.halted = false;
+        true
+    }
+
+    /// Replaces the breakpoint set. Returns `false` for playbacks that are not
+    /// in debug mode.
+    pub fn set_breakpoints(&self, breakpoints: impl IntoIterator<Item = usize>) -> bool {
+        let Some(debugger) = &self.debugger else {
+            return false;
+        };
+        debugger.lock().breakpoints = breakpoints.into_iter().collect();
+        true
+    }
+
+    /// Marks the session halted if it wants to stop before this event.
+    fn enter_halt(&self, event_index: usize, offset_ms: u64) -> bool {
+        let Some(debugger) = &self.debugger else {
+            return false;
+        };
+        let mut session = debugger.lock();
+        session.halted = session.should_halt(event_index, offset_ms);
+        session.halted
+    }
+
+    fn is_halted(&self) -> bool {
+        self.debugger.as_ref().is_some_and(|debugger| debugger.lock().halted)
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Returns `false` if playback was already paused.
+    pub fn pause(&self) -> bool {
+        !self.paused.swap(true, Ordering::Relaxed)
+    }
+
+    /// Returns `false` if playback was not paused.
+    pub fn resume(&self) -> bool {
+        self.paused.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// `stopping`, `halted`, `paused` or `playing`.
+    pub fn state(&self) -> &'static str {
+        if self.is_stopped() {
+            "stopping"
+        } else if self.is_halted() {
+            "halted"
+        } else if self.is_paused() {
+            "paused"
+        } else {
+            "playing"
+        }
+    }
+}
+
+/// Notifications raised by `run_playback` while it is running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaybackEvent {
+    /// Playback halted; `released` lists the inputs let go while paused.
+    Paused { released: HeldInputs },
+    /// Playback continued after a pause.
+    Resumed,
+    /// The event at `position` in the played list, from `event_index` in the
+    /// caller's list (`None` for events playback added), was due `at` after
+    /// the start of playback and injected `lateness` after that deadline.
+    Injected {
+        loop_index: u32,
+        position: usize,
+        event_index: Option<usize>,
+        at: Duration,
+        lateness: Duration,
+    },
+    /// Throttled position update, raised at most every `PROGRESS_INTERVAL`
+    /// and after the final event.
+    Progress(PlaybackProgress),
+    /// A debug playback halted before injecting `event`. `held` is what the
+    /// macro was holding; it is released while halted and pressed again when
+    /// playback continues.
+    Halted {
+        loop_index: u32,
+        event_index: usize,
+        event: MacroEvent,
+        held: HeldInputs,
+    },
+}
+
+/// Where playback is, as reported by `PlaybackEvent::Progress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaybackProgress {
+    /// Index in the caller's list of the event injected last, within its loop.
+    pub event_index: usize,
+    /// Length of the caller's list.
+    pub total_events: usize,
+    /// Zero-based loop the event belongs to.
+    pub loop_index: u32,
+    pub loop_count: u32,
+    /// Playback time so far, not counting pauses.
+    pub elapsed: Duration,
+    /// Time until the final event is due at the current playback speed.
+    pub remaining: Duration,
+}
+
+/// Minimum time between two `PlaybackEvent::Progress` notifications.
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+impl PlaybackEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlaybackEvent::Paused { .. } => "paused",
+            PlaybackEvent::Resumed => "resumed",
+            PlaybackEvent::Injected { .. } => "injected",
+            PlaybackEvent::Progress(_) => "progress",
+            PlaybackEvent::Halted { .. } => "halted",
+        }
+    }
 }
 
 #[tauri::command]
@@ -26,228 +329,1058 @@ pub fn play_macro(
     state: State<'_, AppState>,
     window: Window,
     request: MacroPlaybackRequest,
-) -> Result<(), String> {
+) -> Result<Option<MacroDryRun>, String> {
     if request.events.is_empty() {
         return Err("No macro events supplied".into());
     }
+    if request.timing.segments.iter().any(|segment| segment.start_event > segment.end_event) {
+        return Err("Speed segments must not end before they start".into());
+    }
 
-    let playback_speed = request.playback_speed.max(0.1);
-    let loop_count = request.loop_count.max(1);
-    let events = request.events.clone();
+    if !request.dry_run {
+        refuse_unknown_keys(&request.events)?;
+    }
+
+    let randomized = request.movement.is_some() || request.humanize.is_some();
+    let options = PlaybackOptions {
+        playback_speed: request.playback_speed.max(0.1),
+        loop_count: request.loop_count.max(1),
+        limits: request.limits.or(*state.playback_limits.lock()),
+        timing: request.timing.clone(),
+        humanize: request.humanize,
+        seed: randomized.then(|| request.seed.unwrap_or_else(rand::random)),
+    };
+    let mut events = select_range(&request.events, &PlaybackRange::from_request(&request))?;
+    if let (Some(movement), Some(seed)) = (&request.movement, options.seed) {
+        events = interpolate_moves(events, movement, seed);
+    }
     let context_id = request.context_id.clone();
 
+    if request.dry_run {
+        let screens = screen_areas(&window);
+        return Ok(Some(dry_run(&events, &options, &screens)));
+    }
+
+    let channel = request
+        .channel
+        .clone()
+        .or_else(|| context_id.clone())
+        .unwrap_or_else(|| DEFAULT_CHANNEL.into());
+    let control = if request.debug {
+        PlaybackControl::debugging(DebugSession::new(request.breakpoints.iter().copied()))
+    } else {
+        PlaybackControl::default()
+    };
+    start_playback(&state, &window, channel, events, options, control, context_id);
+
+    Ok(None)
+}
+
+/// Refuses to play `events` if any presses or releases a key whose label did
+/// not parse; playback would have to skip it.
+pub(crate) fn refuse_unknown_keys(events: &[MacroEvent]) -> Result<(), String> {
+    let unknown = events.iter().enumerate().find_map(|(index, event)| match &event.kind {
+        MacroEventKind::KeyDown { key } | MacroEventKind::KeyUp { key } if key.code == KeyCode::Unrecognized => {
+            Some((index, key.label()))
+        }
+        _ => None,
+    });
+    match unknown {
+        Some((index, label)) => Err(format!("Event {index} has the unknown key label `{label}`")),
+        None => Ok(()),
+    }
+}
+
+/// Plays `events` on a player thread for `channel` under `control`,
+/// replacing whatever that channel was playing.
+pub(crate) fn start_playback(
+    state: &AppState,
+    window: &Window,
+    channel: String,
+    events: impl Into<PlaybackEvents>,
+    options: PlaybackOptions,
+    control: PlaybackControl,
+    context_id: Option<String>,
+) {
+    let events = events.into();
     let mut player = state.macro_player.lock();
-    stop_macro_player(&mut player);
-    let stop_flag = Arc::new(AtomicBool::new(false));
-    let flag_clone = stop_flag.clone();
+    stop_macro_player(&mut player, &channel);
+    let control = Arc::new(control);
+    let control_clone = control.clone();
     let window_clone = window.clone();
+    let gate = player.gate();
+    let channel_clone = channel.clone();
+    let context_clone = context_id.clone();
+
+    let kill_control = control.clone();
+    let kill_guard = arm_kill_switch(state, window, "playback", move || kill_control.stop());
+    let injection_log = state.kill_switch.injection_log();
 
     let handle = thread::spawn(move || {
-        let mut enigo = Enigo::new();
-        let mut forced_stop = false;
-
-        'outer: for _ in 0..loop_count {
-            let mut last_offset = 0u64;
-            for event in &events {
-                if flag_clone.load(Ordering::Relaxed) {
-                    forced_stop = true;
-                    break 'outer;
-                }
+        let _kill_guard = kill_guard;
+        let marked = MarkedSink::new(EnigoSink::default(), injection_log);
+        let mut sink = GatedSink::new(marked, gate, control_clone.clone());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            play(
+                &mut sink,
+                &events,
+                &options,
+                &control_clone,
+                false,
+                playback_emitter(&window_clone, &channel_clone, context_clone.clone()),
+            )
+        }));
 
-                let delay_ms = event.offset_ms.saturating_sub(last_offset);
-                let adjusted_delay = (delay_ms as f32 / playback_speed).round() as u64;
-
-                if adjusted_delay > 0 {
-                    let mut waited = 0u64;
-                    while waited < adjusted_delay {
-                        if flag_clone.load(Ordering::Relaxed) {
-                            forced_stop = true;
-                            break 'outer;
-                        }
-                        let slice = std::cmp::min(5u64, adjusted_delay - waited);
-                        thread::sleep(Duration::from_millis(slice));
-                        waited += slice;
-                    }
+        // A panicking player has already released its held inputs while
+        // unwinding, but what it let go of is lost with the stack.
+        let payload = match result {
+            Ok(report) => finished_status(channel_clone, context_clone, &report),
+            Err(_) => {
+                let _ = window_clone.emit("macro://error", "Macro playback failed unexpectedly");
+                MacroPlaybackStatus {
+                    channel: channel_clone,
+                    context_id: context_clone,
+                    state: "error".into(),
+                    released: Vec::new(),
+                    limit: None,
+                    seed: options.seed,
                 }
+            }
+        };
+        let _ = window_clone.emit("macro://playback", payload);
+    });
 
-                if flag_clone.load(Ordering::Relaxed) {
-                    forced_stop = true;
-                    break 'outer;
-                }
+    player.insert(channel, control, handle, context_id);
+}
 
-                apply_macro_event(&mut enigo, &event.kind);
-                last_offset = event.offset_ms;
+/// Forwards the notifications of a playback to the frontend: state changes on
+/// `macro://playback`, throttled progress on `macro://progress` and debugger
+/// halts on `macro://debug`.
+pub(crate) fn playback_emitter<'a>(
+    window: &'a Window,
+    channel: &'a str,
+    context_id: Option<String>,
+) -> impl FnMut(PlaybackEvent) + 'a {
+    move |event| {
+        let released = match &event {
+            PlaybackEvent::Paused { released } => released.labels(),
+            PlaybackEvent::Resumed => Vec::new(),
+            PlaybackEvent::Injected { .. } => return,
+            PlaybackEvent::Progress(progress) => {
+                let payload = MacroPlaybackProgress::new(context_id.clone(), progress);
+                let _ = window.emit("macro://progress", payload);
+                return;
             }
-        }
-
+            PlaybackEvent::Halted {
+                loop_index,
+                event_index,
+                event,
+                held,
+            } => {
+                let payload = MacroDebugHalt {
+                    context_id: context_id.clone(),
+                    current_loop: loop_index + 1,
+                    event_index: *event_index,
+                    event: event.clone(),
+                    held: held.labels(),
+                };
+                let _ = window.emit("macro://debug", payload);
+                Vec::new()
+            }
+        };
         let payload = MacroPlaybackStatus {
-            context_id,
-            state: if forced_stop {
-                "stopped".into()
-            } else {
-                "finished".into()
-            },
+            channel: channel.into(),
+            context_id: context_id.clone(),
+            state: event.as_str().into(),
+            released,
+            limit: None,
+            seed: None,
         };
-        let _ = window_clone.emit("macro://playback", payload);
+        let _ = window.emit("macro://playback", payload);
+    }
+}
+
+/// The final `macro://playback` status for a playback that ran to its end.
+pub(crate) fn finished_status(
+    channel: String,
+    context_id: Option<String>,
+    report: &PlaybackReport,
+) -> MacroPlaybackStatus {
+    MacroPlaybackStatus {
+        channel,
+        context_id,
+        state: report.outcome.as_str().into(),
+        released: report.force_released.labels(),
+        limit: report.outcome.limit().map(|limit| limit.as_str().into()),
+        seed: report.seed,
+    }
+}
+
+/// Plays `events` against a `NullSink` in simulated time and returns the
+/// resulting timeline together with any problems found in the events. The
+/// event and loop limits cut the timeline short; the duration limit does not
+/// apply in simulated time.
+///
+/// Issues are reported against the caller's event list; one found in an event
+/// playback added is reported at the event it leads up to.
+pub fn dry_run(events: &PlaybackEvents, options: &PlaybackOptions, screens: &[ScreenArea]) -> MacroDryRun {
+    let mut timeline = Vec::new();
+    let control = PlaybackControl::default();
+    let mut sink = NullSink;
+    play(&mut sink, events, options, &control, true, |event| {
+        if let PlaybackEvent::Injected {
+            loop_index,
+            position,
+            event_index,
+            at,
+            ..
+        } = event
+        {
+            timeline.push(ScheduledMacroEvent {
+                at_ms: at.as_secs_f64() * 1000.0,
+                current_loop: loop_index + 1,
+                event_index,
+                kind: events.events[position].kind.clone(),
+            });
+        }
     });
 
-    player.stop_flag = Some(stop_flag);
-    player.handle = Some(handle);
+    let timing = events.timing_indices();
+    let mut issues: Vec<MacroIssue> = validate_events(&events.events, screens)
+        .into_iter()
+        .filter_map(|issue| {
+            let event_index = timing[issue.event_index]?;
+            Some(MacroIssue { event_index, ..issue })
+        })
+        .collect();
+    issues.sort_by_key(|issue| issue.event_index);
+    issues.dedup_by(|later, earlier| later.event_index == earlier.event_index && later.kind == earlier.kind);
 
+    MacroDryRun {
+        duration_ms: timeline.last().map_or(0.0, |entry| entry.at_ms),
+        timeline,
+        issues,
+    }
+}
+
+fn screen_areas(window: &Window) -> Vec<ScreenArea> {
+    let Ok(monitors) = window.available_monitors() else {
+        return Vec::new();
+    };
+    monitors
+        .iter()
+        .map(|monitor| ScreenArea {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_playback_limits(state: State<'_, AppState>) -> PlaybackLimits {
+    *state.playback_limits.lock()
+}
+
+/// Sets the limits applied to every playback whose request leaves them unset.
+#[tauri::command]
+pub fn set_playback_limits(state: State<'_, AppState>, limits: PlaybackLimits) -> Result<(), String> {
+    if limits.max_duration_ms == Some(0) || limits.max_events == Some(0) || limits.max_loops == Some(0) {
+        return Err("Playback limits must be at least 1".into());
+    }
+    *state.playback_limits.lock() = limits;
     Ok(())
 }
 
+/// Stops the playback on `channel`, or on every channel when none is named.
 #[tauri::command]
-pub fn stop_macro_playback(state: State<'_, AppState>) -> Result<(), String> {
+pub fn stop_macro_playback(state: State<'_, AppState>, channel: Option<String>) -> Result<(), String> {
     let mut player = state.macro_player.lock();
-    stop_macro_player(&mut player);
+    match channel {
+        Some(channel) => stop_macro_player(&mut player, &channel),
+        None => {
+            for handle in signal_macro_player_stop(&mut player) {
+                let _ = handle.join();
+            }
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_playback_channels(state: State<'_, AppState>) -> Vec<PlaybackChannelStatus> {
+    state.macro_player.lock().statuses()
+}
+
+#[tauri::command]
+pub fn pause_macro_playback(state: State<'_, AppState>, channel: Option<String>) -> Result<(), String> {
+    let player = state.macro_player.lock();
+    let control = player.running_control(channel.as_deref())?;
+    if !control.pause() {
+        return Err("Macro playback is already paused".into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn resume_macro_playback(state: State<'_, AppState>, channel: Option<String>) -> Result<(), String> {
+    let player = state.macro_player.lock();
+    let control = player.running_control(channel.as_deref())?;
+    if !control.resume() {
+        return Err("Macro playback is not paused".into());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn debug_step_macro(state: State<'_, AppState>, channel: Option<String>) -> Result<(), String> {
+    debug_resume(&state, channel.as_deref(), DebugRunMode::Step)
+}
+
+#[tauri::command]
+pub fn debug_continue_macro(state: State<'_, AppState>, channel: Option<String>) -> Result<(), String> {
+    debug_resume(&state, channel.as_deref(), DebugRunMode::Continue)
+}
+
+#[tauri::command]
+pub fn debug_run_to_offset(state: State<'_, AppState>, offset_ms: u64, channel: Option<String>) -> Result<(), String> {
+    debug_resume(&state, channel.as_deref(), DebugRunMode::RunToOffset(offset_ms))
+}
+
+#[tauri::command]
+pub fn set_macro_breakpoints(
+    state: State<'_, AppState>,
+    breakpoints: Vec<usize>,
+    channel: Option<String>,
+) -> Result<(), String> {
+    let player = state.macro_player.lock();
+    let control = player.running_control(channel.as_deref())?;
+    if !control.set_breakpoints(breakpoints) {
+        return Err("Macro playback is not in debug mode".into());
+    }
+    Ok(())
+}
+
+fn debug_resume(state: &State<'_, AppState>, channel: Option<&str>, mode: DebugRunMode) -> Result<(), String> {
+    let player = state.macro_player.lock();
+    let control = player.running_control(channel)?;
+    if !control.debug_resume(mode) {
+        return Err("Macro playback is not in debug mode".into());
+    }
     Ok(())
 }
 
-fn apply_macro_event(enigo: &mut Enigo, kind: &MacroEventKind) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackOutcome {
+    Finished,
+    Stopped,
+    /// A watchdog limit ended playback before it finished.
+    LimitReached(PlaybackLimit),
+}
+
+impl PlaybackOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlaybackOutcome::Finished => "finished",
+            PlaybackOutcome::Stopped => "stopped",
+            PlaybackOutcome::LimitReached(_) => "limit-reached",
+        }
+    }
+
+    pub fn limit(&self) -> Option<PlaybackLimit> {
+        match self {
+            PlaybackOutcome::LimitReached(limit) => Some(*limit),
+            _ => None,
+        }
+    }
+}
+
+/// Which of the `PlaybackLimits` ended a playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackLimit {
+    Duration,
+    Events,
+    Loops,
+}
+
+impl PlaybackLimit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlaybackLimit::Duration => "max-duration",
+            PlaybackLimit::Events => "max-events",
+            PlaybackLimit::Loops => "max-loops",
+        }
+    }
+}
+
+/// How a macro should be played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackOptions {
+    pub playback_speed: f32,
+    pub loop_count: u32,
+    pub limits: PlaybackLimits,
+    pub timing: PlaybackTiming,
+    pub humanize: Option<Humanize>,
+    /// Seed for `humanize`; a random one is drawn when unset.
+    pub seed: Option<u64>,
+}
+
+impl PlaybackOptions {
+    #[cfg(test)]
+    pub fn new(playback_speed: f32, loop_count: u32) -> Self {
+        Self {
+            playback_speed,
+            loop_count,
+            limits: PlaybackLimits::default(),
+            timing: PlaybackTiming::default(),
+            humanize: None,
+            seed: None,
+        }
+    }
+
+    /// How long to wait before an event recorded `gap_ms` after the one
+    /// before it. `event_index` is the caller's index the event is timed as,
+    /// which picks its speed segment; `None` plays at `playback_speed`.
+    pub fn delay(&self, event_index: Option<usize>, gap_ms: u64) -> Duration {
+        let timing = &self.timing;
+        if timing.as_fast_as_possible {
+            return Duration::from_millis(timing.min_gap_ms);
+        }
+        let playback_speed = event_index
+            .and_then(|event_index| {
+                timing
+                    .segments
+                    .iter()
+                    .find(|segment| (segment.start_event..=segment.end_event).contains(&event_index))
+            })
+            .map_or(self.playback_speed, |segment| segment.playback_speed.max(0.1));
+        let delay = scaled_offset(gap_ms, playback_speed);
+        match timing.max_gap_ms {
+            Some(max_gap_ms) => delay.min(Duration::from_millis(max_gap_ms)),
+            None => delay,
+        }
+    }
+}
+
+/// Result of a `run_playback` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaybackReport {
+    pub outcome: PlaybackOutcome,
+    /// Keys and buttons the macro left held at the end of a loop or when it
+    /// was stopped, which the player released on its behalf.
+    pub force_released: HeldInputs,
+    /// How late each injected event was against its deadline.
+    pub lateness: LatenessStats,
+    /// Number of inputs injected, across all loops, counted as for
+    /// `PlaybackLimits::max_events`.
+    pub injected: u64,
+    /// The seed of a randomized playback, as passed in `PlaybackOptions` or
+    /// drawn for `humanize`.
+    pub seed: Option<u64>,
+}
+
+/// Replays `events` into `sink`, honouring relative offsets and the speed,
+/// timing, loop count, limits and humanization in `options`. Returns early
+/// with `Stopped` once `control` is stopped, or with `LimitReached` as soon as
+/// a watchdog limit is hit. Every key and button still held at the end of a loop or when
+/// playback ends early is released and listed in the report.
+///
+/// Each event is due at a deadline measured from the start of playback, the
+/// sum of the delays before it as given by `PlaybackOptions::delay`, so timing
+/// error does not accumulate across events or loops. Offsets that run
+/// backwards are treated as "immediately after the previous event".
+///
+/// While `control` is paused the position in the event list, the loop counter
+/// and the remainder of the current delay are kept, and every key or button
+/// held at that moment is released. Resuming presses them again and carries on
+/// with the original relative timing. `max_duration_ms` counts wall-clock
+/// time, pauses included.
+pub fn run_playback<S: InputSink, F: FnMut(PlaybackEvent)>(
+    sink: &mut S,
+    events: &[MacroEvent],
+    options: &PlaybackOptions,
+    control: &PlaybackControl,
+    on_event: F,
+) -> PlaybackReport {
+    play(sink, &events.to_vec().into(), options, control, false, on_event)
+}
+
+/// `run_playback` of events that may have come through range selection or
+/// interpolation, optionally in simulated time: a simulated run treats every
+/// deadline as reached the moment it is asked for and ignores
+/// `max_duration_ms`.
+fn play<S: InputSink, F: FnMut(PlaybackEvent)>(
+    sink: &mut S,
+    events: &PlaybackEvents,
+    options: &PlaybackOptions,
+    control: &PlaybackControl,
+    simulated: bool,
+    on_event: F,
+) -> PlaybackReport {
+    let started = Instant::now();
+    let time_limit = options
+        .limits
+        .max_duration_ms
+        .filter(|_| !simulated)
+        .map(|limit| started + Duration::from_millis(limit));
+    let seed = match options.humanize {
+        Some(_) => Some(options.seed.unwrap_or_else(rand::random)),
+        None => options.seed,
+    };
+
+    let mut player = Player {
+        sink: TrackingSink::new(sink),
+        control,
+        on_event,
+        simulated,
+        time_limit,
+        clock: PlaybackClock::start(),
+        force_released: HeldInputs::default(),
+        lateness: LatenessStats::default(),
+        injected: 0,
+        last_progress: None,
+        humanizer: options.humanize.zip(seed).map(|(humanize, seed)| Humanizer::new(humanize, seed)),
+    };
+    let outcome = match player.play_loops(events, options) {
+        Ok(()) => PlaybackOutcome::Finished,
+        Err(outcome) => outcome,
+    };
+    let released = player.sink.release_all();
+    player.force_released.merge(released);
+
+    PlaybackReport {
+        outcome,
+        force_released: player.force_released,
+        lateness: player.lateness,
+        injected: player.injected,
+        seed,
+    }
+}
+
+/// Offsets of `events` from the start of a loop in recorded milliseconds,
+/// clamped so they never run backwards.
+fn loop_offsets(events: &[MacroEvent]) -> Vec<u64> {
+    let mut latest = 0u64;
+    events
+        .iter()
+        .map(|event| {
+            latest = latest.max(event.offset_ms);
+            latest
+        })
+        .collect()
+}
+
+/// The recorded gap before each of `offsets`; the first is measured from the
+/// start of the loop.
+fn loop_gaps(offsets: &[u64]) -> impl Iterator<Item = u64> + '_ {
+    offsets.iter().scan(0, |previous, &offset| {
+        let gap = offset - *previous;
+        *previous = offset;
+        Some(gap)
+    })
+}
+
+/// Playback either carries on (`Ok`) or ends early with the given outcome.
+type Flow<T = ()> = Result<T, PlaybackOutcome>;
+
+struct Player<'a, S: InputSink, F> {
+    sink: TrackingSink<S>,
+    control: &'a PlaybackControl,
+    on_event: F,
+    simulated: bool,
+    time_limit: Option<Instant>,
+    clock: PlaybackClock,
+    force_released: HeldInputs,
+    lateness: LatenessStats,
+    injected: u64,
+    last_progress: Option<Instant>,
+    humanizer: Option<Humanizer>,
+}
+
+impl<S: InputSink, F: FnMut(PlaybackEvent)> Player<'_, S, F> {
+    fn play_loops(&mut self, events: &PlaybackEvents, options: &PlaybackOptions) -> Flow {
+        let PlaybackOptions { loop_count, limits, .. } = *options;
+        let timing = events.timing_indices();
+        let recorded = loop_offsets(&events.events);
+        let loop_due: Duration = loop_gaps(&recorded)
+            .zip(&timing)
+            .map(|(gap, &event_index)| options.delay(event_index, gap))
+            .sum();
+        let total_due = loop_due.saturating_mul(loop_count);
+        let mut due = Duration::ZERO;
+
+        for loop_index in 0..loop_count {
+            if limits.max_loops.is_some_and(|max| loop_index >= max) {
+                return Err(PlaybackOutcome::LimitReached(PlaybackLimit::Loops));
+            }
+
+            let humanized;
+            let offsets = match &mut self.humanizer {
+                Some(humanizer) => {
+                    humanized = humanizer.loop_offsets(&events.events, &recorded);
+                    &humanized
+                }
+                None => &recorded,
+            };
+            for (position, (event, gap)) in events.events.iter().zip(loop_gaps(offsets)).enumerate() {
+                if limits.max_events.is_some_and(|max| self.injected >= max) {
+                    return Err(PlaybackOutcome::LimitReached(PlaybackLimit::Events));
+                }
+
+                let source = events.sources[position];
+                due = due.saturating_add(options.delay(timing[position], gap));
+                let lateness = self.wait_for(due)?;
+                if let Some(event_index) = source {
+                    if self.control.enter_halt(event_index, events.recorded_offset(position)) {
+                        self.halt(loop_index, event_index, event)?;
+                    }
+                }
+
+                if let Some(humanizer) = &mut self.humanizer {
+                    if humanizer.before_inject(&mut self.sink, &event.kind) {
+                        self.injected += 1;
+                    }
+                }
+                apply_macro_event(&mut self.sink, &event.kind);
+                self.injected += 1;
+                self.lateness.record(lateness);
+                (self.on_event)(PlaybackEvent::Injected {
+                    loop_index,
+                    position,
+                    event_index: source,
+                    at: due,
+                    lateness,
+                });
+
+                let Some(event_index) = timing[position] else {
+                    continue;
+                };
+                let last = loop_index + 1 == loop_count && position + 1 == events.len();
+                if self.progress_due(last) {
+                    let elapsed = self.clock.elapsed();
+                    (self.on_event)(PlaybackEvent::Progress(PlaybackProgress {
+                        event_index,
+                        total_events: events.source_len,
+                        loop_index,
+                        loop_count,
+                        elapsed,
+                        remaining: total_due.saturating_sub(elapsed),
+                    }));
+                }
+            }
+
+            let released = self.sink.release_all();
+            self.force_released.merge(released);
+        }
+
+        Ok(())
+    }
+
+    /// Whether a progress notification is due: `PROGRESS_INTERVAL` has passed
+    /// since the previous one, or `force` is set.
+    fn progress_due(&mut self, force: bool) -> bool {
+        let now = Instant::now();
+        let due = self
+            .last_progress
+            .is_none_or(|previous| now.duration_since(previous) >= PROGRESS_INTERVAL);
+        if force || due {
+            self.last_progress = Some(now);
+            return true;
+        }
+        false
+    }
+
+    /// Ends playback if it was stopped or has run out of wall-clock time.
+    fn interrupted(&self) -> Flow {
+        if self.control.is_stopped() {
+            return Err(PlaybackOutcome::Stopped);
+        }
+        if self.time_limit.is_some_and(|limit| Instant::now() >= limit) {
+            return Err(PlaybackOutcome::LimitReached(PlaybackLimit::Duration));
+        }
+        Ok(())
+    }
+
+    /// Sits out a debugger halt before `event`, moving the clock on by the time
+    /// spent halted.
+    fn halt(&mut self, loop_index: u32, event_index: usize, event: &MacroEvent) -> Flow {
+        let halted_at = Instant::now();
+        let held = self.sink.release_all();
+        (self.on_event)(PlaybackEvent::Halted {
+            loop_index,
+            event_index,
+            event: event.clone(),
+            held: held.clone(),
+        });
+
+        while self.control.is_halted() {
+            self.interrupted()?;
+            thread::sleep(MAX_SLEEP_SLICE);
+        }
+        self.interrupted()?;
+
+        self.clock.shift(halted_at.elapsed());
+        self.sink.press_all(&held);
+        (self.on_event)(PlaybackEvent::Resumed);
+        Ok(())
+    }
+
+    /// Waits until `due` after the start of playback, sitting out any pauses.
+    /// Returns how late the wait finished.
+    fn wait_for(&mut self, due: Duration) -> Flow<Duration> {
+        if self.simulated {
+            return Ok(Duration::ZERO);
+        }
+
+        let control = self.control;
+        loop {
+            self.checkpoint()?;
+            let deadline = self.clock.deadline(due);
+            let wake = self.time_limit.map_or(deadline, |limit| limit.min(deadline));
+            match wait_until(wake, || control.is_stopped() || control.is_paused()) {
+                WaitOutcome::Reached { lateness } if wake == deadline => return Ok(lateness),
+                WaitOutcome::Reached { .. } | WaitOutcome::Interrupted => continue,
+            }
+        }
+    }
+
+    /// Blocks while playback is paused, moving the clock on by the time spent
+    /// paused.
+    fn checkpoint(&mut self) -> Flow {
+        self.interrupted()?;
+        if !self.control.is_paused() {
+            return Ok(());
+        }
+
+        let paused_at = Instant::now();
+        let released = self.sink.release_all();
+        (self.on_event)(PlaybackEvent::Paused {
+            released: released.clone(),
+        });
+
+        while self.control.is_paused() {
+            self.interrupted()?;
+            thread::sleep(MAX_SLEEP_SLICE);
+        }
+        self.interrupted()?;
+
+        self.clock.shift(paused_at.elapsed());
+        self.sink.press_all(&released);
+        (self.on_event)(PlaybackEvent::Resumed);
+        Ok(())
+    }
+}
+
+fn apply_macro_event<S: InputSink>(sink: &mut S, kind: &MacroEventKind) {
     match kind {
         MacroEventKind::MouseMove { x, y } => {
-            enigo.mouse_move_to(*x, *y);
+            sink.mouse_move_to(*x, *y);
         }
         MacroEventKind::MouseDown { button } => {
-            enigo.mouse_down(parse_mouse_button(button));
+            sink.mouse_down(*button);
         }
         MacroEventKind::MouseUp { button } => {
-            enigo.mouse_up(parse_mouse_button(button));
+            sink.mouse_up(*button);
         }
         MacroEventKind::KeyDown { key } => {
-            send_key_event(enigo, key, true);
+            sink.key_down(key.code);
         }
         MacroEventKind::KeyUp { key } => {
-            send_key_event(enigo, key, false);
+            sink.key_up(key.code);
         }
         MacroEventKind::Scroll { delta_x, delta_y } => {
-            if *delta_y != 0 {
-                enigo.mouse_scroll_y(*delta_y as i32);
-            }
-            if *delta_x != 0 {
-                enigo.mouse_scroll_x(*delta_x as i32);
-            }
+            sink.scroll(*delta_x as i32, *delta_y as i32);
         }
     }
 }
 
-fn send_key_event(enigo: &mut Enigo, label: &str, pressed: bool) {
-    if let Some(key) = label_to_enigo_key(label) {
-        if pressed {
-            enigo.key_down(key);
-        } else {
-            enigo.key_up(key);
-        }
-    } else if pressed {
-        let fallback = key_label_primary_segment(label);
-        if !fallback.is_empty() {
-            enigo.key_sequence(fallback);
-        }
-    }
-}
-
-fn key_label_primary_segment(label: &str) -> &str {
-    label
-        .rsplit('+')
-        .next()
-        .map(|segment| segment.trim())
-        .unwrap_or_else(|| label.trim())
-}
-
-fn label_to_enigo_key(label: &str) -> Option<Key> {
-    let segment = key_label_primary_segment(label);
-    if segment.is_empty() {
-        return None;
-    }
-
-    let normalized = segment.to_lowercase();
-    let key = match normalized.as_str() {
-        "enter" | "return" => Some(Key::Return),
-        "tab" => Some(Key::Tab),
-        "space" => Some(Key::Space),
-        "backspace" => Some(Key::Backspace),
-        "escape" | "esc" => Some(Key::Escape),
-        "capslock" => Some(Key::CapsLock),
-        "home" => Some(Key::Home),
-        "end" => Some(Key::End),
-        "pageup" => Some(Key::PageUp),
-        "pagedown" => Some(Key::PageDown),
-        #[cfg(not(target_os = "macos"))]
-        "insert" => Some(Key::Insert),
-        "delete" => Some(Key::Delete),
-        "up" | "uparrow" => Some(Key::UpArrow),
-        "down" | "downarrow" => Some(Key::DownArrow),
-        "left" | "leftarrow" => Some(Key::LeftArrow),
-        "right" | "rightarrow" => Some(Key::RightArrow),
-        "shift" => Some(Key::Shift),
-        "ctrl" | "control" => Some(Key::Control),
-        "alt" | "altgr" => Some(Key::Alt),
-        "meta" | "command" | "cmd" | "super" => Some(Key::Meta),
-        "f1" => Some(Key::F1),
-        "f2" => Some(Key::F2),
-        "f3" => Some(Key::F3),
-        "f4" => Some(Key::F4),
-        "f5" => Some(Key::F5),
-        "f6" => Some(Key::F6),
-        "f7" => Some(Key::F7),
-        "f8" => Some(Key::F8),
-        "f9" => Some(Key::F9),
-        "f10" => Some(Key::F10),
-        "f11" => Some(Key::F11),
-        "f12" => Some(Key::F12),
-        _ => None,
+impl MacroPlaybackProgress {
+    fn new(context_id: Option<String>, progress: &PlaybackProgress) -> Self {
+        Self {
+            context_id,
+            event_index: progress.event_index,
+            total_events: progress.total_events,
+            current_loop: progress.loop_index + 1,
+            loop_count: progress.loop_count,
+            elapsed_ms: progress.elapsed.as_millis() as u64,
+            remaining_ms: progress.remaining.as_millis() as u64,
+        }
+    }
+}
+
+/// Stops the playback on `channel` and waits for it to release its held
+/// inputs.
+pub(crate) fn stop_macro_player(player: &mut MacroPlaybackState, channel: &str) {
+    if let Some(channel) = player.channels.remove(channel) {
+        channel.control.stop();
+        let _ = channel.handle.join();
+    }
+}
+
+/// Tells every player thread to stop and hands back their handles so the
+/// caller can wait for them to release their held inputs.
+pub(crate) fn signal_macro_player_stop(player: &mut MacroPlaybackState) -> Vec<thread::JoinHandle<()>> {
+    player
+        .channels
+        .drain()
+        .map(|(_, channel)| {
+            channel.control.stop();
+            channel.handle
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input_sink::{RecordingSink, SinkEvent},
+        types::{Humanize, KeyStroke, MouseButton, MouseMovement, MovementModel, SpeedSegment},
     };
 
-    if key.is_some() {
-        return key;
+    fn event(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
     }
 
-    if let Some(stripped) = normalized.strip_prefix("numpad") {
-        if stripped.is_empty() {
-            return None;
-        }
-        let symbol = match stripped {
-            "+" => Some('+'),
-            "-" => Some('-'),
-            "*" => Some('*'),
-            "/" => Some('/'),
-            _ => stripped.chars().next(),
-        };
-        if let Some(ch) = symbol {
-            let value = if ch.is_ascii_alphabetic() {
-                ch.to_ascii_lowercase()
-            } else {
-                ch
-            };
-            return Some(Key::Layout(value));
+    fn click_macro() -> Vec<MacroEvent> {
+        vec![
+            event(0, MacroEventKind::MouseMove { x: 10, y: 20 }),
+            event(40, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            event(80, MacroEventKind::MouseUp { button: MouseButton::Left }),
+        ]
+    }
+
+    /// Plays `events` into a `RecordingSink`, returning the report, what the
+    /// sink received and the deadline of every injected event.
+    fn record(events: &[MacroEvent], options: &PlaybackOptions) -> (PlaybackReport, RecordingSink, Vec<Duration>) {
+        let mut sink = RecordingSink::default();
+        let mut deadlines = Vec::new();
+        let control = PlaybackControl::default();
+        let report = run_playback(&mut sink, events, options, &control, |event| {
+            if let PlaybackEvent::Injected { at, .. } = event {
+                deadlines.push(at);
+            }
+        });
+        (report, sink, deadlines)
+    }
+
+    /// Plays `count` key events 1 ms apart and returns how late they were and
+    /// how far after its recorded offset the last one arrived.
+    fn drift(count: u64) -> (LatenessStats, Duration) {
+        let events: Vec<MacroEvent> = (1..=count)
+            .map(|offset_ms| {
+                let key = KeyStroke::new(KeyCode::Char('a'));
+                match offset_ms % 2 {
+                    1 => event(offset_ms, MacroEventKind::KeyDown { key }),
+                    _ => event(offset_ms, MacroEventKind::KeyUp { key }),
+                }
+            })
+            .collect();
+        let (report, sink, _) = record(&events, &PlaybackOptions::new(1.0, 1));
+        let last = sink.inputs().last().expect("playback injected nothing").at;
+        (report.lateness, last.saturating_sub(Duration::from_millis(count)))
+    }
+
+    #[test]
+    fn plays_events_in_order_for_every_loop() {
+        let (report, sink, _) = record(&click_macro(), &PlaybackOptions::new(10.0, 3));
+
+        let one_loop = [
+            SinkEvent::MouseMove { x: 10, y: 20 },
+            SinkEvent::MouseDown(MouseButton::Left),
+            SinkEvent::MouseUp(MouseButton::Left),
+        ];
+        assert_eq!(sink.events(), vec![one_loop.to_vec(); 3].concat());
+        assert_eq!(report.outcome, PlaybackOutcome::Finished);
+        assert_eq!(report.injected, 9);
+        assert!(report.force_released.is_empty());
+    }
+
+    #[test]
+    fn scales_offsets_by_playback_speed() {
+        let (_, sink, deadlines) = record(&click_macro(), &PlaybackOptions::new(2.0, 2));
+
+        let expected: Vec<Duration> = [0, 20, 40, 40, 60, 80].into_iter().map(Duration::from_millis).collect();
+        assert_eq!(deadlines, expected);
+        for (input, due) in sink.inputs().iter().zip(&expected) {
+            assert!(input.at >= *due, "{:?} injected at {:?}, before its deadline {due:?}", input.event, input.at);
         }
     }
 
-    if segment.chars().count() == 1 {
-        let mut ch = segment.chars().next().unwrap();
-        if ch.is_ascii_uppercase() {
-            ch = ch.to_ascii_lowercase();
+    #[test]
+    fn releases_inputs_left_held_at_the_end_of_a_loop() {
+        let events = vec![
+            event(0, MacroEventKind::KeyDown { key: KeyStroke::new(KeyCode::Shift) }),
+            event(5, MacroEventKind::MouseDown { button: MouseButton::Right }),
+        ];
+        let (report, sink, _) = record(&events, &PlaybackOptions::new(10.0, 2));
+
+        let one_loop = [
+            SinkEvent::KeyDown(KeyCode::Shift),
+            SinkEvent::MouseDown(MouseButton::Right),
+            SinkEvent::MouseUp(MouseButton::Right),
+            SinkEvent::KeyUp(KeyCode::Shift),
+        ];
+        assert_eq!(sink.events(), vec![one_loop.to_vec(); 2].concat());
+        assert_eq!(report.force_released.keys, vec![KeyCode::Shift]);
+        assert_eq!(report.force_released.buttons, vec![MouseButton::Right]);
+    }
+
+    #[test]
+    fn stops_at_the_loop_limit() {
+        let mut options = PlaybackOptions::new(10.0, 5);
+        options.limits.max_loops = Some(2);
+        let (report, sink, _) = record(&click_macro(), &options);
+
+        assert_eq!(report.outcome, PlaybackOutcome::LimitReached(PlaybackLimit::Loops));
+        assert_eq!(sink.events().len(), 6);
+    }
+
+    #[test]
+    fn click_nudges_count_toward_the_event_limit() {
+        let mut options = PlaybackOptions::new(10.0, 1);
+        options.humanize = Some(Humanize {
+            click_radius_px: 50,
+            ..Humanize::default()
+        });
+        options.seed = Some(7);
+        options.limits.max_events = Some(3);
+        let (report, sink, _) = record(&click_macro(), &options);
+
+        assert_eq!(report.outcome, PlaybackOutcome::LimitReached(PlaybackLimit::Events));
+        assert_eq!(report.injected, 3);
+        assert!(matches!(
+            sink.events()[..],
+            [SinkEvent::MouseMove { .. }, SinkEvent::MouseMove { .. }, SinkEvent::MouseDown(MouseButton::Left), ..]
+        ));
+    }
+
+    #[test]
+    fn refuses_keys_whose_label_did_not_parse() {
+        let mut events = shifted_click();
+        events[4].kind = MacroEventKind::KeyUp { key: KeyStroke::unrecognized("Hyper".into()) };
+
+        assert_eq!(refuse_unknown_keys(&shifted_click()), Ok(()));
+        assert_eq!(refuse_unknown_keys(&events), Err("Event 4 has the unknown key label `Hyper`".into()));
+    }
+
+    /// Shift held, the cursor parked, then a click and the release of Shift.
+    fn shifted_click() -> Vec<MacroEvent> {
+        vec![
+            event(0, MacroEventKind::KeyDown { key: KeyStroke::new(KeyCode::Shift) }),
+            event(100, MacroEventKind::MouseMove { x: 5, y: 5 }),
+            event(200, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            event(300, MacroEventKind::MouseUp { button: MouseButton::Left }),
+            event(400, MacroEventKind::KeyUp { key: KeyStroke::new(KeyCode::Shift) }),
+        ]
+    }
+
+    fn from_event(start_event: usize) -> PlaybackRange {
+        PlaybackRange {
+            start_event: Some(start_event),
+            ..PlaybackRange::default()
         }
-        return Some(Key::Layout(ch));
     }
 
-    None
-}
+    #[test]
+    fn ranges_are_reported_by_the_callers_indices() {
+        let events = select_range(&shifted_click(), &from_event(2)).unwrap();
+
+        let timeline = dry_run(&events, &PlaybackOptions::new(1.0, 1), &[]).timeline;
 
-fn parse_mouse_button(button: &str) -> EnigoMouseButton {
-    match button {
-        "right" => EnigoMouseButton::Right,
-        "middle" => EnigoMouseButton::Middle,
-        _ => EnigoMouseButton::Left,
+        let scheduled: Vec<(Option<usize>, f64)> =
+            timeline.iter().map(|entry| (entry.event_index, entry.at_ms)).collect();
+        assert_eq!(scheduled, [(None, 0.0), (None, 0.0), (Some(2), 0.0), (Some(3), 100.0), (Some(4), 200.0)]);
     }
-}
 
-fn stop_macro_player(player: &mut MacroPlaybackState) {
-    if let Some(flag) = player.stop_flag.take() {
-        flag.store(true, Ordering::Relaxed);
+    #[test]
+    fn ranges_halt_and_report_progress_by_the_callers_indices() {
+        let events = select_range(&shifted_click(), &from_event(2)).unwrap();
+        let control = PlaybackControl::debugging(DebugSession::new([3]));
+        let mut halts = Vec::new();
+        let mut progress = None;
+
+        play(&mut NullSink, &events, &PlaybackOptions::new(100.0, 1), &control, false, |event| match event {
+            PlaybackEvent::Halted { event_index, .. } => {
+                halts.push(event_index);
+                control.debug_resume(DebugRunMode::Continue);
+            }
+            PlaybackEvent::Progress(update) => progress = Some(update),
+            _ => {}
+        });
+
+        assert_eq!(halts, [2, 3]);
+        let progress = progress.unwrap();
+        assert_eq!((progress.event_index, progress.total_events), (4, 5));
     }
-    if let Some(handle) = player.handle.take() {
-        let _ = handle.join();
+
+    #[test]
+    fn interpolated_moves_neither_halt_nor_shift_progress() {
+        let events = vec![
+            event(0, MacroEventKind::MouseMove { x: 0, y: 0 }),
+            event(100, MacroEventKind::MouseMove { x: 100, y: 0 }),
+            event(150, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            event(160, MacroEventKind::MouseUp { button: MouseButton::Left }),
+        ];
+        let movement = MouseMovement {
+            model: MovementModel::Linear,
+            rate_hz: 100,
+        };
+        let events = interpolate_moves(events.into(), &movement, 7);
+        assert!(events.len() > 4);
+        let control = PlaybackControl::debugging(DebugSession::new([2]));
+        let mut halts = Vec::new();
+        let mut progress = None;
+
+        play(&mut NullSink, &events, &PlaybackOptions::new(100.0, 1), &control, false, |event| match event {
+            PlaybackEvent::Halted { event_index, .. } => {
+                halts.push(event_index);
+                control.debug_resume(DebugRunMode::Continue);
+            }
+            PlaybackEvent::Progress(update) => progress = Some(update),
+            _ => {}
+        });
+
+        assert_eq!(halts, [0, 2]);
+        let progress = progress.unwrap();
+        assert_eq!((progress.event_index, progress.total_events), (3, 4));
+    }
+
+    #[test]
+    fn speed_segments_follow_the_callers_indices() {
+        let events = select_range(&shifted_click(), &from_event(2)).unwrap();
+        let mut options = PlaybackOptions::new(1.0, 1);
+        options.timing.segments = vec![SpeedSegment {
+            start_event: 3,
+            end_event: 3,
+            playback_speed: 2.0,
+        }];
+
+        let timeline = dry_run(&events, &options, &[]).timeline;
+
+        let at_ms: Vec<f64> = timeline.iter().map(|entry| entry.at_ms).collect();
+        assert_eq!(at_ms, [0.0, 0.0, 0.0, 50.0, 150.0]);
+    }
+
+    #[test]
+    fn keeps_to_the_recorded_timeline() {
+        let (lateness, drift) = drift(500);
+        assert_eq!(lateness.samples, 500);
+        assert!(lateness.mean() < Duration::from_millis(5), "{lateness:?}");
+        assert!(lateness.max < Duration::from_millis(50), "{lateness:?}");
+        assert!(drift < Duration::from_millis(50), "drifted {drift:?}");
+    }
+
+    /// The full-length drift check; run it on a quiet machine with
+    /// `cargo test --release -- --ignored does_not_drift_over_a_long_macro`.
+    #[test]
+    #[ignore = "plays for ten seconds"]
+    fn does_not_drift_over_a_long_macro() {
+        let (lateness, drift) = drift(10_000);
+        assert_eq!(lateness.samples, 10_000);
+        assert!(lateness.mean() < Duration::from_millis(1), "{lateness:?}");
+        assert!(lateness.max < Duration::from_millis(25), "{lateness:?}");
+        assert!(drift < Duration::from_millis(25), "drifted {drift:?}");
     }
 }
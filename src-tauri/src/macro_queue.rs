@@ -0,0 +1,525 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::{
+    app_state::AppState,
+    injection_gate::GatedSink,
+    input_sink::{EnigoSink, InputSink},
+    kill_switch::{arm_kill_switch, MarkedSink},
+    macro_player::{
+        finished_status,
+        playback_emitter,
+        refuse_unknown_keys,
+        run_playback,
+        stop_macro_player,
+        PlaybackControl,
+        PlaybackOptions,
+        PlaybackOutcome,
+    },
+    playback_clock::{wait_until, WaitOutcome, MAX_SLEEP_SLICE},
+    types::{MacroQueueState, PlaybackTiming, QueueSettings},
+};
+
+const QUEUE_STATE_CHANNEL: &str = "macro://queue-state";
+
+/// Playback channel the queue plays on.
+pub const QUEUE_PLAYBACK_CHANNEL: &str = "queue";
+
+/// Ordered list of stored macro ids played back-to-back on the player thread.
+/// The list can be edited while it runs; the running pass picks up the change
+/// from the entry after the one playing.
+#[derive(Default)]
+pub struct QueueEngine {
+    entries: Vec<String>,
+    settings: QueueSettings,
+    run: Option<QueueRun>,
+}
+
+struct QueueRun {
+    control: Arc<PlaybackControl>,
+    /// Index of the next entry to start.
+    cursor: usize,
+    /// Whether the entry before `cursor` is playing or about to play.
+    active: bool,
+    cycle: u32,
+}
+
+impl QueueEngine {
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn settings(&self) -> QueueSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: QueueSettings) {
+        self.settings = settings;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Index of the entry playing, or waiting out its padding before it plays.
+    pub fn current_index(&self) -> Option<usize> {
+        self.run.as_ref().filter(|run| run.active).map(|run| run.cursor - 1)
+    }
+
+    /// Inserts `id` at `position`, or appends it when `position` is `None` or
+    /// past the end.
+    pub fn enqueue(&mut self, id: String, position: Option<usize>) {
+        let position = position.unwrap_or(self.entries.len()).min(self.entries.len());
+        self.entries.insert(position, id);
+        if let Some(run) = &mut self.run {
+            if position < run.cursor {
+                run.cursor += 1;
+            }
+        }
+    }
+
+    pub fn dequeue(&mut self, index: usize) -> Result<String, String> {
+        if index >= self.entries.len() {
+            return Err(missing_entry(index));
+        }
+        let removed = self.entries.remove(index);
+        if let Some(run) = &mut self.run {
+            if run.active && index + 1 == run.cursor {
+                run.active = false;
+            }
+            if index < run.cursor {
+                run.cursor -= 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Moves the entry at `from` so it ends up at index `to`.
+    pub fn reorder(&mut self, from: usize, to: usize) -> Result<(), String> {
+        if to >= self.entries.len() {
+            return Err(missing_entry(to));
+        }
+        let moves_current = self.current_index() == Some(from);
+        let entry = self.dequeue(from)?;
+        self.enqueue(entry, Some(to));
+        if let (true, Some(run)) = (moves_current, &mut self.run) {
+            run.cursor = to + 1;
+            run.active = true;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        if let Some(run) = &mut self.run {
+            run.cursor = 0;
+            run.active = false;
+        }
+    }
+
+    pub fn snapshot(&self) -> MacroQueueState {
+        MacroQueueState {
+            queue: self.entries.clone(),
+            padding_ms: self.settings.padding_ms,
+            loop_enabled: self.settings.loop_enabled,
+            loop_delay_ms: self.settings.loop_delay_ms,
+            running: self.is_running(),
+            current_index: self.current_index(),
+            cycle: self.run.as_ref().map_or(0, |run| run.cycle),
+        }
+    }
+
+    /// Moves the run on to its next entry. Returns the entry's id and whether
+    /// it opens a pass through the queue, or `None` once the pass is over.
+    fn advance(&mut self) -> Option<(String, bool)> {
+        let run = self.run.as_mut()?;
+        let id = self.entries.get(run.cursor)?.clone();
+        let first = run.cursor == 0;
+        run.cursor += 1;
+        run.active = true;
+        Some((id, first))
+    }
+
+    fn finish_entry(&mut self) {
+        if let Some(run) = &mut self.run {
+            run.active = false;
+        }
+    }
+
+    /// Starts the next pass if looping is on and there is anything to play.
+    fn start_cycle(&mut self) -> bool {
+        let looping = self.settings.loop_enabled && !self.entries.is_empty();
+        match &mut self.run {
+            Some(run) if looping => {
+                run.cursor = 0;
+                run.active = false;
+                run.cycle += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_run_of(&self, control: &Arc<PlaybackControl>) -> bool {
+        self.run.as_ref().is_some_and(|run| Arc::ptr_eq(&run.control, control))
+    }
+}
+
+fn missing_entry(index: usize) -> String {
+    format!("No queue entry at index {index}")
+}
+
+fn emit_queue_state(window: &Window, queue: &QueueEngine) {
+    let _ = window.emit(QUEUE_STATE_CHANNEL, queue.snapshot());
+}
+
+/// Plays the queue until it runs out, a macro ends early or `control` is
+/// stopped, or a pass finds none of its macros. Each entry plays with its own speed and loop count and the
+/// current playback limits; `padding_ms` separates the last event of one
+/// entry from the start of the next.
+fn run_queue<S: InputSink>(window: &Window, control: &Arc<PlaybackControl>, sink: &mut S) {
+    let state = window.state::<AppState>();
+    let mut played_in_cycle = false;
+
+    loop {
+        let next = {
+            let mut queue = state.queue.lock();
+            let next = queue.advance();
+            if next.is_some() {
+                emit_queue_state(window, &queue);
+            }
+            next
+        };
+
+        let Some((id, first)) = next else {
+            let loop_delay = {
+                let queue = state.queue.lock();
+                queue.settings.loop_enabled.then_some(queue.settings.loop_delay_ms)
+            };
+            // A pass that found none of its macros would otherwise repeat forever.
+            let Some(loop_delay) = loop_delay.filter(|_| played_in_cycle) else {
+                return;
+            };
+            played_in_cycle = false;
+            if !wait_gap(control, Duration::from_millis(loop_delay)) {
+                return;
+            }
+            let mut queue = state.queue.lock();
+            if !queue.start_cycle() {
+                return;
+            }
+            emit_queue_state(window, &queue);
+            continue;
+        };
+
+        if !first {
+            let padding = state.queue.lock().settings.padding_ms;
+            if !wait_gap(control, Duration::from_millis(padding)) {
+                return;
+            }
+        }
+
+        let stored = state.macro_store.lock().get(&id).cloned();
+        let Some(stored) = stored else {
+            let _ = window.emit("macro://error", format!("Queued macro `{id}` no longer exists"));
+            let mut queue = state.queue.lock();
+            queue.finish_entry();
+            emit_queue_state(window, &queue);
+            continue;
+        };
+        let options = PlaybackOptions {
+            playback_speed: stored.playback_speed.map_or(1.0, |speed| speed as f32).max(0.1),
+            loop_count: stored.loop_count.max(1),
+            limits: *state.playback_limits.lock(),
+            timing: PlaybackTiming::default(),
+            humanize: None,
+            seed: None,
+        };
+
+        let events = stored.macro_events();
+        if let Err(error) = refuse_unknown_keys(&events) {
+            let _ = window.emit("macro://error", format!("Queued macro `{}`: {error}", stored.name));
+            let mut queue = state.queue.lock();
+            queue.finish_entry();
+            emit_queue_state(window, &queue);
+            continue;
+        }
+        let context_id = Some(id);
+        let report = run_playback(
+            sink,
+            &events,
+            &options,
+            control,
+            playback_emitter(window, QUEUE_PLAYBACK_CHANNEL, context_id.clone()),
+        );
+        let _ = window.emit("macro://playback", finished_status(QUEUE_PLAYBACK_CHANNEL.into(), context_id, &report));
+        played_in_cycle = true;
+
+        let mut queue = state.queue.lock();
+        queue.finish_entry();
+        emit_queue_state(window, &queue);
+        if report.outcome != PlaybackOutcome::Finished {
+            return;
+        }
+    }
+}
+
+/// Waits `gap`, sitting out any pause. Returns `false` if `control` was
+/// stopped first.
+fn wait_gap(control: &PlaybackControl, gap: Duration) -> bool {
+    let mut deadline = Instant::now() + gap;
+    loop {
+        if let WaitOutcome::Reached { .. } = wait_until(deadline, || control.is_stopped() || control.is_paused()) {
+            return true;
+        }
+
+        let paused_at = Instant::now();
+        while control.is_paused() && !control.is_stopped() {
+            thread::sleep(MAX_SLEEP_SLICE);
+        }
+        if control.is_stopped() {
+            return false;
+        }
+        deadline += paused_at.elapsed();
+    }
+}
+
+#[tauri::command]
+pub fn get_queue_state(state: State<'_, AppState>) -> MacroQueueState {
+    state.queue.lock().snapshot()
+}
+
+#[tauri::command]
+pub fn enqueue_macro(
+    state: State<'_, AppState>,
+    window: Window,
+    id: String,
+    position: Option<usize>,
+) -> Result<MacroQueueState, String> {
+    if state.macro_store.lock().get(&id).is_none() {
+        return Err(format!("No macro with id `{id}`"));
+    }
+    let mut queue = state.queue.lock();
+    queue.enqueue(id, position);
+    emit_queue_state(&window, &queue);
+    Ok(queue.snapshot())
+}
+
+#[tauri::command]
+pub fn dequeue_macro(state: State<'_, AppState>, window: Window, index: usize) -> Result<MacroQueueState, String> {
+    let mut queue = state.queue.lock();
+    queue.dequeue(index)?;
+    emit_queue_state(&window, &queue);
+    Ok(queue.snapshot())
+}
+
+#[tauri::command]
+pub fn reorder_queue(
+    state: State<'_, AppState>,
+    window: Window,
+    from: usize,
+    to: usize,
+) -> Result<MacroQueueState, String> {
+    let mut queue = state.queue.lock();
+    queue.reorder(from, to)?;
+    emit_queue_state(&window, &queue);
+    Ok(queue.snapshot())
+}
+
+#[tauri::command]
+pub fn clear_queue(state: State<'_, AppState>, window: Window) -> MacroQueueState {
+    let mut queue = state.queue.lock();
+    queue.clear();
+    emit_queue_state(&window, &queue);
+    queue.snapshot()
+}
+
+#[tauri::command]
+pub fn set_queue_settings(state: State<'_, AppState>, window: Window, settings: QueueSettings) -> MacroQueueState {
+    let mut queue = state.queue.lock();
+    queue.set_settings(settings);
+    emit_queue_state(&window, &queue);
+    queue.snapshot()
+}
+
+/// Starts the queue from its first entry on the `queue` playback channel.
+/// Pause, resume and stop for that channel apply to the queue as a whole.
+#[tauri::command]
+pub fn start_queue(state: State<'_, AppState>, window: Window) -> Result<(), String> {
+    if state.queue.lock().entries.is_empty() {
+        return Err("The queue is empty".into());
+    }
+
+    let mut player = state.macro_player.lock();
+    stop_macro_player(&mut player, QUEUE_PLAYBACK_CHANNEL);
+    let control = Arc::new(PlaybackControl::default());
+    {
+        let mut queue = state.queue.lock();
+        queue.run = Some(QueueRun {
+            control: control.clone(),
+            cursor: 0,
+            active: false,
+            cycle: 1,
+        });
+        emit_queue_state(&window, &queue);
+    }
+
+    let kill_control = control.clone();
+    let kill_guard = arm_kill_switch(&state, &window, "playback", move || kill_control.stop());
+    let injection_log = state.kill_switch.injection_log();
+    let control_clone = control.clone();
+    let gate = player.gate();
+
+    let handle = thread::spawn(move || {
+        let _kill_guard = kill_guard;
+        let marked = MarkedSink::new(EnigoSink::default(), injection_log);
+        let mut sink = GatedSink::new(marked, gate, control_clone.clone());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| run_queue(&window, &control_clone, &mut sink)));
+        if result.is_err() {
+            let _ = window.emit("macro://error", "Queue playback failed unexpectedly");
+        }
+
+        let state = window.state::<AppState>();
+        let mut queue = state.queue.lock();
+        if queue.is_run_of(&control_clone) {
+            queue.run = None;
+        }
+        emit_queue_state(&window, &queue);
+    });
+
+    player.insert(QUEUE_PLAYBACK_CHANNEL.into(), control, handle, None);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_queue(state: State<'_, AppState>) -> Result<(), String> {
+    let mut player = state.macro_player.lock();
+    let control = state.queue.lock().run.as_ref().map(|run| run.control.clone());
+    let control = control.ok_or("The queue is not running")?;
+    let playing = player.channel(QUEUE_PLAYBACK_CHANNEL).map(|channel| &channel.control);
+    if playing.is_some_and(|playing| Arc::ptr_eq(playing, &control)) {
+        stop_macro_player(&mut player, QUEUE_PLAYBACK_CHANNEL);
+    } else {
+        control.stop();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A queue of `ids` that has been started but not yet advanced.
+    fn running(ids: &[&str]) -> QueueEngine {
+        let mut queue = QueueEngine::default();
+        for id in ids {
+            queue.enqueue((*id).into(), None);
+        }
+        queue.run = Some(QueueRun {
+            control: Arc::new(PlaybackControl::default()),
+            cursor: 0,
+            active: false,
+            cycle: 1,
+        });
+        queue
+    }
+
+    fn current(queue: &QueueEngine) -> Option<&str> {
+        queue.current_index().map(|index| queue.entries()[index].as_str())
+    }
+
+    #[test]
+    fn advance_walks_the_entries_once_per_pass() {
+        let mut queue = running(&["a", "b"]);
+        assert_eq!(queue.current_index(), None);
+
+        assert_eq!(queue.advance(), Some(("a".into(), true)));
+        assert_eq!(current(&queue), Some("a"));
+        queue.finish_entry();
+        assert_eq!(queue.current_index(), None);
+
+        assert_eq!(queue.advance(), Some(("b".into(), false)));
+        queue.finish_entry();
+        assert_eq!(queue.advance(), None);
+    }
+
+    #[test]
+    fn enqueue_before_the_cursor_keeps_the_current_entry() {
+        let mut queue = running(&["a", "b", "c"]);
+        queue.advance();
+        queue.advance();
+
+        queue.enqueue("x".into(), Some(0));
+        queue.enqueue("y".into(), Some(99));
+
+        assert_eq!(queue.entries(), ["x", "a", "b", "c", "y"]);
+        assert_eq!(current(&queue), Some("b"));
+        assert_eq!(queue.advance(), Some(("c".into(), false)));
+    }
+
+    #[test]
+    fn dequeue_shifts_the_cursor_and_drops_the_current_entry() {
+        let mut queue = running(&["a", "b", "c", "d"]);
+        queue.advance();
+        queue.advance();
+        queue.advance();
+
+        assert_eq!(queue.dequeue(0), Ok("a".into()));
+        assert_eq!(current(&queue), Some("c"));
+        assert_eq!(queue.dequeue(1), Ok("c".into()));
+        assert_eq!(queue.current_index(), None);
+        assert_eq!(queue.dequeue(2), Err("No queue entry at index 2".into()));
+
+        assert_eq!(queue.advance(), Some(("d".into(), false)));
+    }
+
+    #[test]
+    fn reorder_follows_the_current_entry() {
+        let mut queue = running(&["a", "b", "c"]);
+        queue.advance();
+
+        queue.reorder(0, 2).unwrap();
+        assert_eq!(queue.entries(), ["b", "c", "a"]);
+        assert_eq!(current(&queue), Some("a"));
+        assert_eq!(queue.advance(), None);
+    }
+
+    #[test]
+    fn reorder_around_the_current_entry_keeps_it() {
+        let mut queue = running(&["a", "b", "c"]);
+        queue.advance();
+        queue.advance();
+
+        queue.reorder(2, 0).unwrap();
+        assert_eq!(queue.entries(), ["c", "a", "b"]);
+        assert_eq!(current(&queue), Some("b"));
+        assert_eq!(queue.reorder(0, 3), Err("No queue entry at index 3".into()));
+        assert_eq!(queue.entries(), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn start_cycle_starts_over_only_when_looping() {
+        let mut queue = running(&["a"]);
+        queue.advance();
+        queue.finish_entry();
+        assert!(!queue.start_cycle());
+
+        queue.set_settings(QueueSettings {
+            loop_enabled: true,
+            ..QueueSettings::default()
+        });
+        assert!(queue.start_cycle());
+        assert_eq!(queue.snapshot().cycle, 2);
+        assert_eq!(queue.advance(), Some(("a".into(), true)));
+
+        queue.clear();
+        assert_eq!(queue.current_index(), None);
+        assert!(!queue.start_cycle());
+    }
+}
@@ -0,0 +1,641 @@
+//! Line-oriented text format for macros:
+//!
+//! ```text
+//! # open the save dialog
+//! move 640,480
+//! click left
+//! wait 120ms
+//! press Ctrl+S
+//! type "report.txt"
+//! scroll 0,-3
+//! ```
+//!
+//! Every command happens at the current time; only `wait` advances it.
+
+use std::fmt;
+
+use crate::types::{KeyCode, KeyStroke, MacroEvent, MacroEventKind, Modifiers, MouseButton};
+
+const COMMANDS: &str = "wait, move, click, mousedown, mouseup, press, keydown, keyup, type or scroll";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+pub fn compile_script(source: &str) -> Result<Vec<MacroEvent>, ScriptError> {
+    let mut compiler = Compiler::default();
+    for (index, line) in source.lines().enumerate() {
+        compiler.compile_line(index + 1, line)?;
+    }
+    Ok(compiler.events)
+}
+
+/// Prints `events` as a script, turning offset gaps into `wait` lines and
+/// folding down/up pairs that happen at the same instant back into `click`,
+/// `press` and `type`.
+pub fn decompile_events(events: &[MacroEvent]) -> String {
+    let mut lines = Vec::new();
+    let mut cursor_ms = 0u64;
+    let mut index = 0;
+
+    while index < events.len() {
+        let offset = events[index].offset_ms;
+        if offset > cursor_ms {
+            lines.push(format!("wait {}", format_duration(offset - cursor_ms)));
+            cursor_ms = offset;
+        }
+
+        let rest = &events[index..];
+        if let Some((text, consumed)) = match_typed_text(rest) {
+            lines.push(format!("type {}", quote(&text)));
+            index += consumed;
+            continue;
+        }
+        if let Some((stroke, consumed)) = match_press(rest) {
+            lines.push(format!("press {}", format_key(&stroke)));
+            index += consumed;
+            continue;
+        }
+        if let Some(button) = match_click(rest) {
+            lines.push(format!("click {button}"));
+            index += 2;
+            continue;
+        }
+
+        lines.push(format_event(&rest[0].kind));
+        index += 1;
+    }
+
+    let mut script = lines.join("\n");
+    script.push('\n');
+    script
+}
+
+#[derive(Default)]
+struct Compiler {
+    cursor_ms: u64,
+    events: Vec<MacroEvent>,
+}
+
+impl Compiler {
+    fn compile_line(&mut self, line: usize, text: &str) -> Result<(), ScriptError> {
+        let tokens = tokenize(line, text)?;
+        let Some((command, args)) = tokens.split_first() else {
+            return Ok(());
+        };
+        let error = |column: usize, message: String| ScriptError { line, column, message };
+        if command.quoted {
+            return Err(error(command.column, format!("expected a command ({COMMANDS})")));
+        }
+
+        match command.text.to_lowercase().as_str() {
+            "wait" => {
+                let [duration] = expect_args(line, command, args)?;
+                let delay = parse_duration(&duration.text).map_err(|message| error(duration.column, message))?;
+                self.cursor_ms = self.cursor_ms.saturating_add(delay);
+            }
+            "move" => {
+                let ((x, x_column), (y, y_column)) = parse_pair(line, command, args)?;
+                let x = i32::try_from(x).map_err(|_| error(x_column, format!("x coordinate {x} is out of range")))?;
+                let y = i32::try_from(y).map_err(|_| error(y_column, format!("y coordinate {y} is out of range")))?;
+                self.push(MacroEventKind::MouseMove { x, y });
+            }
+            "scroll" => {
+                let ((delta_x, _), (delta_y, _)) = parse_pair(line, command, args)?;
+                self.push(MacroEventKind::Scroll { delta_x, delta_y });
+            }
+            "click" | "mousedown" | "mouseup" => {
+                let button = match args {
+                    [] => MouseButton::Left,
+                    [token] => MouseButton::parse(&token.text).map_err(|message| error(token.column, message))?,
+                    [_, extra, ..] => return Err(unexpected(line, extra)),
+                };
+                match command.text.to_lowercase().as_str() {
+                    "mousedown" => self.push(MacroEventKind::MouseDown { button }),
+                    "mouseup" => self.push(MacroEventKind::MouseUp { button }),
+                    _ => {
+                        self.push(MacroEventKind::MouseDown { button });
+                        self.push(MacroEventKind::MouseUp { button });
+                    }
+                }
+            }
+            "press" | "keydown" | "keyup" => {
+                let [key] = expect_args(line, command, args)?;
+                let stroke = KeyStroke::parse(&key.text).map_err(|message| error(key.column, message))?;
+                match command.text.to_lowercase().as_str() {
+                    "keydown" => self.push(MacroEventKind::KeyDown { key: stroke }),
+                    "keyup" => self.push(MacroEventKind::KeyUp { key: stroke }),
+                    _ => {
+                        for kind in press_kinds(&stroke) {
+                            self.push(kind);
+                        }
+                    }
+                }
+            }
+            "type" => {
+                let [text] = expect_args(line, command, args)?;
+                if !text.quoted {
+                    return Err(error(text.column, "expected quoted text, e.g. `type \"hello\"`".into()));
+                }
+                for ch in text.text.chars() {
+                    for kind in press_kinds(&typed_stroke(ch)) {
+                        self.push(kind);
+                    }
+                }
+            }
+            _ => {
+                return Err(error(
+                    command.column,
+                    format!("unknown command `{}` (expected {COMMANDS})", command.text),
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, kind: MacroEventKind) {
+        self.events.push(MacroEvent {
+            offset_ms: self.cursor_ms,
+            kind,
+        });
+    }
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    column: usize,
+    quoted: bool,
+}
+
+/// Splits a line into whitespace-separated words and double-quoted strings.
+/// A `#` that starts a word begins a comment.
+fn tokenize(line: usize, text: &str) -> Result<Vec<Token>, ScriptError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let ch = chars[index];
+        if ch.is_whitespace() {
+            index += 1;
+            continue;
+        }
+        if ch == '#' {
+            break;
+        }
+
+        let column = index + 1;
+        if ch != '"' {
+            let start = index;
+            while index < chars.len() && !chars[index].is_whitespace() {
+                index += 1;
+            }
+            tokens.push(Token {
+                text: chars[start..index].iter().collect(),
+                column,
+                quoted: false,
+            });
+            continue;
+        }
+
+        let mut value = String::new();
+        index += 1;
+        loop {
+            let Some(&next) = chars.get(index) else {
+                return Err(ScriptError {
+                    line,
+                    column,
+                    message: "unterminated string".into(),
+                });
+            };
+            index += 1;
+            match next {
+                '"' => break,
+                '\\' => {
+                    let escaped = match chars.get(index) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some(other) => {
+                            return Err(ScriptError {
+                                line,
+                                column: index,
+                                message: format!("unknown escape `\\{other}`"),
+                            })
+                        }
+                        None => {
+                            return Err(ScriptError {
+                                line,
+                                column,
+                                message: "unterminated string".into(),
+                            })
+                        }
+                    };
+                    value.push(escaped);
+                    index += 1;
+                }
+                other => value.push(other),
+            }
+        }
+        tokens.push(Token {
+            text: value,
+            column,
+            quoted: true,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn expect_args<'a, const N: usize>(
+    line: usize,
+    command: &Token,
+    args: &'a [Token],
+) -> Result<[&'a Token; N], ScriptError> {
+    if let Some(extra) = args.get(N) {
+        return Err(unexpected(line, extra));
+    }
+    let collected: Vec<&Token> = args.iter().collect();
+    collected.try_into().map_err(|_| ScriptError {
+        line,
+        column: command.column + command.text.chars().count(),
+        message: format!("`{}` expects {N} argument(s)", command.text),
+    })
+}
+
+fn unexpected(line: usize, token: &Token) -> ScriptError {
+    ScriptError {
+        line,
+        column: token.column,
+        message: format!("unexpected `{}`", token.text),
+    }
+}
+
+/// A parsed value and the column it started at.
+type Located = (i64, usize);
+
+/// Parses `X,Y`, allowing spaces around the comma.
+fn parse_pair(line: usize, command: &Token, args: &[Token]) -> Result<(Located, Located), ScriptError> {
+    let mut parts: Vec<(String, usize)> = vec![(String::new(), 0)];
+    for token in args {
+        if token.quoted {
+            return Err(unexpected(line, token));
+        }
+        for (offset, ch) in token.text.chars().enumerate() {
+            if ch == ',' {
+                parts.push((String::new(), 0));
+                continue;
+            }
+            let (text, column) = parts.last_mut().expect("parts is never empty");
+            if text.is_empty() {
+                *column = token.column + offset;
+            } else if *column + text.chars().count() != token.column + offset {
+                return Err(ScriptError {
+                    line,
+                    column: token.column + offset,
+                    message: "expected `,` between the two values".into(),
+                });
+            }
+            text.push(ch);
+        }
+    }
+
+    let usage = || ScriptError {
+        line,
+        column: args.first().map_or(command.column + command.text.chars().count(), |token| token.column),
+        message: format!("`{}` expects two values, e.g. `{} 0,-3`", command.text, command.text),
+    };
+    let [(x_text, x_column), (y_text, y_column)] = <[(String, usize); 2]>::try_from(parts).map_err(|_| usage())?;
+    if x_text.is_empty() || y_text.is_empty() {
+        return Err(usage());
+    }
+    let number = |text: &str, column: usize| {
+        text.parse::<i64>().map_err(|_| ScriptError {
+            line,
+            column,
+            message: format!("expected an integer, found `{text}`"),
+        })
+    };
+    Ok((
+        (number(&x_text, x_column)?, x_column),
+        (number(&y_text, y_column)?, y_column),
+    ))
+}
+
+fn parse_duration(text: &str) -> Result<u64, String> {
+    let lowered = text.to_lowercase();
+    let (number, scale) = if let Some(number) = lowered.strip_suffix("ms") {
+        (number, 1.0)
+    } else if let Some(number) = lowered.strip_suffix('s') {
+        (number, 1000.0)
+    } else {
+        return Err(format!("expected a duration such as `120ms` or `1.5s`, found `{text}`"));
+    };
+    match number.parse::<f64>() {
+        Ok(value) if value.is_finite() && value >= 0.0 => Ok((value * scale).round() as u64),
+        _ => Err(format!("expected a duration such as `120ms` or `1.5s`, found `{text}`")),
+    }
+}
+
+/// Key-down and key-up for `stroke`, wrapped in presses of its modifiers the
+/// way the recorder captures a chord: each modifier is labelled with the
+/// ones already held (`Ctrl`, `Ctrl+Shift`, ...) and released in reverse.
+fn press_kinds(stroke: &KeyStroke) -> Vec<MacroEventKind> {
+    let held: Vec<KeyCode> = stroke
+        .modifiers
+        .keys()
+        .into_iter()
+        .filter(|key| *key != stroke.code)
+        .collect();
+
+    let mut strokes = Vec::with_capacity(held.len());
+    let mut modifiers = Modifiers::default();
+    for key in &held {
+        strokes.push(KeyStroke {
+            code: *key,
+            modifiers,
+            text: None,
+        });
+        match key {
+            KeyCode::Ctrl => modifiers.ctrl = true,
+            KeyCode::Shift => modifiers.shift = true,
+            KeyCode::Alt => modifiers.alt = true,
+            _ => modifiers.meta = true,
+        }
+    }
+
+    let mut kinds = Vec::with_capacity(strokes.len() * 2 + 2);
+    kinds.extend(strokes.iter().cloned().map(|key| MacroEventKind::KeyDown { key }));
+    kinds.push(MacroEventKind::KeyDown { key: stroke.clone() });
+    kinds.push(MacroEventKind::KeyUp { key: stroke.clone() });
+    kinds.extend(strokes.into_iter().rev().map(|key| MacroEventKind::KeyUp { key }));
+    kinds
+}
+
+/// The key that types `ch`: uppercase letters are their lowercase key with
+/// Shift held, everything else is the key `KeyCode::from_char` picks.
+fn typed_stroke(ch: char) -> KeyStroke {
+    let mut stroke = KeyStroke::new(KeyCode::from_char(ch));
+    stroke.modifiers.shift = ch.is_ascii_uppercase();
+    stroke
+}
+
+fn typed_char(stroke: &KeyStroke) -> Option<char> {
+    if stroke.text.is_some() {
+        return None;
+    }
+    let ch = match (stroke.code, stroke.modifiers.shift) {
+        (KeyCode::Char(ch), true) if ch.is_ascii_lowercase() => ch.to_ascii_uppercase(),
+        (KeyCode::Char(ch), false) => ch,
+        (KeyCode::Space, false) => ' ',
+        (KeyCode::Enter, false) => '\n',
+        (KeyCode::Tab, false) => '\t',
+        _ => return None,
+    };
+    (typed_stroke(ch) == *stroke).then_some(ch)
+}
+
+/// A `press` whose events all share one offset.
+fn match_press(events: &[MacroEvent]) -> Option<(KeyStroke, usize)> {
+    let offset = events.first()?.offset_ms;
+    (0..=4).find_map(|index| {
+        let MacroEventKind::KeyDown { key } = &events.get(index)?.kind else {
+            return None;
+        };
+        let expected = press_kinds(key);
+        let candidate = events.get(..expected.len())?;
+        let matches = candidate
+            .iter()
+            .zip(&expected)
+            .all(|(event, kind)| event.offset_ms == offset && event.kind == *kind);
+        matches.then(|| (key.clone(), expected.len()))
+    })
+}
+
+/// Two or more back-to-back presses that `type` reproduces exactly.
+fn match_typed_text(events: &[MacroEvent]) -> Option<(String, usize)> {
+    let mut text = String::new();
+    let mut consumed = 0;
+    while let Some((stroke, length)) = match_press(&events[consumed..]) {
+        if consumed > 0 && events[consumed].offset_ms != events[0].offset_ms {
+            break;
+        }
+        let Some(ch) = typed_char(&stroke) else {
+            break;
+        };
+        text.push(ch);
+        consumed += length;
+    }
+    (text.chars().count() >= 2).then_some((text, consumed))
+}
+
+fn match_click(events: &[MacroEvent]) -> Option<MouseButton> {
+    match events {
+        [down, up, ..] if down.offset_ms == up.offset_ms => match (&down.kind, &up.kind) {
+            (MacroEventKind::MouseDown { button }, MacroEventKind::MouseUp { button: released }) if button == released => {
+                Some(*button)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn format_event(kind: &MacroEventKind) -> String {
+    match kind {
+        MacroEventKind::MouseMove { x, y } => format!("move {x},{y}"),
+        MacroEventKind::MouseDown { button } => format!("mousedown {button}"),
+        MacroEventKind::MouseUp { button } => format!("mouseup {button}"),
+        MacroEventKind::KeyDown { key } => format!("keydown {}", format_key(key)),
+        MacroEventKind::KeyUp { key } => format!("keyup {}", format_key(key)),
+        MacroEventKind::Scroll { delta_x, delta_y } => format!("scroll {delta_x},{delta_y}"),
+    }
+}
+
+fn format_key(stroke: &KeyStroke) -> String {
+    let label = stroke.label();
+    if label.is_empty() || label.starts_with(['#', '"']) || label.contains(char::is_whitespace) {
+        quote(&label)
+    } else {
+        label
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms >= 1000 && ms.is_multiple_of(1000) {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{ms}ms")
+    }
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            other => quoted.push(other),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[tauri::command]
+pub fn compile_macro_script(source: String) -> Result<Vec<MacroEvent>, String> {
+    compile_script(&source).map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub fn decompile_macro_script(events: Vec<MacroEvent>) -> String {
+    decompile_events(&events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
+    }
+
+    fn key(label: &str) -> KeyStroke {
+        KeyStroke::parse(label).expect(label)
+    }
+
+    fn pressed(offset_ms: u64, stroke: &KeyStroke) -> Vec<MacroEvent> {
+        press_kinds(stroke).into_iter().map(|kind| at(offset_ms, kind)).collect()
+    }
+
+    #[test]
+    fn compiles_the_documented_example() {
+        let script = "# open the save dialog\nmove 640,480\nclick left\nwait 120ms\n\
+                      press Ctrl+S\ntype \"Ab\"\nscroll 0,-3\n";
+        let mut expected = vec![
+            at(0, MacroEventKind::MouseMove { x: 640, y: 480 }),
+            at(0, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            at(0, MacroEventKind::MouseUp { button: MouseButton::Left }),
+        ];
+        expected.extend(pressed(120, &key("Ctrl+S")));
+        expected.extend(pressed(120, &key("Shift+A")));
+        expected.extend(pressed(120, &key("B")));
+        expected.push(at(120, MacroEventKind::Scroll { delta_x: 0, delta_y: -3 }));
+
+        assert_eq!(compile_script(script), Ok(expected));
+    }
+
+    #[test]
+    fn reports_the_line_and_column_of_malformed_scripts() {
+        let cases = [
+            ("wait 2", 1, 6, "expected a duration such as `120ms` or `1.5s`, found `2`"),
+            ("wait -1s", 1, 6, "expected a duration such as `120ms` or `1.5s`, found `-1s`"),
+            ("  move 10;20", 1, 8, "`move` expects two values, e.g. `move 0,-3`"),
+            ("move", 1, 5, "`move` expects two values, e.g. `move 0,-3`"),
+            ("move 10 20", 1, 9, "expected `,` between the two values"),
+            ("move 10, abc", 1, 10, "expected an integer, found `abc`"),
+            ("move 3000000000,0", 1, 6, "x coordinate 3000000000 is out of range"),
+            ("scroll 0, \"3\"", 1, 11, "unexpected `3`"),
+            ("click sideways", 1, 7, "unknown mouse button `sideways` (expected left, right, middle, x1 or x2)"),
+            ("click left right", 1, 12, "unexpected `right`"),
+            ("press", 1, 6, "`press` expects 1 argument(s)"),
+            ("press A B", 1, 9, "unexpected `B`"),
+            ("keydown Hyper+A", 1, 9, "unknown modifier `Hyper` in key label `Hyper+A`"),
+            ("type hello", 1, 6, "expected quoted text, e.g. `type \"hello\"`"),
+            ("type \"abc", 1, 6, "unterminated string"),
+            ("type \"abc\\", 1, 6, "unterminated string"),
+            ("type \"a\\qb\"", 1, 8, "unknown escape `\\q`"),
+            ("type \"é\" extra", 1, 10, "unexpected `extra`"),
+            ("jump 1,2", 1, 1, &format!("unknown command `jump` (expected {COMMANDS})")),
+            ("\"move\" 1,2", 1, 1, &format!("expected a command ({COMMANDS})")),
+            ("# setup\n\nmove 1,2\n\twait 1x", 4, 7, "expected a duration such as `120ms` or `1.5s`, found `1x`"),
+        ];
+        for (script, line, column, message) in cases {
+            let expected = ScriptError {
+                line,
+                column,
+                message: message.to_string(),
+            };
+            assert_eq!(compile_script(script), Err(expected), "{script}");
+        }
+    }
+
+    #[test]
+    fn waits_accumulate_and_round_to_milliseconds() {
+        let events = compile_script("wait 1.5s\nmove 0,0\nwait 0.4ms\nWAIT 2S\nmove 1,1").unwrap();
+        let offsets: Vec<u64> = events.iter().map(|event| event.offset_ms).collect();
+        assert_eq!(offsets, [1500, 3500]);
+    }
+
+    #[test]
+    fn decompiles_to_the_shortest_script() {
+        let mut events = vec![
+            at(0, MacroEventKind::MouseMove { x: -5, y: 1080 }),
+            at(0, MacroEventKind::MouseDown { button: MouseButton::X1 }),
+            at(0, MacroEventKind::MouseUp { button: MouseButton::X1 }),
+            at(100, MacroEventKind::MouseDown { button: MouseButton::Right }),
+            at(1100, MacroEventKind::MouseUp { button: MouseButton::Right }),
+        ];
+        events.extend(pressed(1100, &key("Ctrl+Shift+T")));
+        for ch in "Say \"hi\"\\\n".chars() {
+            events.extend(pressed(1250, &typed_stroke(ch)));
+        }
+        events.extend(pressed(1300, &key("#")));
+        events.push(at(1300, MacroEventKind::Scroll { delta_x: 2, delta_y: -3 }));
+
+        let expected = "move -5,1080\nclick x1\nwait 100ms\nmousedown right\nwait 1s\nmouseup right\n\
+                        press Ctrl+Shift+T\nwait 150ms\ntype \"Say \\\"hi\\\"\\\\\\n\"\n\
+                        wait 50ms\npress \"#\"\nscroll 2,-3\n";
+        assert_eq!(decompile_events(&events), expected);
+    }
+
+    #[test]
+    fn round_trips_every_event_kind() {
+        let mut events = vec![
+            at(0, MacroEventKind::MouseMove { x: -5, y: 1080 }),
+            at(40, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            at(90, MacroEventKind::MouseUp { button: MouseButton::Left }),
+        ];
+        for (index, button) in MouseButton::ALL.into_iter().enumerate() {
+            let offset = 100 + index as u64;
+            events.push(at(offset, MacroEventKind::MouseDown { button }));
+            events.push(at(offset, MacroEventKind::MouseUp { button }));
+        }
+        for (index, label) in ["Shift", "Ctrl+Alt+Delete", "Meta+Space", "F24", "NumPad+", "Ctrl++", "Unknown(57)"]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = 200 + 10 * index as u64;
+            events.push(at(offset, MacroEventKind::KeyDown { key: key(label) }));
+            events.push(at(offset + 5, MacroEventKind::KeyUp { key: key(label) }));
+        }
+        events.extend(pressed(1000, &key("Ctrl+Shift+T")));
+        events.extend(pressed(1000, &key("\"")));
+        for ch in "Tab\there\r\n".chars() {
+            events.extend(pressed(2000, &typed_stroke(ch)));
+        }
+        events.push(at(2000, MacroEventKind::Scroll { delta_x: 0, delta_y: -3 }));
+        events.push(at(60_000, MacroEventKind::Scroll { delta_x: 7, delta_y: 0 }));
+
+        let script = decompile_events(&events);
+        assert_eq!(compile_script(&script), Ok(events), "{script}");
+    }
+}
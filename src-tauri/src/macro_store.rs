@@ -0,0 +1,660 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serde::Serialize;
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::{
+    app_state::AppState,
+    document::{parse_document, serialize_document, MacroDocument, MigrationOutcome, StoredMacro},
+    json_schema::import_macro_file,
+};
+
+pub const MACROS_FILENAME: &str = "macroarc.macros.json";
+
+/// Number of previous library versions kept next to the live file as
+/// `macroarc.macros.json.bak.1` (newest) through `.bak.N` (oldest).
+pub const MACRO_BACKUP_LIMIT: usize = 5;
+
+/// The file a write replaces is kept as a backup on the first write of a
+/// session and then at most this often, so a burst of edits cannot push every
+/// older version out of the rotation.
+const MACRO_BACKUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const LIBRARY_CHANNEL: &str = "macro://library";
+
+const ID_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789_-";
+const ID_LENGTH: usize = 21;
+
+/// The macro library, owned by the backend. Until `open` succeeds the store
+/// lives in memory only and never touches the disk; `open_error` says why.
+#[derive(Default)]
+pub struct MacroStore {
+    path: Option<PathBuf>,
+    document: MacroDocument,
+    open_error: Option<String>,
+    last_backup: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroImportReport {
+    pub imported: Vec<StoredMacro>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroLibraryChange {
+    /// Opaque id of the window that requested the change, echoed back so it
+    /// can ignore its own update.
+    pub source: Option<String>,
+    pub reason: String,
+    pub macro_id: Option<String>,
+    pub macros: Vec<StoredMacro>,
+}
+
+impl MacroStore {
+    /// Loads the library at `path`, falling back to the newest readable backup
+    /// when the live file is corrupt. A missing file is an empty library.
+    /// Documents written by an older schema are upgraded and saved straight
+    /// away so the previous layout ends up in the backup rotation.
+    pub fn open(&mut self, path: PathBuf) -> Result<Vec<String>, String> {
+        let opened = self.load(path);
+        self.open_error = opened.as_ref().err().cloned();
+        opened
+    }
+
+    fn load(&mut self, path: PathBuf) -> Result<Vec<String>, String> {
+        let mut warnings = Vec::new();
+        let outcome = match read_if_exists(&path)? {
+            None => None,
+            Some(raw) => match parse_document(&raw) {
+                Ok(outcome) => Some(outcome),
+                Err(error) => {
+                    let (outcome, backup) = load_newest_backup(&path).ok_or_else(|| {
+                        format!("{} is unreadable and no backup could be loaded: {error}", path.display())
+                    })?;
+                    let quarantined = sibling(&path, "corrupt");
+                    fs::rename(&path, &quarantined)
+                        .map_err(|io_error| format!("failed to move aside {}: {io_error}", path.display()))?;
+                    warnings.push(format!(
+                        "{error}; restored {} and kept the damaged file as {}",
+                        backup.display(),
+                        quarantined.display()
+                    ));
+                    Some(outcome)
+                }
+            },
+        };
+
+        let migrated = outcome.as_ref().is_some_and(|outcome| outcome.migrated());
+        if let Some(outcome) = outcome {
+            warnings.extend(outcome.warnings);
+            self.document = outcome.document;
+        } else {
+            self.document = MacroDocument::default();
+        }
+
+        // The library is only tied to `path` once it is written in the current
+        // layout; otherwise it stays in memory and the old file is untouched.
+        if migrated {
+            let document = self.document.clone();
+            self.write(&path, &document)?;
+        }
+        self.path = Some(path);
+        Ok(warnings)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Why the last `open` failed, leaving changes unsaved.
+    pub fn open_error(&self) -> Option<&str> {
+        self.open_error.as_deref()
+    }
+
+    /// Records why `open` could not even be attempted.
+    pub fn set_open_error(&mut self, error: String) {
+        self.open_error = Some(error);
+    }
+
+    pub fn list(&self) -> &[StoredMacro] {
+        &self.document.macros
+    }
+
+    pub fn get(&self, id: &str) -> Option<&StoredMacro> {
+        self.document.macros.iter().find(|entry| entry.id == id)
+    }
+
+    /// Inserts `entry`, or replaces the macro with the same id in place.
+    pub fn save(&mut self, entry: StoredMacro) -> Result<StoredMacro, String> {
+        if entry.id.trim().is_empty() {
+            return Err("Macro id cannot be empty".into());
+        }
+        let mut document = self.document.clone();
+        match self.position(&entry.id) {
+            Some(index) => document.macros[index] = entry.clone(),
+            None => document.macros.push(entry.clone()),
+        }
+        self.commit(document)?;
+        Ok(entry)
+    }
+
+    pub fn delete(&mut self, id: &str) -> Result<StoredMacro, String> {
+        let index = self.position(id).ok_or_else(|| missing_macro(id))?;
+        let mut document = self.document.clone();
+        let removed = document.macros.remove(index);
+        self.commit(document)?;
+        Ok(removed)
+    }
+
+    /// Copies a macro under fresh ids and places it right after the original.
+    /// The copy does not inherit the hotkey or run history.
+    pub fn duplicate(&mut self, id: &str, name: Option<String>) -> Result<StoredMacro, String> {
+        let index = self.position(id).ok_or_else(|| missing_macro(id))?;
+        let mut copy = self.document.macros[index].clone();
+        copy.id = generate_id();
+        copy.name = match name {
+            Some(name) => normalize_name(&name)?,
+            None => format!("{} copy", copy.name),
+        };
+        copy.hotkey = None;
+        copy.last_run = None;
+        for event in &mut copy.events {
+            event.id = generate_id();
+        }
+
+        let mut document = self.document.clone();
+        document.macros.insert(index + 1, copy.clone());
+        self.commit(document)?;
+        Ok(copy)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<StoredMacro, String> {
+        let name = normalize_name(name)?;
+        let index = self.position(id).ok_or_else(|| missing_macro(id))?;
+        let mut document = self.document.clone();
+        document.macros[index].name = name;
+        let renamed = document.macros[index].clone();
+        self.commit(document)?;
+        Ok(renamed)
+    }
+
+    /// Appends `macros`, giving a fresh id to any that would collide with a
+    /// macro already in the library.
+    pub fn import(&mut self, macros: Vec<StoredMacro>) -> Result<Vec<StoredMacro>, String> {
+        let mut document = self.document.clone();
+        let mut imported = Vec::with_capacity(macros.len());
+        for mut entry in macros {
+            if entry.id.trim().is_empty() || document.macros.iter().any(|existing| existing.id == entry.id) {
+                entry.id = generate_id();
+            }
+            document.macros.push(entry.clone());
+            imported.push(entry);
+        }
+        self.commit(document)?;
+        Ok(imported)
+    }
+
+    /// Replaces the whole library. Returns `false` without writing anything
+    /// when `macros` matches what is already stored.
+    pub fn replace_all(&mut self, macros: Vec<StoredMacro>) -> Result<bool, String> {
+        if let Some(index) = macros.iter().position(|entry| entry.id.trim().is_empty()) {
+            return Err(format!("Macro at index {index} has an empty id"));
+        }
+        if serde_json::to_value(&macros).ok() == serde_json::to_value(&self.document.macros).ok() {
+            return Ok(false);
+        }
+        let document = MacroDocument {
+            macros,
+            ..self.document.clone()
+        };
+        self.commit(document)?;
+        Ok(true)
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.document.macros.iter().position(|entry| entry.id == id)
+    }
+
+    /// Writes `document` and only then makes it the live library, so a failed
+    /// write leaves the store as it was on disk.
+    fn commit(&mut self, document: MacroDocument) -> Result<(), String> {
+        self.persist(&document)?;
+        self.document = document;
+        Ok(())
+    }
+
+    fn persist(&mut self, document: &MacroDocument) -> Result<(), String> {
+        match self.path.clone() {
+            Some(path) => self.write(&path, document),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes `document` over `path`, then keeps the replaced file as a
+    /// backup when one is due. A backup that cannot be taken is logged; the
+    /// library itself was saved.
+    fn write(&mut self, path: &Path, document: &MacroDocument) -> Result<(), String> {
+        let contents = serialize_document(document)?;
+        let backup_due = self.last_backup.is_none_or(|at| at.elapsed() >= MACRO_BACKUP_INTERVAL);
+        let previous = if backup_due { fs::read(path).ok() } else { None };
+        write_atomic(path, contents.as_bytes())
+            .map_err(|error| format!("failed to write {}: {error}", path.display()))?;
+
+        if let Some(previous) = previous {
+            match rotate_backups(path, &previous, MACRO_BACKUP_LIMIT) {
+                Ok(()) => self.last_backup = Some(Instant::now()),
+                Err(error) => eprintln!("failed to back up {}: {error}", path.display()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `contents` to a temporary file next to `path`, flushes it to disk
+/// and renames it over `path`, so readers see either the old file or the new
+/// one but never a partial write.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = sibling(path, "tmp");
+    let result = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+        return result;
+    }
+
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(directory) = File::open(parent) {
+            let _ = directory.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Shifts `.bak.1..N-1` down one slot, dropping the oldest, then writes
+/// `previous` into `.bak.1`.
+pub fn rotate_backups(path: &Path, previous: &[u8], limit: usize) -> io::Result<()> {
+    if limit == 0 {
+        return Ok(());
+    }
+    for slot in (1..limit).rev() {
+        let from = backup_path(path, slot);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, slot + 1))?;
+        }
+    }
+    write_atomic(&backup_path(path, 1), previous)
+}
+
+pub fn backup_path(path: &Path, slot: usize) -> PathBuf {
+    sibling(path, &format!("bak.{slot}"))
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub(crate) fn read_if_exists(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(raw) => Ok(Some(raw)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("failed to read {}: {error}", path.display())),
+    }
+}
+
+fn load_newest_backup(path: &Path) -> Option<(MigrationOutcome, PathBuf)> {
+    (1..=MACRO_BACKUP_LIMIT).find_map(|slot| {
+        let backup = backup_path(path, slot);
+        let raw = fs::read_to_string(&backup).ok()?;
+        parse_document(&raw).ok().map(|outcome| (outcome, backup))
+    })
+}
+
+fn normalize_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Macro name cannot be empty".into());
+    }
+    Ok(trimmed.to_string())
+}
+
+fn missing_macro(id: &str) -> String {
+    format!("No macro with id `{id}`")
+}
+
+pub(crate) fn generate_id() -> String {
+    let mut rng = rand::thread_rng();
+    (0..ID_LENGTH)
+        .map(|_| ID_ALPHABET[rng.gen_range(0..ID_ALPHABET.len())] as char)
+        .collect()
+}
+
+fn emit_library_change(
+    window: &Window,
+    store: &MacroStore,
+    source: Option<String>,
+    reason: &str,
+    macro_id: Option<String>,
+) {
+    let payload = MacroLibraryChange {
+        source,
+        reason: reason.into(),
+        macro_id,
+        macros: store.list().to_vec(),
+    };
+    let _ = window.app_handle().emit(LIBRARY_CHANNEL, payload);
+}
+
+#[tauri::command]
+pub fn list_macros(state: State<'_, AppState>) -> Vec<StoredMacro> {
+    state.macro_store.lock().list().to_vec()
+}
+
+/// Why the library could not be opened at startup, if it could not. Until
+/// then every change is kept in memory only.
+#[tauri::command]
+pub fn macro_library_error(state: State<'_, AppState>) -> Option<String> {
+    state.macro_store.lock().open_error().map(str::to_owned)
+}
+
+#[tauri::command]
+pub fn get_macro(state: State<'_, AppState>, id: String) -> Result<StoredMacro, String> {
+    state.macro_store.lock().get(&id).cloned().ok_or_else(|| missing_macro(&id))
+}
+
+#[tauri::command]
+pub fn save_macro(
+    state: State<'_, AppState>,
+    window: Window,
+    entry: StoredMacro,
+    source: Option<String>,
+) -> Result<StoredMacro, String> {
+    let mut store = state.macro_store.lock();
+    let saved = store.save(entry)?;
+    emit_library_change(&window, &store, source, "saved", Some(saved.id.clone()));
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_macro(
+    state: State<'_, AppState>,
+    window: Window,
+    id: String,
+    source: Option<String>,
+) -> Result<StoredMacro, String> {
+    let mut store = state.macro_store.lock();
+    let removed = store.delete(&id)?;
+    emit_library_change(&window, &store, source, "deleted", Some(id));
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn duplicate_macro(
+    state: State<'_, AppState>,
+    window: Window,
+    id: String,
+    name: Option<String>,
+    source: Option<String>,
+) -> Result<StoredMacro, String> {
+    let mut store = state.macro_store.lock();
+    let copy = store.duplicate(&id, name)?;
+    emit_library_change(&window, &store, source, "duplicated", Some(copy.id.clone()));
+    Ok(copy)
+}
+
+#[tauri::command]
+pub fn rename_macro(
+    state: State<'_, AppState>,
+    window: Window,
+    id: String,
+    name: String,
+    source: Option<String>,
+) -> Result<StoredMacro, String> {
+    let mut store = state.macro_store.lock();
+    let renamed = store.rename(&id, &name)?;
+    emit_library_change(&window, &store, source, "renamed", Some(id));
+    Ok(renamed)
+}
+
+/// Validates `contents` as a macro file (any supported schema version) and
+/// adds its macros to the library.
+#[tauri::command]
+pub fn import_macros(
+    state: State<'_, AppState>,
+    window: Window,
+    contents: String,
+    source: Option<String>,
+) -> Result<MacroImportReport, String> {
+    let outcome = import_macro_file(&contents)?;
+    let mut store = state.macro_store.lock();
+    let imported = store.import(outcome.document.macros)?;
+    emit_library_change(&window, &store, source, "imported", None);
+    Ok(MacroImportReport {
+        imported,
+        warnings: outcome.warnings,
+    })
+}
+
+#[tauri::command]
+pub fn replace_macros(
+    state: State<'_, AppState>,
+    window: Window,
+    macros: Vec<StoredMacro>,
+    source: Option<String>,
+) -> Result<(), String> {
+    let mut store = state.macro_store.lock();
+    if store.replace_all(macros)? {
+        emit_library_change(&window, &store, source, "replaced", None);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+    use crate::{document::StoredMacroEvent, types::MacroEventKind};
+
+    fn stored(id: &str, name: &str) -> StoredMacro {
+        StoredMacro {
+            id: id.into(),
+            name: name.into(),
+            accent: String::new(),
+            tags: Vec::new(),
+            loop_count: 1,
+            loop_enabled: None,
+            loop_delay_ms: None,
+            playback_speed: None,
+            events: Vec::new(),
+            last_run: None,
+            hotkey: None,
+            scroll_delta_mode: None,
+            extra: Map::new(),
+        }
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("macroarc-store-{}", generate_id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn library(macros: &[&str]) -> String {
+        let document = MacroDocument {
+            macros: macros.iter().map(|name| stored(name, name)).collect(),
+            ..MacroDocument::default()
+        };
+        serialize_document(&document).unwrap()
+    }
+
+    fn names_in(path: &Path) -> Vec<String> {
+        let raw = fs::read_to_string(path).unwrap();
+        let outcome = parse_document(&raw).unwrap();
+        outcome.document.macros.into_iter().map(|entry| entry.name).collect()
+    }
+
+    fn names(store: &MacroStore) -> Vec<&str> {
+        store.list().iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn rotates_backups_newest_first_and_drops_the_oldest() {
+        let dir = temp_dir();
+        let path = dir.join(MACROS_FILENAME);
+        for version in ["one", "two", "three", "four"] {
+            rotate_backups(&path, version.as_bytes(), 3).unwrap();
+        }
+        rotate_backups(&path, b"ignored", 0).unwrap();
+
+        let backups: Vec<String> = (1..=4)
+            .map(|slot| fs::read_to_string(backup_path(&path, slot)).unwrap_or_default())
+            .collect();
+        assert_eq!(backups, ["four", "three", "two", ""]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backs_up_the_replaced_file_at_most_once_per_interval() {
+        let dir = temp_dir();
+        let path = dir.join(MACROS_FILENAME);
+        let mut store = MacroStore::default();
+        store.open(path.clone()).unwrap();
+
+        store.save(stored("a", "A")).unwrap();
+        assert!(!backup_path(&path, 1).exists(), "there was no file to back up");
+        store.save(stored("b", "B")).unwrap();
+        store.save(stored("c", "C")).unwrap();
+        assert_eq!(names_in(&backup_path(&path, 1)), ["A"]);
+        assert!(!backup_path(&path, 2).exists());
+
+        store.last_backup = Instant::now().checked_sub(MACRO_BACKUP_INTERVAL);
+        store.delete("a").unwrap();
+        assert_eq!(names_in(&path), ["B", "C"]);
+        assert_eq!(names_in(&backup_path(&path, 1)), ["A", "B", "C"]);
+        assert_eq!(names_in(&backup_path(&path, 2)), ["A"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recovers_from_the_newest_readable_backup() {
+        let dir = temp_dir();
+        let path = dir.join(MACROS_FILENAME);
+        fs::write(&path, "{ not json").unwrap();
+        fs::write(backup_path(&path, 1), "also not json").unwrap();
+        fs::write(backup_path(&path, 2), library(&["Older"])).unwrap();
+        fs::write(backup_path(&path, 3), library(&["Oldest"])).unwrap();
+
+        let mut store = MacroStore::default();
+        let warnings = store.open(path.clone()).unwrap();
+        assert_eq!(names(&store), ["Older"]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(".bak.2"), "{}", warnings[0]);
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(sibling(&path, "corrupt")).unwrap(), "{ not json");
+
+        store.save(stored("n", "New")).unwrap();
+        assert_eq!(names_in(&path), ["Older", "New"]);
+        assert_eq!(names_in(&backup_path(&path, 2)), ["Older"], "the backups are left as they were");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn refuses_to_open_a_corrupt_library_without_a_readable_backup() {
+        let dir = temp_dir();
+        let path = dir.join(MACROS_FILENAME);
+        fs::write(&path, "{ not json").unwrap();
+        fs::write(backup_path(&path, 1), "also not json").unwrap();
+
+        let mut store = MacroStore::default();
+        assert!(store.open(path.clone()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicates_renames_and_deletes_macros() {
+        let mut store = MacroStore::default();
+        let mut original = stored("a", "Macro");
+        original.hotkey = Some("Ctrl+1".into());
+        original.last_run = Some(5);
+        original.events.push(StoredMacroEvent {
+            id: "e1".into(),
+            offset_ms: 10,
+            kind: MacroEventKind::Scroll { delta_x: 0, delta_y: 1 },
+            created_at: None,
+            extra: Map::new(),
+        });
+        store.save(original).unwrap();
+        store.save(stored("b", "Other")).unwrap();
+
+        let copy = store.duplicate("a", None).unwrap();
+        assert_eq!(names(&store), ["Macro", "Macro copy", "Other"]);
+        assert_ne!(copy.id, "a");
+        assert_eq!((copy.hotkey.as_deref(), copy.last_run), (None, None));
+        assert_ne!(copy.events[0].id, "e1");
+        assert_eq!(copy.events[0].offset_ms, 10);
+
+        assert_eq!(store.duplicate("a", Some("  Named ".into())).unwrap().name, "Named");
+        assert_eq!(store.duplicate("a", Some(" ".into())).unwrap_err(), "Macro name cannot be empty");
+        assert_eq!(store.duplicate("zzz", None).unwrap_err(), "No macro with id `zzz`");
+
+        assert_eq!(store.rename("b", " Renamed ").unwrap().name, "Renamed");
+        assert_eq!(store.rename("b", "").unwrap_err(), "Macro name cannot be empty");
+        assert_eq!(store.rename("zzz", "Name").unwrap_err(), "No macro with id `zzz`");
+
+        assert_eq!(store.delete(&copy.id).unwrap().name, "Macro copy");
+        assert_eq!(names(&store), ["Macro", "Named", "Renamed"]);
+        assert!(store.delete(&copy.id).is_err());
+    }
+
+    #[test]
+    fn failed_writes_leave_the_library_unchanged() {
+        let dir = std::env::temp_dir().join(format!("macroarc-store-{}", generate_id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut store = MacroStore::default();
+        store.open(dir.join(MACROS_FILENAME)).unwrap();
+        store.save(stored("a", "First")).unwrap();
+
+        // A directory where the temporary file should go makes every write fail.
+        fs::create_dir(sibling(&dir.join(MACROS_FILENAME), "tmp")).unwrap();
+        assert!(store.save(stored("b", "Second")).is_err());
+        assert!(store.rename("a", "Renamed").is_err());
+        assert!(store.delete("a").is_err());
+
+        assert_eq!(names(&store), ["First"]);
+        assert_eq!(names_in(&dir.join(MACROS_FILENAME)), ["First"]);
+        assert!(!backup_path(&dir.join(MACROS_FILENAME), 1).exists(), "nothing is rotated for a failed write");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn remembers_why_the_library_could_not_be_opened() {
+        let dir = std::env::temp_dir().join(format!("macroarc-store-{}", generate_id()));
+        fs::create_dir_all(dir.join(MACROS_FILENAME)).unwrap();
+        let mut store = MacroStore::default();
+
+        assert!(store.open(dir.join(MACROS_FILENAME)).is_err());
+        assert!(store.open_error().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+use serde::Serialize;
+
+use crate::{
+    input_sink::can_inject_key,
+    types::{KeyCode, KeyStroke, MacroEvent, MacroEventKind, MouseButton},
+};
+
+/// A rectangle of the desktop, in the physical pixels playback moves the
+/// cursor in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ScreenArea {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (i64::from(x), i64::from(y));
+        let (left, top) = (i64::from(self.x), i64::from(self.y));
+        x >= left && y >= top && x < left + i64::from(self.width) && y < top + i64::from(self.height)
+    }
+}
+
+/// Something in a macro that would misbehave when played.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MacroIssue {
+    pub event_index: usize,
+    /// `unmatched-key-down`, `unmatched-button-down`, `unknown-key`,
+    /// `unsupported-key` or `off-screen`.
+    pub kind: &'static str,
+    pub message: String,
+}
+
+/// Checks `events` for key and button presses that are never released, key
+/// labels that do not parse, keys the recorder could not name or this
+/// platform cannot inject, and cursor
+/// moves that land outside every area of `screens`. An empty `screens` skips
+/// the coordinate check.
+pub fn validate_events(events: &[MacroEvent], screens: &[ScreenArea]) -> Vec<MacroIssue> {
+    let mut issues = Vec::new();
+    let mut held_keys: Vec<(KeyCode, usize)> = Vec::new();
+    let mut held_buttons: Vec<(MouseButton, usize)> = Vec::new();
+
+    for (index, event) in events.iter().enumerate() {
+        match &event.kind {
+            MacroEventKind::KeyDown { key } => {
+                check_key(index, key, &mut issues);
+                if key.code != KeyCode::Unrecognized && !held_keys.iter().any(|(code, _)| *code == key.code) {
+                    held_keys.push((key.code, index));
+                }
+            }
+            MacroEventKind::KeyUp { key } => {
+                check_key(index, key, &mut issues);
+                held_keys.retain(|(code, _)| *code != key.code);
+            }
+            MacroEventKind::MouseDown { button } => {
+                if !held_buttons.iter().any(|(held, _)| held == button) {
+                    held_buttons.push((*button, index));
+                }
+            }
+            MacroEventKind::MouseUp { button } => held_buttons.retain(|(held, _)| held != button),
+            MacroEventKind::MouseMove { x, y } => {
+                if !screens.is_empty() && !screens.iter().any(|screen| screen.contains(*x, *y)) {
+                    issues.push(MacroIssue {
+                        event_index: index,
+                        kind: "off-screen",
+                        message: format!("cursor move to ({x}, {y}) is outside every screen"),
+                    });
+                }
+            }
+            MacroEventKind::Scroll { .. } => {}
+        }
+    }
+
+    for (code, index) in held_keys {
+        issues.push(MacroIssue {
+            event_index: index,
+            kind: "unmatched-key-down",
+            message: format!("{} is pressed but never released", code.label()),
+        });
+    }
+    for (button, index) in held_buttons {
+        issues.push(MacroIssue {
+            event_index: index,
+            kind: "unmatched-button-down",
+            message: format!("{button} mouse button is pressed but never released"),
+        });
+    }
+
+    issues.sort_by_key(|issue| issue.event_index);
+    issues
+}
+
+fn check_key(index: usize, key: &KeyStroke, issues: &mut Vec<MacroIssue>) {
+    let code = key.code;
+    if code == KeyCode::Unrecognized {
+        let message = match key.label() {
+            label if label.is_empty() => "key event has no key label".to_string(),
+            label => format!("`{label}` is not a recognised key label"),
+        };
+        issues.push(MacroIssue {
+            event_index: index,
+            kind: "unknown-key",
+            message,
+        });
+    } else if let KeyCode::Raw(raw) = code {
+        issues.push(MacroIssue {
+            event_index: index,
+            kind: "unknown-key",
+            message: format!("key with scan code {raw} has no known label"),
+        });
+    } else if !can_inject_key(code) {
+        issues.push(MacroIssue {
+            event_index: index,
+            kind: "unsupported-key",
+            message: format!("{} cannot be injected on this platform", code.label()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn reports_key_labels_that_do_not_parse() {
+        let events: Vec<MacroEvent> = serde_json::from_value(json!([
+            { "offset_ms": 0, "kind": { "type": "key-down", "key": "" } },
+            { "offset_ms": 5, "kind": { "type": "key-down", "key": "Hyper+Q" } },
+            { "offset_ms": 10, "kind": { "type": "key-up", "key": "Hyper+Q" } },
+            { "offset_ms": 15, "kind": { "type": "key-down", "key": "CmdOrCtrl+S" } },
+            { "offset_ms": 20, "kind": { "type": "key-up", "key": "CmdOrCtrl+S" } },
+        ]))
+        .unwrap();
+
+        let issues = validate_events(&events, &[]);
+
+        let found: Vec<(usize, &str)> = issues.iter().map(|issue| (issue.event_index, issue.kind)).collect();
+        assert_eq!(found, [(0, "unknown-key"), (1, "unknown-key"), (2, "unknown-key")]);
+        assert_eq!(issues[1].message, "`Hyper+Q` is not a recognised key label");
+    }
+}
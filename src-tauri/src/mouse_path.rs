@@ -0,0 +1,186 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    playback_range::PlaybackEvents,
+    types::{MacroEvent, MacroEventKind, MouseMovement, MovementModel},
+};
+
+/// Highest interpolation rate; offsets are whole milliseconds.
+const MAX_RATE_HZ: u32 = 1000;
+
+/// How far bezier control points may stray from the straight line, as a
+/// fraction of the distance moved.
+const BEZIER_SPREAD: f64 = 0.3;
+
+type Point = (f64, f64);
+
+/// Adds cursor moves between consecutive `mouse-move` events following
+/// `movement`. Each stretch starts at the event before the move, so a click
+/// at the old position still lands there, and ends at the move itself.
+/// Moves with no time before them are left as jumps. Randomized paths are
+/// drawn from `seed`, so the same seed gives the same moves. The added moves
+/// have no source.
+pub fn interpolate_moves(events: PlaybackEvents, movement: &MouseMovement, seed: u64) -> PlaybackEvents {
+    let step_ms = u64::from(MAX_RATE_HZ / movement.rate_hz.clamp(1, MAX_RATE_HZ));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut interpolated: Vec<MacroEvent> = Vec::with_capacity(events.len());
+    let mut sources: Vec<Option<usize>> = Vec::with_capacity(events.len());
+    let mut cursor: Option<(i32, i32)> = None;
+
+    for (event, source) in events.events.into_iter().zip(events.sources) {
+        if let MacroEventKind::MouseMove { x, y } = event.kind {
+            if let Some(from) = cursor.filter(|from| *from != (x, y)) {
+                let start_ms = interpolated.last().map_or(0, |previous| previous.offset_ms);
+                let path = MousePath::new(movement.model, from, (x, y), &mut rng);
+                let span = event.offset_ms.saturating_sub(start_ms) as f64;
+                let mut last = from;
+                let mut at = start_ms + step_ms;
+                while at < event.offset_ms {
+                    let point = path.point((at - start_ms) as f64 / span);
+                    if point != last && point != (x, y) {
+                        interpolated.push(MacroEvent {
+                            offset_ms: at,
+                            kind: MacroEventKind::MouseMove { x: point.0, y: point.1 },
+                        });
+                        sources.push(None);
+                        last = point;
+                    }
+                    at += step_ms;
+                }
+            }
+            cursor = Some((x, y));
+        }
+        interpolated.push(event);
+        sources.push(source);
+    }
+
+    PlaybackEvents {
+        events: interpolated,
+        sources,
+        ..events
+    }
+}
+
+enum MousePath {
+    Line { from: Point, to: Point, eased: bool },
+    Bezier { points: [Point; 4] },
+}
+
+impl MousePath {
+    fn new(model: MovementModel, from: (i32, i32), to: (i32, i32), rng: &mut StdRng) -> Self {
+        let from = (f64::from(from.0), f64::from(from.1));
+        let to = (f64::from(to.0), f64::from(to.1));
+        match model {
+            MovementModel::Linear => MousePath::Line { from, to, eased: false },
+            MovementModel::Eased => MousePath::Line { from, to, eased: true },
+            MovementModel::Bezier => {
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                // Perpendicular to the straight line, as long as it.
+                let normal = (-dy, dx);
+                let mut control = |along: f64| {
+                    let along = rng.gen_range(along - 0.1..along + 0.1);
+                    let across = rng.gen_range(-BEZIER_SPREAD..BEZIER_SPREAD);
+                    (
+                        from.0 + dx * along + normal.0 * across,
+                        from.1 + dy * along + normal.1 * across,
+                    )
+                };
+                let first = control(1.0 / 3.0);
+                let second = control(2.0 / 3.0);
+                MousePath::Bezier {
+                    points: [from, first, second, to],
+                }
+            }
+        }
+    }
+
+    /// Position `progress` (0 to 1) of the way through the movement's time.
+    fn point(&self, progress: f64) -> (i32, i32) {
+        let progress = progress.clamp(0.0, 1.0);
+        let (x, y) = match self {
+            MousePath::Line { from, to, eased } => {
+                let t = if *eased { ease_in_out(progress) } else { progress };
+                (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+            }
+            MousePath::Bezier { points: [p0, p1, p2, p3] } => {
+                let t = ease_in_out(progress);
+                let u = 1.0 - t;
+                let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                (
+                    a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                    a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+                )
+            }
+        };
+        (x.round() as i32, y.round() as i32)
+    }
+}
+
+fn ease_in_out(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MouseButton;
+
+    fn event(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
+    }
+
+    fn move_to(offset_ms: u64, x: i32, y: i32) -> MacroEvent {
+        event(offset_ms, MacroEventKind::MouseMove { x, y })
+    }
+
+    fn movement(model: MovementModel, rate_hz: u32) -> MouseMovement {
+        MouseMovement { model, rate_hz }
+    }
+
+    #[test]
+    fn linear_moves_fill_the_gap_at_the_rate() {
+        let events = vec![move_to(0, 0, 0), move_to(50, 100, 0)];
+
+        let path = interpolate_moves(events.into(), &movement(MovementModel::Linear, 100), 1);
+
+        let expected = [
+            move_to(0, 0, 0),
+            move_to(10, 20, 0),
+            move_to(20, 40, 0),
+            move_to(30, 60, 0),
+            move_to(40, 80, 0),
+            move_to(50, 100, 0),
+        ];
+        assert_eq!(path.events, expected);
+        assert_eq!(path.sources, [Some(0), None, None, None, None, Some(1)]);
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_path() {
+        let events = vec![move_to(0, 0, 0), move_to(200, 300, 150)];
+        let bezier = movement(MovementModel::Bezier, 250);
+
+        let first = interpolate_moves(events.clone().into(), &bezier, 42);
+        let again = interpolate_moves(events.clone().into(), &bezier, 42);
+        let other = interpolate_moves(events.into(), &bezier, 43);
+
+        assert!(first.len() > 2);
+        assert_eq!(first, again);
+        assert_ne!(first.events, other.events);
+    }
+
+    #[test]
+    fn moves_without_time_before_them_stay_jumps() {
+        let events = vec![
+            move_to(0, 0, 0),
+            move_to(0, 500, 500),
+            event(80, MacroEventKind::MouseDown { button: MouseButton::Left }),
+            move_to(80, 10, 10),
+        ];
+
+        let path = interpolate_moves(events.clone().into(), &movement(MovementModel::Eased, 1000), 7);
+
+        assert_eq!(path.events, events);
+        assert_eq!(path.sources, [Some(0), Some(1), Some(2), Some(3)]);
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    hint,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Remaining time below which `wait_until` stops sleeping and spins. OS sleeps
+/// routinely overshoot by around a millisecond, so the last stretch is spun.
+pub const SPIN_THRESHOLD: Duration = Duration::from_micros(1500);
+
+/// Longest single sleep, so stop and pause requests are noticed promptly.
+pub const MAX_SLEEP_SLICE: Duration = Duration::from_millis(5);
+
+/// Origin that every playback deadline is measured from. Deadlines are derived
+/// from the origin rather than from the previous event, so a late event does
+/// not push back the ones after it.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaybackClock {
+    origin: Instant,
+}
+
+impl PlaybackClock {
+    pub fn start() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+
+    pub fn deadline(&self, at: Duration) -> Instant {
+        self.origin + at
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    /// Pushes every later deadline back by `by`, e.g. the time spent paused.
+    pub fn shift(&mut self, by: Duration) {
+        self.origin += by;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// The deadline passed; `lateness` is how far past it the wait returned.
+    Reached { lateness: Duration },
+    /// `interrupted` returned `true` before the deadline.
+    Interrupted,
+}
+
+/// Sleeps in slices of at most `MAX_SLEEP_SLICE` until the deadline is within
+/// `SPIN_THRESHOLD`, then spins. `interrupted` is polled before every slice and
+/// on every spin.
+pub fn wait_until<F: FnMut() -> bool>(deadline: Instant, mut interrupted: F) -> WaitOutcome {
+    loop {
+        if interrupted() {
+            return WaitOutcome::Interrupted;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return WaitOutcome::Reached {
+                lateness: now - deadline,
+            };
+        }
+
+        let remaining = deadline - now;
+        if remaining > SPIN_THRESHOLD {
+            thread::sleep((remaining - SPIN_THRESHOLD).min(MAX_SLEEP_SLICE));
+        } else {
+            hint::spin_loop();
+        }
+    }
+}
+
+/// Scales a recorded offset by `playback_speed` without rounding it to whole
+/// milliseconds.
+pub fn scaled_offset(offset_ms: u64, playback_speed: f32) -> Duration {
+    Duration::from_secs_f64(offset_ms as f64 / 1000.0 / f64::from(playback_speed))
+}
+
+/// How late injected events were against their deadlines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatenessStats {
+    pub samples: u64,
+    pub total: Duration,
+    pub max: Duration,
+    pub last: Duration,
+}
+
+impl LatenessStats {
+    pub fn record(&mut self, lateness: Duration) {
+        self.samples += 1;
+        self.total += lateness;
+        self.max = self.max.max(lateness);
+        self.last = lateness;
+    }
+
+    #[cfg(test)]
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.samples) {
+            Ok(0) => Duration::ZERO,
+            Ok(samples) => self.total / samples,
+            Err(_) => Duration::from_secs_f64(self.total.as_secs_f64() / self.samples as f64),
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::types::{KeyCode, KeyStroke, MacroEvent, MacroEventKind, MacroPlaybackRequest, MouseButton};
+
+/// Section of a macro to play. Event bounds are inclusive indices into the
+/// event list; time bounds select events with `start_ms <= offset_ms < end_ms`.
+/// When both kinds are given, only events inside both are played.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaybackRange {
+    pub start_event: Option<usize>,
+    pub end_event: Option<usize>,
+    pub start_ms: Option<u64>,
+    pub end_ms: Option<u64>,
+}
+
+impl PlaybackRange {
+    pub fn from_request(request: &MacroPlaybackRequest) -> Self {
+        Self {
+            start_event: request.start_event,
+            end_event: request.end_event,
+            start_ms: request.start_ms,
+            end_ms: request.end_ms,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == PlaybackRange::default()
+    }
+
+    fn contains(&self, index: usize, offset_ms: u64) -> bool {
+        self.start_event.is_none_or(|start| index >= start)
+            && self.end_event.is_none_or(|end| index <= end)
+            && self.start_ms.is_none_or(|start| offset_ms >= start)
+            && self.end_ms.is_none_or(|end| offset_ms < end)
+    }
+}
+
+/// Events ready to play, each with the index of the event it came from in
+/// the caller's list. Events playback adds itself, such as the lead-in of a
+/// range or interpolated cursor moves, have no source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaybackEvents {
+    pub events: Vec<MacroEvent>,
+    pub sources: Vec<Option<usize>>,
+    /// Subtracted from the recorded offsets when a range was selected.
+    pub base_ms: u64,
+    /// Length of the caller's list.
+    pub source_len: usize,
+}
+
+impl PlaybackEvents {
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// For each event, the caller's index it is timed and reported as: its
+    /// own source, or for an added event the source of the next event that
+    /// has one, since it fills the time before that event.
+    pub fn timing_indices(&self) -> Vec<Option<usize>> {
+        let mut next = None;
+        let mut indices: Vec<Option<usize>> = self
+            .sources
+            .iter()
+            .rev()
+            .map(|source| {
+                next = source.or(next);
+                next
+            })
+            .collect();
+        indices.reverse();
+        indices
+    }
+
+    /// Offset of event `index` as recorded, before any range rebasing.
+    pub fn recorded_offset(&self, index: usize) -> u64 {
+        self.events[index].offset_ms + self.base_ms
+    }
+}
+
+impl From<Vec<MacroEvent>> for PlaybackEvents {
+    fn from(events: Vec<MacroEvent>) -> Self {
+        Self {
+            sources: (0..events.len()).map(Some).collect(),
+            base_ms: 0,
+            source_len: events.len(),
+            events,
+        }
+    }
+}
+
+/// Cut `events` down to `range`. The returned events are rebased so the range
+/// starts at offset 0; with a `start_ms` bound the gap before the first
+/// selected event is kept.
+///
+/// When the range starts partway through, the result opens with events that
+/// recreate the state the recording was in at that point: a move to the last
+/// recorded cursor position, then presses for the modifier keys and mouse
+/// buttons that were held down.
+pub fn select_range(events: &[MacroEvent], range: &PlaybackRange) -> Result<PlaybackEvents, String> {
+    if range.is_full() {
+        return Ok(events.to_vec().into());
+    }
+
+    if let (Some(start), Some(end)) = (range.start_event, range.end_event) {
+        if start > end {
+            return Err(format!("Playback range start event {start} is after end event {end}"));
+        }
+    }
+    if let (Some(start), Some(end)) = (range.start_ms, range.end_ms) {
+        if start >= end {
+            return Err(format!("Playback range start {start} ms is not before end {end} ms"));
+        }
+    }
+
+    let first = events
+        .iter()
+        .enumerate()
+        .position(|(index, event)| range.contains(index, event.offset_ms))
+        .ok_or("Playback range selects no events")?;
+    let selected = events[first..]
+        .iter()
+        .enumerate()
+        .take_while(|(index, event)| range.contains(first + index, event.offset_ms))
+        .count();
+
+    let base_ms = range.start_ms.unwrap_or(events[first].offset_ms);
+    let mut sliced: Vec<MacroEvent> = lead_in_events(&events[..first])
+        .into_iter()
+        .map(|kind| MacroEvent { offset_ms: 0, kind })
+        .collect();
+    let mut sources = vec![None; sliced.len()];
+    for (index, event) in events.iter().enumerate().skip(first).take(selected) {
+        sliced.push(MacroEvent {
+            offset_ms: event.offset_ms.saturating_sub(base_ms),
+            kind: event.kind.clone(),
+        });
+        sources.push(Some(index));
+    }
+    Ok(PlaybackEvents {
+        events: sliced,
+        sources,
+        base_ms,
+        source_len: events.len(),
+    })
+}
+
+/// Events that restore the cursor position and held modifiers and buttons
+/// left behind by `skipped`.
+fn lead_in_events(skipped: &[MacroEvent]) -> Vec<MacroEventKind> {
+    let mut cursor = None;
+    let mut modifiers: Vec<KeyCode> = Vec::new();
+    let mut buttons: Vec<MouseButton> = Vec::new();
+
+    for event in skipped {
+        match &event.kind {
+            MacroEventKind::MouseMove { x, y } => cursor = Some((*x, *y)),
+            MacroEventKind::KeyDown { key } if key.code.is_modifier() => {
+                if !modifiers.contains(&key.code) {
+                    modifiers.push(key.code);
+                }
+            }
+            MacroEventKind::KeyUp { key } => modifiers.retain(|held| *held != key.code),
+            MacroEventKind::MouseDown { button } => {
+                if !buttons.contains(button) {
+                    buttons.push(*button);
+                }
+            }
+            MacroEventKind::MouseUp { button } => buttons.retain(|held| held != button),
+            MacroEventKind::KeyDown { .. } | MacroEventKind::Scroll { .. } => {}
+        }
+    }
+
+    let mut lead_in = Vec::new();
+    if let Some((x, y)) = cursor {
+        lead_in.push(MacroEventKind::MouseMove { x, y });
+    }
+    lead_in.extend(modifiers.into_iter().map(|code| MacroEventKind::KeyDown {
+        key: KeyStroke::new(code),
+    }));
+    lead_in.extend(buttons.into_iter().map(|button| MacroEventKind::MouseDown { button }));
+    lead_in
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(offset_ms: u64, kind: MacroEventKind) -> MacroEvent {
+        MacroEvent { offset_ms, kind }
+    }
+
+    fn key(code: KeyCode) -> KeyStroke {
+        KeyStroke::new(code)
+    }
+
+    /// Four scroll steps 100 ms apart, which need no lead-in.
+    fn scrolls() -> Vec<MacroEvent> {
+        (0..4)
+            .map(|index| event(index * 100, MacroEventKind::Scroll { delta_x: 0, delta_y: -1 }))
+            .collect()
+    }
+
+    fn offsets_and_sources(selected: &PlaybackEvents) -> Vec<(u64, Option<usize>)> {
+        selected
+            .events
+            .iter()
+            .zip(&selected.sources)
+            .map(|(event, source)| (event.offset_ms, *source))
+            .collect()
+    }
+
+    #[test]
+    fn the_full_range_keeps_every_event() {
+        let selected = select_range(&scrolls(), &PlaybackRange::default()).unwrap();
+
+        assert_eq!(selected.events, scrolls());
+        assert_eq!(selected.sources, [Some(0), Some(1), Some(2), Some(3)]);
+        assert_eq!((selected.base_ms, selected.source_len), (0, 4));
+    }
+
+    #[test]
+    fn event_bounds_are_inclusive() {
+        let range = PlaybackRange {
+            start_event: Some(1),
+            end_event: Some(2),
+            ..PlaybackRange::default()
+        };
+        let selected = select_range(&scrolls(), &range).unwrap();
+
+        assert_eq!(offsets_and_sources(&selected), [(0, Some(1)), (100, Some(2))]);
+        assert_eq!(selected.base_ms, 100);
+    }
+
+    #[test]
+    fn time_bounds_are_half_open_and_keep_the_leading_gap() {
+        let range = PlaybackRange {
+            start_ms: Some(50),
+            end_ms: Some(300),
+            ..PlaybackRange::default()
+        };
+        let selected = select_range(&scrolls(), &range).unwrap();
+
+        assert_eq!(offsets_and_sources(&selected), [(50, Some(1)), (150, Some(2))]);
+        assert_eq!(selected.recorded_offset(0), 100);
+    }
+
+    #[test]
+    fn combined_bounds_select_events_inside_both() {
+        let range = PlaybackRange {
+            start_event: Some(1),
+            end_event: Some(3),
+            end_ms: Some(300),
+            ..PlaybackRange::default()
+        };
+        let selected = select_range(&scrolls(), &range).unwrap();
+
+        assert_eq!(offsets_and_sources(&selected), [(0, Some(1)), (100, Some(2))]);
+    }
+
+    #[test]
+    fn rejects_ranges_that_select_nothing() {
+        let backwards = PlaybackRange {
+            start_event: Some(2),
+            end_event: Some(1),
+            ..PlaybackRange::default()
+        };
+        let empty_window = PlaybackRange {
+            start_ms: Some(200),
+            end_ms: Some(200),
+            ..PlaybackRange::default()
+        };
+        let past_the_end = PlaybackRange {
+            start_ms: Some(1_000),
+            ..PlaybackRange::default()
+        };
+
+        assert_eq!(
+            select_range(&scrolls(), &backwards).unwrap_err(),
+            "Playback range start event 2 is after end event 1"
+        );
+        assert_eq!(
+            select_range(&scrolls(), &empty_window).unwrap_err(),
+            "Playback range start 200 ms is not before end 200 ms"
+        );
+        assert_eq!(select_range(&scrolls(), &past_the_end).unwrap_err(), "Playback range selects no events");
+    }
+
+    #[test]
+    fn lead_in_restores_the_cursor_and_held_inputs() {
+        let events = vec![
+            event(0, MacroEventKind::MouseMove { x: 1, y: 1 }),
+            event(10, MacroEventKind::KeyDown { key: key(KeyCode::Ctrl) }),
+            event(20, MacroEventKind::KeyDown { key: key(KeyCode::Char('a')) }),
+            event(30, MacroEventKind::KeyDown { key: key(KeyCode::Shift) }),
+            event(40, MacroEventKind::KeyUp { key: key(KeyCode::Shift) }),
+            event(50, MacroEventKind::MouseDown { button: MouseButton::Right }),
+            event(60, MacroEventKind::MouseMove { x: 7, y: 8 }),
+            event(70, MacroEventKind::MouseDown { button: MouseButton::Left }),
+        ];
+        let selected = select_range(&events, &PlaybackRange {
+            start_event: Some(7),
+            ..PlaybackRange::default()
+        })
+        .unwrap();
+
+        let kinds: Vec<&MacroEventKind> = selected.events.iter().map(|event| &event.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &MacroEventKind::MouseMove { x: 7, y: 8 },
+                &MacroEventKind::KeyDown { key: key(KeyCode::Ctrl) },
+                &MacroEventKind::MouseDown { button: MouseButton::Right },
+                &MacroEventKind::MouseDown { button: MouseButton::Left },
+            ]
+        );
+        assert_eq!(selected.sources, [None, None, None, Some(7)]);
+        assert_eq!(selected.timing_indices(), [Some(7), Some(7), Some(7), Some(7)]);
+    }
+}
@@ -4,7 +4,6 @@ use std::{
         atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
-    thread,
     time::{Duration, Instant},
 };
 
@@ -12,10 +11,17 @@ use parking_lot::Mutex;
 use rdev::{Button as RdevButton, Event as RdevEvent, EventType, Key as RdevKey};
 use tauri::{Emitter, Manager, State, Window};
 
-use crate::{app_state::AppState, types::{MacroEvent, MacroEventKind}};
+#[cfg(test)]
+use crate::input_source::InputSource;
+use crate::{
+    app_state::AppState,
+    types::{KeyCode, KeyStroke, MacroEvent, MacroEventKind, Modifiers, MouseButton},
+};
 
 #[cfg(target_os = "windows")]
 use device_query::{DeviceQuery, DeviceState, Keycode};
+#[cfg(target_os = "windows")]
+use std::thread;
 
 #[derive(Default)]
 pub struct RecorderState {
@@ -32,6 +38,46 @@ pub struct RecorderState {
     pub(crate) keyboard_thread_started: bool,
 }
 
+/// Shared handles the capture callbacks need to turn raw input into recorded
+/// macro events.
+#[derive(Clone)]
+pub(crate) struct CaptureContext {
+    events: Arc<Mutex<Vec<MacroEvent>>>,
+    capture_flag: Arc<AtomicBool>,
+    start_time: Arc<Mutex<Option<Instant>>>,
+    window: Arc<Mutex<Option<Window>>>,
+    modifier_state: Arc<Mutex<ModifierState>>,
+    key_events: Arc<AtomicU64>,
+    pointer_events: Arc<AtomicU64>,
+}
+
+impl CaptureContext {
+    fn handle_event(&self, event: &RdevEvent) {
+        if !self.capture_flag.load(Ordering::Relaxed) {
+            self.modifier_state.lock().reset();
+            return;
+        }
+
+        if let Some(kind) = translate_event(event, &self.modifier_state) {
+            dispatch_macro_event(kind, self);
+        }
+    }
+}
+
+impl RecorderState {
+    fn capture_context(&self) -> CaptureContext {
+        CaptureContext {
+            events: self.events.clone(),
+            capture_flag: self.capture_flag.clone(),
+            start_time: self.start_time.clone(),
+            window: self.window.clone(),
+            modifier_state: self.modifier_state.clone(),
+            key_events: self.key_events.clone(),
+            pointer_events: self.pointer_events.clone(),
+        }
+    }
+}
+
 #[tauri::command]
 pub fn start_recording(state: State<'_, AppState>, window: Window) -> Result<(), String> {
     let mut recorder = state.recorder.lock();
@@ -52,73 +98,51 @@ pub fn start_recording(state: State<'_, AppState>, window: Window) -> Result<(),
     ensure_keyboard_poller(&mut recorder);
 
     if !recorder.listener_running {
-        let events_arc = recorder.events.clone();
-        let capture_flag = recorder.capture_flag.clone();
-        let start_time = recorder.start_time.clone();
-        let window_handle = recorder.window.clone();
-        let modifier_state = recorder.modifier_state.clone();
-        let key_counter = recorder.key_events.clone();
-        let pointer_counter = recorder.pointer_events.clone();
-
-        thread::spawn(move || {
-            let callback_window = window_handle.clone();
-            let result = rdev::listen(move |event: RdevEvent| {
-                if !capture_flag.load(Ordering::Relaxed) {
-                    modifier_state.lock().reset();
-                    return;
-                }
-
-                #[cfg(target_os = "windows")]
-                if matches!(event.event_type, EventType::KeyPress(_) | EventType::KeyRelease(_)) {
-                    return;
-                }
-
-                if let Some(kind) = translate_event(&event, &modifier_state) {
-                    dispatch_macro_event(
-                        kind,
-                        &start_time,
-                        &events_arc,
-                        &callback_window,
-                        &key_counter,
-                        &pointer_counter,
-                    );
-                }
-            });
-
-            if let Err(error) = result {
-                if let Some(active_window) = window_handle.lock().clone() {
-                    let _ = active_window.emit("macro://error", format!("Recorder error: {error:?}"));
-                }
+        let context = recorder.capture_context();
+        state.input_hook.subscribe(move |event: &RdevEvent| {
+            #[cfg(target_os = "windows")]
+            if matches!(event.event_type, EventType::KeyPress(_) | EventType::KeyRelease(_)) {
+                return;
             }
-        });
 
+            context.handle_event(event);
+        });
         recorder.listener_running = true;
     }
 
+    // Every time, so a hook that failed earlier gets another try.
+    let error_window = recorder.window.clone();
+    state.input_hook.start(move |error| {
+        if let Some(active_window) = error_window.lock().clone() {
+            let _ = active_window.emit("macro://error", format!("Recorder error: {error}"));
+        }
+    });
+
     recorder.active = true;
     let app_handle = window.app_handle();
     let _ = app_handle.emit("macro://status", "recording-started");
     Ok(())
 }
 
-#[tauri::command]
-pub fn stop_recording(state: State<'_, AppState>, window: Window) -> Result<Vec<MacroEvent>, String> {
-    let events = {
-        let mut recorder = state.recorder.lock();
+/// Runs `source` to completion through the same translation pipeline as a
+/// live recording and returns the captured events. Used to replay scripted
+/// input without an OS hook.
+#[cfg(test)]
+pub fn capture_from_source<S: InputSource>(source: S) -> Result<Vec<MacroEvent>, String> {
+    let recorder = RecorderState::default();
+    recorder.capture_flag.store(true, Ordering::Relaxed);
+    *recorder.start_time.lock() = Some(Instant::now());
 
-        if !recorder.active {
-            return Err("No active recording".into());
-        }
+    let context = recorder.capture_context();
+    source.listen(move |event: RdevEvent| context.handle_event(&event))?;
 
-        recorder.capture_flag.store(false, Ordering::Relaxed);
-        recorder.active = false;
-        recorder.window.lock().take();
-        *recorder.start_time.lock() = None;
-        recorder.modifier_state.lock().reset();
+    let events = recorder.events.lock().clone();
+    Ok(events)
+}
 
-        let snapshot = recorder.events.lock().clone();
-        snapshot
-    };
+#[tauri::command]
+pub fn stop_recording(state: State<'_, AppState>, window: Window) -> Result<Vec<MacroEvent>, String> {
+    let events = finish_recording(&mut state.recorder.lock()).ok_or("No active recording")?;
 
     let app_handle = window.app_handle();
     let _ = app_handle.emit("macro://status", "recording-stopped");
@@ -126,16 +150,33 @@ pub fn stop_recording(state: State<'_, AppState>, window: Window) -> Result<Vec<
     Ok(events)
 }
 
+/// Ends the active recording and returns what it captured, or `None` when
+/// nothing was being recorded.
+pub(crate) fn finish_recording(recorder: &mut RecorderState) -> Option<Vec<MacroEvent>> {
+    if !recorder.active {
+        return None;
+    }
+
+    recorder.capture_flag.store(false, Ordering::Relaxed);
+    recorder.active = false;
+    recorder.window.lock().take();
+    *recorder.start_time.lock() = None;
+    recorder.modifier_state.lock().reset();
+
+    let snapshot = recorder.events.lock().clone();
+    Some(snapshot)
+}
+
 fn translate_event(event: &RdevEvent, modifiers: &Arc<Mutex<ModifierState>>) -> Option<MacroEventKind> {
     match event.event_type {
         EventType::KeyPress(key) => Some(compose_key_event(key, true, modifiers, event.name.as_deref())),
         EventType::KeyRelease(key) => Some(compose_key_event(key, false, modifiers, event.name.as_deref())),
-        EventType::ButtonPress(button) => Some(MacroEventKind::MouseDown {
-            button: button_to_string(button).to_string(),
-        }),
-        EventType::ButtonRelease(button) => Some(MacroEventKind::MouseUp {
-            button: button_to_string(button).to_string(),
-        }),
+        EventType::ButtonPress(button) => {
+            mouse_button_from_rdev(button).map(|button| MacroEventKind::MouseDown { button })
+        }
+        EventType::ButtonRelease(button) => {
+            mouse_button_from_rdev(button).map(|button| MacroEventKind::MouseUp { button })
+        }
         EventType::MouseMove { x, y } => Some(MacroEventKind::MouseMove {
             x: x as i32,
             y: y as i32,
@@ -147,24 +188,33 @@ fn translate_event(event: &RdevEvent, modifiers: &Arc<Mutex<ModifierState>>) ->
     }
 }
 
-fn button_to_string(button: RdevButton) -> &'static str {
+fn mouse_button_from_rdev(button: RdevButton) -> Option<MouseButton> {
     match button {
-        RdevButton::Left => "left",
-        RdevButton::Right => "right",
-        RdevButton::Middle => "middle",
-        RdevButton::Unknown(_) => "unknown",
+        RdevButton::Left => Some(MouseButton::Left),
+        RdevButton::Right => Some(MouseButton::Right),
+        RdevButton::Middle => Some(MouseButton::Middle),
+        RdevButton::Unknown(code) => extra_button_from_code(code),
+    }
+}
+
+/// Side buttons arrive as raw codes: XBUTTON1/2 on Windows, X11 buttons 8/9 on
+/// Linux. Anything else (e.g. X11 horizontal wheel buttons) is not recorded.
+fn extra_button_from_code(code: u8) -> Option<MouseButton> {
+    match code {
+        #[cfg(target_os = "windows")]
+        1 => Some(MouseButton::X1),
+        #[cfg(target_os = "windows")]
+        2 => Some(MouseButton::X2),
+        #[cfg(target_os = "linux")]
+        8 => Some(MouseButton::X1),
+        #[cfg(target_os = "linux")]
+        9 => Some(MouseButton::X2),
+        _ => None,
     }
 }
 
-fn dispatch_macro_event(
-    kind: MacroEventKind,
-    start_time: &Arc<Mutex<Option<Instant>>>,
-    events_arc: &Arc<Mutex<Vec<MacroEvent>>>,
-    window_handle: &Arc<Mutex<Option<Window>>>,
-    key_counter: &Arc<AtomicU64>,
-    pointer_counter: &Arc<AtomicU64>,
-) {
-    let start_opt = start_time.lock().clone();
+fn dispatch_macro_event(kind: MacroEventKind, context: &CaptureContext) {
+    let start_opt = context.start_time.lock().clone();
     if let Some(start) = start_opt {
         let offset_ms = start.elapsed().as_millis() as u64;
         let entry = MacroEvent {
@@ -174,17 +224,17 @@ fn dispatch_macro_event(
         let is_key = matches!(entry.kind, MacroEventKind::KeyDown { .. } | MacroEventKind::KeyUp { .. });
 
         {
-            let mut events = events_arc.lock();
+            let mut events = context.events.lock();
             events.push(entry.clone());
         }
-        if let Some(active_window) = window_handle.lock().clone() {
+        if let Some(active_window) = context.window.lock().clone() {
             let _ = active_window.emit("macro://event", &entry);
         }
 
         if is_key {
-            key_counter.fetch_add(1, Ordering::Relaxed);
+            context.key_events.fetch_add(1, Ordering::Relaxed);
         } else {
-            pointer_counter.fetch_add(1, Ordering::Relaxed);
+            context.pointer_events.fetch_add(1, Ordering::Relaxed);
         }
     }
 }
@@ -195,34 +245,36 @@ fn compose_key_event(
     modifiers: &Arc<Mutex<ModifierState>>,
     name_hint: Option<&str>,
 ) -> MacroEventKind {
-    let label = {
+    let stroke = {
         let mut state = modifiers.lock();
-        let key_name = key_label_from_hint(key, name_hint);
+        let text = produced_text(name_hint);
         if pressed {
             state.update(key, true);
-            state.describe_combo(key, &key_name)
+            state.describe_combo(key, text)
         } else {
-            let combo = state.describe_combo(key, &key_name);
+            let combo = state.describe_combo(key, text);
             state.update(key, false);
             combo
         }
     };
 
     if pressed {
-        MacroEventKind::KeyDown { key: label }
+        MacroEventKind::KeyDown { key: stroke }
     } else {
-        MacroEventKind::KeyUp { key: label }
+        MacroEventKind::KeyUp { key: stroke }
     }
 }
 
-fn key_label_from_hint(key: RdevKey, name_hint: Option<&str>) -> String {
-    if let Some(name) = name_hint {
-        if !name.trim().is_empty() {
-            return name.to_string();
-        }
+/// Keeps the OS-reported name only when it is a single printable character;
+/// control characters (`\r`, `\u{1}` for Ctrl+A, ...) say nothing the key
+/// code does not already.
+fn produced_text(name_hint: Option<&str>) -> Option<String> {
+    let name = name_hint?;
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) if !ch.is_control() && !ch.is_whitespace() => Some(name.to_string()),
+        _ => None,
     }
-
-    friendly_key_name(key)
 }
 
 #[cfg(target_os = "windows")]
@@ -231,20 +283,14 @@ fn ensure_keyboard_poller(recorder: &mut RecorderState) {
         return;
     }
 
-    let events_arc = recorder.events.clone();
-    let capture_flag = recorder.capture_flag.clone();
-    let start_time = recorder.start_time.clone();
-    let window_handle = recorder.window.clone();
-    let modifier_state = recorder.modifier_state.clone();
-    let key_counter = recorder.key_events.clone();
-    let pointer_counter = recorder.pointer_events.clone();
+    let context = recorder.capture_context();
 
     thread::spawn(move || {
         let device_state = DeviceState::new();
         let mut last_keys: HashSet<Keycode> = HashSet::new();
 
         loop {
-            if !capture_flag.load(Ordering::Relaxed) {
+            if !context.capture_flag.load(Ordering::Relaxed) {
                 last_keys.clear();
                 thread::sleep(Duration::from_millis(8));
                 continue;
@@ -256,29 +302,11 @@ fn ensure_keyboard_poller(recorder: &mut RecorderState) {
             let released: Vec<Keycode> = last_keys.difference(&current).cloned().collect();
 
             for keycode in pressed {
-                emit_poller_event(
-                    keycode,
-                    true,
-                    &modifier_state,
-                    &start_time,
-                    &events_arc,
-                    &window_handle,
-                    &key_counter,
-                    &pointer_counter,
-                );
+                emit_poller_event(keycode, true, &context);
             }
 
             for keycode in released {
-                emit_poller_event(
-                    keycode,
-                    false,
-                    &modifier_state,
-                    &start_time,
-                    &events_arc,
-                    &window_handle,
-                    &key_counter,
-                    &pointer_counter,
-                );
+                emit_poller_event(keycode, false, &context);
             }
 
             last_keys = current;
@@ -290,35 +318,13 @@ fn ensure_keyboard_poller(recorder: &mut RecorderState) {
 }
 
 #[cfg(target_os = "windows")]
-fn emit_poller_event(
-    keycode: Keycode,
-    pressed: bool,
-    modifier_state: &Arc<Mutex<ModifierState>>,
-    start_time: &Arc<Mutex<Option<Instant>>>,
-    events_arc: &Arc<Mutex<Vec<MacroEvent>>>,
-    window_handle: &Arc<Mutex<Option<Window>>>,
-    key_counter: &Arc<AtomicU64>,
-    pointer_counter: &Arc<AtomicU64>,
-) {
-    let kind = if let Some(mapped) = keycode_to_rdev(keycode) {
-        compose_key_event(mapped, pressed, modifier_state, None)
-    } else {
-        let label = keycode.to_string();
-        if pressed {
-            MacroEventKind::KeyDown { key: label }
-        } else {
-            MacroEventKind::KeyUp { key: label }
-        }
+fn emit_poller_event(keycode: Keycode, pressed: bool, context: &CaptureContext) {
+    let Some(mapped) = keycode_to_rdev(keycode) else {
+        return;
     };
+    let kind = compose_key_event(mapped, pressed, &context.modifier_state, None);
 
-    dispatch_macro_event(
-        kind,
-        start_time,
-        events_arc,
-        window_handle,
-        key_counter,
-        pointer_counter,
-    );
+    dispatch_macro_event(kind, context);
 }
 
 #[cfg(target_os = "windows")]
@@ -446,24 +452,21 @@ impl ModifierState {
         }
     }
 
-    fn describe_combo(&self, key: RdevKey, key_label: &str) -> String {
-        let mut parts: Vec<&str> = Vec::new();
+    fn describe_combo(&self, key: RdevKey, text: Option<String>) -> KeyStroke {
+        let code = key_code_from_rdev(key);
+        let modifiers = Modifiers {
+            ctrl: self.ctrl && code != KeyCode::Ctrl,
+            shift: self.shift && code != KeyCode::Shift,
+            alt: self.alt && code != KeyCode::Alt,
+            meta: self.meta && code != KeyCode::Meta,
+        };
+        let text = text.filter(|value| *value != code.label());
 
-        if self.ctrl && !matches!(key, RdevKey::ControlLeft | RdevKey::ControlRight) {
-            parts.push("Ctrl");
+        KeyStroke {
+            code,
+            modifiers,
+            text,
         }
-        if self.shift && !matches!(key, RdevKey::ShiftLeft | RdevKey::ShiftRight) {
-            parts.push("Shift");
-        }
-        if self.alt && !matches!(key, RdevKey::Alt | RdevKey::AltGr) {
-            parts.push("Alt");
-        }
-        if self.meta && !matches!(key, RdevKey::MetaLeft | RdevKey::MetaRight) {
-            parts.push("Meta");
-        }
-
-        parts.push(key_label);
-        parts.join("+")
     }
 
     fn reset(&mut self) {
@@ -474,64 +477,204 @@ impl ModifierState {
     }
 }
 
-fn friendly_key_name(key: RdevKey) -> String {
+pub(crate) fn key_code_from_rdev(key: RdevKey) -> KeyCode {
     match key {
-        RdevKey::Backspace => "Backspace".into(),
-        RdevKey::Tab => "Tab".into(),
-        RdevKey::Return | RdevKey::KpReturn => "Enter".into(),
-        RdevKey::Escape => "Esc".into(),
-        RdevKey::Space => "Space".into(),
-        RdevKey::ControlLeft | RdevKey::ControlRight => "Ctrl".into(),
-        RdevKey::ShiftLeft | RdevKey::ShiftRight => "Shift".into(),
-        RdevKey::Alt | RdevKey::AltGr => "Alt".into(),
-        RdevKey::MetaLeft | RdevKey::MetaRight => "Meta".into(),
-        RdevKey::CapsLock => "CapsLock".into(),
-        RdevKey::Home => "Home".into(),
-        RdevKey::End => "End".into(),
-        RdevKey::PageUp => "PageUp".into(),
-        RdevKey::PageDown => "PageDown".into(),
-        RdevKey::Insert => "Insert".into(),
-        RdevKey::Delete | RdevKey::KpDelete => "Delete".into(),
-        RdevKey::LeftArrow => "Left".into(),
-        RdevKey::RightArrow => "Right".into(),
-        RdevKey::UpArrow => "Up".into(),
-        RdevKey::DownArrow => "Down".into(),
-        RdevKey::Num0 => "0".into(),
-        RdevKey::Num1 => "1".into(),
-        RdevKey::Num2 => "2".into(),
-        RdevKey::Num3 => "3".into(),
-        RdevKey::Num4 => "4".into(),
-        RdevKey::Num5 => "5".into(),
-        RdevKey::Num6 => "6".into(),
-        RdevKey::Num7 => "7".into(),
-        RdevKey::Num8 => "8".into(),
-        RdevKey::Num9 => "9".into(),
-        RdevKey::Kp0 => "NumPad0".into(),
-        RdevKey::Kp1 => "NumPad1".into(),
-        RdevKey::Kp2 => "NumPad2".into(),
-        RdevKey::Kp3 => "NumPad3".into(),
-        RdevKey::Kp4 => "NumPad4".into(),
-        RdevKey::Kp5 => "NumPad5".into(),
-        RdevKey::Kp6 => "NumPad6".into(),
-        RdevKey::Kp7 => "NumPad7".into(),
-        RdevKey::Kp8 => "NumPad8".into(),
-        RdevKey::Kp9 => "NumPad9".into(),
-        RdevKey::KpPlus => "NumPad+".into(),
-        RdevKey::KpMinus => "NumPad-".into(),
-        RdevKey::KpMultiply => "NumPad*".into(),
-        RdevKey::KpDivide => "NumPad/".into(),
-        RdevKey::Function => "Fn".into(),
-        _ => {
-            let raw = format!("{:?}", key);
-            if let Some(stripped) = raw.strip_prefix("Key") {
-                stripped.to_uppercase()
-            } else if let Some(stripped) = raw.strip_prefix("Num") {
-                stripped.to_string()
-            } else if let Some(stripped) = raw.strip_prefix("Kp") {
-                format!("NumPad{}", stripped)
-            } else {
-                raw
-            }
+        RdevKey::Backspace => KeyCode::Backspace,
+        RdevKey::Tab => KeyCode::Tab,
+        RdevKey::Return | RdevKey::KpReturn => KeyCode::Enter,
+        RdevKey::Escape => KeyCode::Escape,
+        RdevKey::Space => KeyCode::Space,
+        RdevKey::ControlLeft | RdevKey::ControlRight => KeyCode::Ctrl,
+        RdevKey::ShiftLeft | RdevKey::ShiftRight => KeyCode::Shift,
+        RdevKey::Alt | RdevKey::AltGr => KeyCode::Alt,
+        RdevKey::MetaLeft | RdevKey::MetaRight => KeyCode::Meta,
+        RdevKey::CapsLock => KeyCode::CapsLock,
+        RdevKey::Home => KeyCode::Home,
+        RdevKey::End => KeyCode::End,
+        RdevKey::PageUp => KeyCode::PageUp,
+        RdevKey::PageDown => KeyCode::PageDown,
+        RdevKey::Insert => KeyCode::Insert,
+        RdevKey::Delete | RdevKey::KpDelete => KeyCode::Delete,
+        RdevKey::LeftArrow => KeyCode::Left,
+        RdevKey::RightArrow => KeyCode::Right,
+        RdevKey::UpArrow => KeyCode::Up,
+        RdevKey::DownArrow => KeyCode::Down,
+        RdevKey::PrintScreen => KeyCode::PrintScreen,
+        RdevKey::ScrollLock => KeyCode::ScrollLock,
+        RdevKey::Pause => KeyCode::Pause,
+        RdevKey::NumLock => KeyCode::NumLock,
+        RdevKey::Function => KeyCode::Fn,
+        RdevKey::F1 => KeyCode::F(1),
+        RdevKey::F2 => KeyCode::F(2),
+        RdevKey::F3 => KeyCode::F(3),
+        RdevKey::F4 => KeyCode::F(4),
+        RdevKey::F5 => KeyCode::F(5),
+        RdevKey::F6 => KeyCode::F(6),
+        RdevKey::F7 => KeyCode::F(7),
+        RdevKey::F8 => KeyCode::F(8),
+        RdevKey::F9 => KeyCode::F(9),
+        RdevKey::F10 => KeyCode::F(10),
+        RdevKey::F11 => KeyCode::F(11),
+        RdevKey::F12 => KeyCode::F(12),
+        RdevKey::Num0 => KeyCode::Char('0'),
+        RdevKey::Num1 => KeyCode::Char('1'),
+        RdevKey::Num2 => KeyCode::Char('2'),
+        RdevKey::Num3 => KeyCode::Char('3'),
+        RdevKey::Num4 => KeyCode::Char('4'),
+        RdevKey::Num5 => KeyCode::Char('5'),
+        RdevKey::Num6 => KeyCode::Char('6'),
+        RdevKey::Num7 => KeyCode::Char('7'),
+        RdevKey::Num8 => KeyCode::Char('8'),
+        RdevKey::Num9 => KeyCode::Char('9'),
+        RdevKey::Kp0 => KeyCode::Numpad('0'),
+        RdevKey::Kp1 => KeyCode::Numpad('1'),
+        RdevKey::Kp2 => KeyCode::Numpad('2'),
+        RdevKey::Kp3 => KeyCode::Numpad('3'),
+        RdevKey::Kp4 => KeyCode::Numpad('4'),
+        RdevKey::Kp5 => KeyCode::Numpad('5'),
+        RdevKey::Kp6 => KeyCode::Numpad('6'),
+        RdevKey::Kp7 => KeyCode::Numpad('7'),
+        RdevKey::Kp8 => KeyCode::Numpad('8'),
+        RdevKey::Kp9 => KeyCode::Numpad('9'),
+        RdevKey::KpPlus => KeyCode::Numpad('+'),
+        RdevKey::KpMinus => KeyCode::Numpad('-'),
+        RdevKey::KpMultiply => KeyCode::Numpad('*'),
+        RdevKey::KpDivide => KeyCode::Numpad('/'),
+        RdevKey::BackQuote => KeyCode::Char('`'),
+        RdevKey::Minus => KeyCode::Char('-'),
+        RdevKey::Equal => KeyCode::Char('='),
+        RdevKey::LeftBracket => KeyCode::Char('['),
+        RdevKey::RightBracket => KeyCode::Char(']'),
+        RdevKey::SemiColon => KeyCode::Char(';'),
+        RdevKey::Quote => KeyCode::Char('\''),
+        RdevKey::BackSlash | RdevKey::IntlBackslash => KeyCode::Char('\\'),
+        RdevKey::Comma => KeyCode::Char(','),
+        RdevKey::Dot => KeyCode::Char('.'),
+        RdevKey::Slash => KeyCode::Char('/'),
+        RdevKey::KeyA => KeyCode::Char('a'),
+        RdevKey::KeyB => KeyCode::Char('b'),
+        RdevKey::KeyC => KeyCode::Char('c'),
+        RdevKey::KeyD => KeyCode::Char('d'),
+        RdevKey::KeyE => KeyCode::Char('e'),
+        RdevKey::KeyF => KeyCode::Char('f'),
+        RdevKey::KeyG => KeyCode::Char('g'),
+        RdevKey::KeyH => KeyCode::Char('h'),
+        RdevKey::KeyI => KeyCode::Char('i'),
+        RdevKey::KeyJ => KeyCode::Char('j'),
+        RdevKey::KeyK => KeyCode::Char('k'),
+        RdevKey::KeyL => KeyCode::Char('l'),
+        RdevKey::KeyM => KeyCode::Char('m'),
+        RdevKey::KeyN => KeyCode::Char('n'),
+        RdevKey::KeyO => KeyCode::Char('o'),
+        RdevKey::KeyP => KeyCode::Char('p'),
+        RdevKey::KeyQ => KeyCode::Char('q'),
+        RdevKey::KeyR => KeyCode::Char('r'),
+        RdevKey::KeyS => KeyCode::Char('s'),
+        RdevKey::KeyT => KeyCode::Char('t'),
+        RdevKey::KeyU => KeyCode::Char('u'),
+        RdevKey::KeyV => KeyCode::Char('v'),
+        RdevKey::KeyW => KeyCode::Char('w'),
+        RdevKey::KeyX => KeyCode::Char('x'),
+        RdevKey::KeyY => KeyCode::Char('y'),
+        RdevKey::KeyZ => KeyCode::Char('z'),
+        RdevKey::Unknown(code) => KeyCode::Raw(code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_source::{synthetic_event, ScriptedSource};
+
+    fn stroke(code: KeyCode, modifiers: Modifiers, text: Option<&str>) -> KeyStroke {
+        KeyStroke {
+            code,
+            modifiers,
+            text: text.map(str::to_string),
         }
     }
+
+    fn captured_keys(source: ScriptedSource) -> Vec<(bool, KeyStroke)> {
+        capture_from_source(source)
+            .expect("scripted capture")
+            .into_iter()
+            .map(|event| match event.kind {
+                MacroEventKind::KeyDown { key } => (true, key),
+                MacroEventKind::KeyUp { key } => (false, key),
+                other => panic!("unexpected event {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn labels_modifier_combos_with_the_modifiers_held() {
+        let source = ScriptedSource::default()
+            .push(EventType::KeyPress(RdevKey::ControlLeft))
+            .push(EventType::KeyPress(RdevKey::ShiftLeft))
+            .push_named(EventType::KeyPress(RdevKey::KeyA), "\u{1}")
+            .push(EventType::KeyRelease(RdevKey::KeyA))
+            .push(EventType::KeyRelease(RdevKey::ShiftLeft))
+            .push(EventType::KeyRelease(RdevKey::ControlLeft));
+
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        let ctrl_shift = Modifiers { shift: true, ..ctrl };
+        let expected = vec![
+            (true, stroke(KeyCode::Ctrl, Modifiers::default(), None)),
+            (true, stroke(KeyCode::Shift, ctrl, None)),
+            (true, stroke(KeyCode::Char('a'), ctrl_shift, None)),
+            (false, stroke(KeyCode::Char('a'), ctrl_shift, None)),
+            (false, stroke(KeyCode::Shift, ctrl, None)),
+            (false, stroke(KeyCode::Ctrl, Modifiers::default(), None)),
+        ];
+        let keys = captured_keys(source);
+        assert_eq!(keys, expected);
+        assert_eq!(keys[2].1.label(), "Ctrl+Shift+A");
+    }
+
+    #[test]
+    fn keeps_the_character_altgr_produces() {
+        let source = ScriptedSource::default()
+            .push(EventType::KeyPress(RdevKey::AltGr))
+            .push_named(EventType::KeyPress(RdevKey::KeyQ), "@")
+            .push(EventType::KeyRelease(RdevKey::KeyQ))
+            .push(EventType::KeyRelease(RdevKey::AltGr));
+
+        let alt = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+        let keys = captured_keys(source);
+        assert_eq!(keys[1], (true, stroke(KeyCode::Char('q'), alt, Some("@"))));
+        assert_eq!(keys[1].1.label(), "Alt+@");
+        assert_eq!(keys[2], (false, stroke(KeyCode::Char('q'), alt, None)));
+        assert_eq!(keys[3], (false, stroke(KeyCode::Alt, Modifiers::default(), None)));
+    }
+
+    #[test]
+    fn shifted_symbols_keep_the_physical_key() {
+        let source = ScriptedSource::default()
+            .push(EventType::KeyPress(RdevKey::ShiftLeft))
+            .push_named(EventType::KeyPress(RdevKey::Num1), "!")
+            .push(EventType::KeyRelease(RdevKey::Num1))
+            .push(EventType::KeyRelease(RdevKey::ShiftLeft));
+
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        let keys = captured_keys(source);
+        assert_eq!(keys[1], (true, stroke(KeyCode::Char('1'), shift, Some("!"))));
+        assert_eq!(keys[1].1.label(), "Shift+!");
+    }
+
+    #[test]
+    fn ignores_input_while_capture_is_off() {
+        let recorder = RecorderState::default();
+        let context = recorder.capture_context();
+        context.handle_event(&synthetic_event(EventType::KeyPress(RdevKey::KeyA), None));
+        assert!(recorder.events.lock().is_empty());
+    }
 }
@@ -0,0 +1,528 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{Local, TimeZone};
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    app_state::AppState,
+    cron::CronSchedule,
+    macro_player::{refuse_unknown_keys, start_playback, PlaybackControl, PlaybackOptions},
+    macro_store::{generate_id, read_if_exists, write_atomic},
+    types::{
+        BusyPolicy,
+        MacroSchedule,
+        PlaybackTiming,
+        ScheduleOverview,
+        ScheduleTrigger,
+        ScheduledRun,
+        UpcomingRun,
+    },
+};
+
+pub const SCHEDULES_FILENAME: &str = "macroarc.schedules.json";
+
+const SCHEDULES_CHANNEL: &str = "macro://schedules";
+const SCHEDULE_RUN_CHANNEL: &str = "macro://schedule-run";
+
+/// Longest the scheduler sleeps between checks, so wall-clock changes and
+/// system sleep are noticed.
+const MAX_IDLE: Duration = Duration::from_secs(30);
+
+/// How often runs queued behind a busy player check whether it is free.
+const BUSY_RETRY: Duration = Duration::from_millis(250);
+
+/// A one-shot run found more than this late, e.g. because the app was not
+/// running at the time, is reported as missed instead of being played.
+const MISSED_GRACE_MS: u64 = 60_000;
+
+#[derive(Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    #[serde(default)]
+    schedules: Vec<MacroSchedule>,
+}
+
+/// Plays stored macros on their timetables through the macro player. Until
+/// `open` succeeds schedules live in memory only.
+#[derive(Default)]
+pub struct Scheduler {
+    state: Mutex<SchedulerState>,
+    changed: Condvar,
+    started: AtomicBool,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    path: Option<PathBuf>,
+    schedules: Vec<MacroSchedule>,
+    /// Next run of every enabled schedule, in Unix milliseconds.
+    next_runs: HashMap<String, u64>,
+    /// Schedules whose run waits for the player to be free, oldest first.
+    waiting: Vec<String>,
+}
+
+impl Scheduler {
+    /// Loads the schedules saved at `path`. A missing file means no schedules.
+    pub fn open(&self, path: PathBuf) -> Result<(), String> {
+        let file = match read_if_exists(&path)? {
+            Some(raw) => serde_json::from_str::<ScheduleFile>(&raw)
+                .map_err(|error| format!("{} is not a valid schedule file: {error}", path.display()))?,
+            None => ScheduleFile::default(),
+        };
+
+        let mut state = self.state.lock();
+        state.path = Some(path);
+        state.schedules = file.schedules;
+        state.plan_all(now_ms());
+        self.changed.notify_all();
+        Ok(())
+    }
+
+    pub fn overview(&self) -> ScheduleOverview {
+        self.state.lock().overview()
+    }
+
+    /// Inserts `schedule`, or replaces the schedule with the same id, and
+    /// plans its next run from now.
+    pub fn save(&self, mut schedule: MacroSchedule) -> Result<MacroSchedule, String> {
+        validate_schedule(&schedule, now_ms())?;
+
+        let mut state = self.state.lock();
+        if schedule.id.trim().is_empty() {
+            schedule.id = generate_id();
+        }
+        match state.position(&schedule.id) {
+            Some(index) => state.schedules[index] = schedule.clone(),
+            None => state.schedules.push(schedule.clone()),
+        }
+        if !schedule.enabled {
+            state.waiting.retain(|id| *id != schedule.id);
+        }
+        state.plan(&schedule.id, now_ms());
+        state.persist()?;
+        self.changed.notify_all();
+        Ok(schedule)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<MacroSchedule, String> {
+        let mut state = self.state.lock();
+        let index = state.position(id).ok_or_else(|| format!("No schedule with id `{id}`"))?;
+        let removed = state.schedules.remove(index);
+        state.next_runs.remove(id);
+        state.waiting.retain(|waiting| waiting != id);
+        state.persist()?;
+        self.changed.notify_all();
+        Ok(removed)
+    }
+
+    /// Drops every run queued behind a busy player, returning how many there
+    /// were, and tells the frontend.
+    pub fn clear_waiting(&self, app: &AppHandle) -> usize {
+        let mut state = self.state.lock();
+        let cleared = state.waiting.len();
+        if cleared > 0 {
+            state.waiting.clear();
+            let _ = app.emit(SCHEDULES_CHANNEL, state.overview());
+        }
+        cleared
+    }
+
+    /// Starts the scheduler thread unless it is already running.
+    pub fn start(&self, app: AppHandle) {
+        if self.started.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        thread::spawn(move || run_scheduler(&app));
+    }
+
+    /// Handles a run that came due at `due_at`.
+    fn fire(&self, app: &AppHandle, schedule: &MacroSchedule, due_at: u64) {
+        let late_ms = now_ms().saturating_sub(due_at);
+        if matches!(schedule.trigger, ScheduleTrigger::Once { .. }) && late_ms > MISSED_GRACE_MS {
+            report_run(app, schedule, "missed", Some(format!("due {} s ago", late_ms / 1000)));
+            return;
+        }
+
+        if !app.state::<AppState>().macro_player.lock().is_busy() {
+            self.launch(app, schedule);
+            return;
+        }
+        match schedule.when_busy {
+            BusyPolicy::Skip => report_run(app, schedule, "skipped", Some("playback already running".into())),
+            BusyPolicy::Queue => {
+                let mut state = self.state.lock();
+                if !state.waiting.contains(&schedule.id) {
+                    state.waiting.push(schedule.id.clone());
+                }
+                drop(state);
+                report_run(app, schedule, "queued", None);
+            }
+        }
+    }
+
+    /// Starts the oldest queued run if the player has become free. Returns
+    /// whether a run left the queue.
+    fn release_waiting(&self, app: &AppHandle) -> bool {
+        if app.state::<AppState>().macro_player.lock().is_busy() {
+            return false;
+        }
+        let schedule = {
+            let mut state = self.state.lock();
+            if state.waiting.is_empty() {
+                return false;
+            }
+            let id = state.waiting.remove(0);
+            state.position(&id).map(|index| state.schedules[index].clone())
+        };
+        if let Some(schedule) = schedule {
+            self.launch(app, &schedule);
+        }
+        true
+    }
+
+    fn launch(&self, app: &AppHandle, schedule: &MacroSchedule) {
+        if let Err(error) = play_schedule(app, schedule) {
+            report_run(app, schedule, "failed", Some(error));
+            return;
+        }
+
+        let mut state = self.state.lock();
+        if let Some(index) = state.position(&schedule.id) {
+            state.schedules[index].last_run_ms = Some(now_ms());
+        }
+        drop(state);
+        report_run(app, schedule, "started", None);
+    }
+}
+
+impl SchedulerState {
+    fn position(&self, id: &str) -> Option<usize> {
+        self.schedules.iter().position(|schedule| schedule.id == id)
+    }
+
+    fn plan_all(&mut self, now: u64) {
+        self.next_runs = self
+            .schedules
+            .iter()
+            .filter_map(|schedule| Some((schedule.id.clone(), first_run(schedule, now)?)))
+            .collect();
+    }
+
+    fn plan(&mut self, id: &str, now: u64) {
+        let next = self.position(id).and_then(|index| first_run(&self.schedules[index], now));
+        match next {
+            Some(at) => self.next_runs.insert(id.to_string(), at),
+            None => self.next_runs.remove(id),
+        };
+    }
+
+    /// Removes the runs due by `now`, planning each schedule's following run,
+    /// and returns them with the time they were due. One-shot schedules are
+    /// disabled once they come due.
+    fn take_due(&mut self, now: u64) -> Vec<(MacroSchedule, u64)> {
+        let mut due = Vec::new();
+        for schedule in &mut self.schedules {
+            let Some(at) = self.next_runs.get(&schedule.id).copied().filter(|at| *at <= now) else {
+                continue;
+            };
+
+            let next = match &schedule.trigger {
+                ScheduleTrigger::Every { minutes } => {
+                    let period = u64::from(*minutes) * 60_000;
+                    Some(if at + period > now { at + period } else { now + period })
+                }
+                ScheduleTrigger::Once { .. } => {
+                    schedule.enabled = false;
+                    None
+                }
+                ScheduleTrigger::Cron { .. } => first_run(schedule, now),
+            };
+            match next {
+                Some(next) => self.next_runs.insert(schedule.id.clone(), next),
+                None => self.next_runs.remove(&schedule.id),
+            };
+            due.push((schedule.clone(), at));
+        }
+        due
+    }
+
+    /// How long the scheduler thread may sleep before something needs doing.
+    fn idle_time(&self, now: u64) -> Duration {
+        let until_next = self
+            .next_runs
+            .values()
+            .min()
+            .map_or(MAX_IDLE, |at| Duration::from_millis(at.saturating_sub(now)));
+        let idle = until_next.min(MAX_IDLE);
+        if self.waiting.is_empty() {
+            idle
+        } else {
+            idle.min(BUSY_RETRY)
+        }
+    }
+
+    fn overview(&self) -> ScheduleOverview {
+        let mut upcoming: Vec<UpcomingRun> = self
+            .schedules
+            .iter()
+            .filter_map(|schedule| {
+                Some(UpcomingRun {
+                    schedule_id: schedule.id.clone(),
+                    macro_id: schedule.macro_id.clone(),
+                    at_ms: *self.next_runs.get(&schedule.id)?,
+                })
+            })
+            .collect();
+        upcoming.sort_by_key(|run| run.at_ms);
+
+        ScheduleOverview {
+            schedules: self.schedules.clone(),
+            upcoming,
+            waiting: self.waiting.clone(),
+        }
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = ScheduleFile {
+            schedules: self.schedules.clone(),
+        };
+        let contents = serde_json::to_string_pretty(&file)
+            .map_err(|error| format!("failed to serialize schedules: {error}"))?;
+        write_atomic(path, contents.as_bytes()).map_err(|error| format!("failed to write {}: {error}", path.display()))
+    }
+}
+
+fn run_scheduler(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let scheduler = &state.scheduler;
+
+    loop {
+        let due = scheduler.state.lock().take_due(now_ms());
+        let mut changed = !due.is_empty();
+        for (schedule, due_at) in &due {
+            scheduler.fire(app, schedule, *due_at);
+        }
+        changed |= scheduler.release_waiting(app);
+
+        let mut state = scheduler.state.lock();
+        if changed {
+            if let Err(error) = state.persist() {
+                let _ = app.emit("macro://error", error);
+            }
+            let _ = app.emit(SCHEDULES_CHANNEL, state.overview());
+        }
+
+        let idle = state.idle_time(now_ms());
+        if !idle.is_zero() {
+            scheduler.changed.wait_for(&mut state, idle);
+        }
+    }
+}
+
+/// Plays the macro `schedule` points at on its own playback channel.
+fn play_schedule(app: &AppHandle, schedule: &MacroSchedule) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let stored = state
+        .macro_store
+        .lock()
+        .get(&schedule.macro_id)
+        .cloned()
+        .ok_or_else(|| format!("No macro with id `{}`", schedule.macro_id))?;
+    let events = stored.macro_events();
+    if events.is_empty() {
+        return Err(format!("Macro `{}` has no events", stored.name));
+    }
+    refuse_unknown_keys(&events).map_err(|error| format!("Macro `{}`: {error}", stored.name))?;
+    let window = app
+        .get_webview_window("main")
+        .ok_or("Main window is not available")?
+        .as_ref()
+        .window();
+
+    let stored_speed = stored.playback_speed.map_or(1.0, |speed| speed as f32);
+    let options = PlaybackOptions {
+        playback_speed: schedule.playback_speed.unwrap_or(stored_speed).max(0.1),
+        loop_count: schedule.loop_count.unwrap_or(stored.loop_count).max(1),
+        limits: schedule.limits.or(*state.playback_limits.lock()),
+        timing: PlaybackTiming::default(),
+        humanize: None,
+        seed: None,
+    };
+    let channel = format!("schedule:{}", schedule.id);
+    let context_id = Some(channel.clone());
+    start_playback(&state, &window, channel, events, options, PlaybackControl::default(), context_id);
+    Ok(())
+}
+
+fn report_run(app: &AppHandle, schedule: &MacroSchedule, action: &str, detail: Option<String>) {
+    let payload = ScheduledRun {
+        schedule_id: schedule.id.clone(),
+        macro_id: schedule.macro_id.clone(),
+        action: action.into(),
+        detail,
+    };
+    let _ = app.emit(SCHEDULE_RUN_CHANNEL, payload);
+}
+
+/// The first run of `schedule` after `now`, if it is enabled and can fire.
+fn first_run(schedule: &MacroSchedule, now: u64) -> Option<u64> {
+    if !schedule.enabled {
+        return None;
+    }
+    match &schedule.trigger {
+        ScheduleTrigger::Cron { expression } => {
+            let after = Local.timestamp_millis_opt(i64::try_from(now).ok()?).single()?;
+            let next = CronSchedule::parse(expression).ok()?.next_after(after)?;
+            u64::try_from(next.timestamp_millis()).ok()
+        }
+        ScheduleTrigger::Every { minutes } => Some(now + u64::from(*minutes) * 60_000),
+        ScheduleTrigger::Once { at_ms } => Some(*at_ms),
+    }
+}
+
+fn validate_schedule(schedule: &MacroSchedule, now: u64) -> Result<(), String> {
+    if schedule.macro_id.trim().is_empty() {
+        return Err("Schedule needs a macro id".into());
+    }
+    match &schedule.trigger {
+        ScheduleTrigger::Cron { expression } => {
+            CronSchedule::parse(expression)?;
+        }
+        ScheduleTrigger::Every { minutes: 0 } => return Err("Schedule interval must be at least 1 minute".into()),
+        ScheduleTrigger::Every { .. } => {}
+        ScheduleTrigger::Once { at_ms } => {
+            if schedule.enabled && *at_ms < now {
+                return Err("One-shot schedule time is in the past".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[tauri::command]
+pub fn list_schedules(state: State<'_, AppState>) -> ScheduleOverview {
+    state.scheduler.overview()
+}
+
+#[tauri::command]
+pub fn save_schedule(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    schedule: MacroSchedule,
+) -> Result<MacroSchedule, String> {
+    if state.macro_store.lock().get(&schedule.macro_id).is_none() {
+        return Err(format!("No macro with id `{}`", schedule.macro_id));
+    }
+    let saved = state.scheduler.save(schedule)?;
+    let _ = app.emit(SCHEDULES_CHANNEL, state.scheduler.overview());
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_schedule(state: State<'_, AppState>, app: AppHandle, id: String) -> Result<MacroSchedule, String> {
+    let removed = state.scheduler.delete(&id)?;
+    let _ = app.emit(SCHEDULES_CHANNEL, state.scheduler.overview());
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PlaybackLimits;
+
+    const MINUTE: u64 = 60_000;
+
+    fn schedule(id: &str, trigger: ScheduleTrigger) -> MacroSchedule {
+        MacroSchedule {
+            id: id.into(),
+            macro_id: "m1".into(),
+            trigger,
+            enabled: true,
+            playback_speed: None,
+            loop_count: None,
+            limits: PlaybackLimits::default(),
+            when_busy: BusyPolicy::Skip,
+            last_run_ms: None,
+        }
+    }
+
+    /// A scheduler holding `schedules`, each next due at the given time.
+    fn planned(schedules: &[(MacroSchedule, u64)]) -> SchedulerState {
+        SchedulerState {
+            schedules: schedules.iter().map(|(schedule, _)| schedule.clone()).collect(),
+            next_runs: schedules.iter().map(|(schedule, at)| (schedule.id.clone(), *at)).collect(),
+            ..SchedulerState::default()
+        }
+    }
+
+    fn due_ids(due: &[(MacroSchedule, u64)]) -> Vec<(&str, u64)> {
+        due.iter().map(|(schedule, at)| (schedule.id.as_str(), *at)).collect()
+    }
+
+    #[test]
+    fn take_due_returns_only_runs_that_have_come_due() {
+        let every = schedule("every", ScheduleTrigger::Every { minutes: 5 });
+        let once = schedule("once", ScheduleTrigger::Once { at_ms: 20 * MINUTE });
+        let mut state = planned(&[(every, 10 * MINUTE), (once, 20 * MINUTE)]);
+
+        assert!(state.take_due(10 * MINUTE - 1).is_empty());
+        assert_eq!(due_ids(&state.take_due(10 * MINUTE)), [("every", 10 * MINUTE)]);
+        assert!(state.take_due(10 * MINUTE).is_empty());
+    }
+
+    #[test]
+    fn take_due_plans_the_next_run_of_each_trigger() {
+        let cases = [
+            // On time: the next run keeps to the period.
+            (ScheduleTrigger::Every { minutes: 5 }, 10 * MINUTE, 11 * MINUTE, Some(15 * MINUTE)),
+            // Periods missed while asleep are not made up.
+            (ScheduleTrigger::Every { minutes: 5 }, 10 * MINUTE, 31 * MINUTE, Some(36 * MINUTE)),
+            (ScheduleTrigger::Once { at_ms: 10 * MINUTE }, 10 * MINUTE, 10 * MINUTE, None),
+        ];
+        for (trigger, at, now, expected) in cases {
+            let mut state = planned(&[(schedule("s", trigger.clone()), at)]);
+
+            assert_eq!(due_ids(&state.take_due(now)), [("s", at)], "{trigger:?}");
+            assert_eq!(state.next_runs.get("s").copied(), expected, "{trigger:?}");
+        }
+    }
+
+    #[test]
+    fn take_due_disables_one_shot_schedules() {
+        let mut state = planned(&[(schedule("once", ScheduleTrigger::Once { at_ms: MINUTE }), MINUTE)]);
+
+        let due = state.take_due(MINUTE);
+
+        assert!(!due[0].0.enabled);
+        assert!(!state.schedules[0].enabled);
+        assert_eq!(first_run(&state.schedules[0], 0), None);
+    }
+
+    #[test]
+    fn take_due_plans_cron_runs_from_now() {
+        let trigger = ScheduleTrigger::Cron { expression: "*/10 * * * *".into() };
+        let now = 1_700_000_123_456;
+        let mut state = planned(&[(schedule("cron", trigger), now - MINUTE)]);
+
+        assert_eq!(due_ids(&state.take_due(now)), [("cron", now - MINUTE)]);
+        let next = state.next_runs["cron"];
+        assert!(next > now && next <= now + 10 * MINUTE, "{next} is not within ten minutes of {now}");
+        assert_eq!(next % MINUTE, 0);
+    }
+}
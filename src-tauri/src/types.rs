@@ -1,23 +1,42 @@
+use std::fmt;
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::macro_validation::MacroIssue;
+
+/// `format` given to key label strings in generated schemas. Validation
+/// checks it with `KeyStroke::parse`.
+pub const KEY_STROKE_FORMAT: &str = "key-stroke";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum MacroEventKind {
     MouseMove { x: i32, y: i32 },
-    MouseDown { button: String },
-    MouseUp { button: String },
-    KeyDown { key: String },
-    KeyUp { key: String },
+    MouseDown { button: MouseButton },
+    MouseUp { button: MouseButton },
+    KeyDown {
+        #[serde(flatten)]
+        key: KeyStroke,
+    },
+    KeyUp {
+        #[serde(flatten)]
+        key: KeyStroke,
+    },
     Scroll { delta_x: i64, delta_y: i64 },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct MacroEvent {
     pub offset_ms: u64,
     pub kind: MacroEventKind,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct MacroPlaybackRequest {
     pub events: Vec<MacroEvent>,
     #[serde(default = "default_speed")]
@@ -25,17 +44,225 @@ pub struct MacroPlaybackRequest {
     #[serde(default = "default_loops")]
     pub loop_count: u32,
     pub context_id: Option<String>,
+    /// Playback channel to play on. Defaults to `context_id`, then to
+    /// `default`. Starting a playback only replaces the one on its channel.
+    #[serde(default)]
+    pub channel: Option<String>,
+    /// First event to play (inclusive index).
+    #[serde(default)]
+    pub start_event: Option<usize>,
+    /// Last event to play (inclusive index).
+    #[serde(default)]
+    pub end_event: Option<usize>,
+    /// Skip events recorded before this offset.
+    #[serde(default)]
+    pub start_ms: Option<u64>,
+    /// Skip events recorded at or after this offset.
+    #[serde(default)]
+    pub end_ms: Option<u64>,
+    /// Play under the step-through debugger, halting before the first event.
+    #[serde(default)]
+    pub debug: bool,
+    /// Indices into `events` the debugger halts at.
+    #[serde(default)]
+    pub breakpoints: Vec<usize>,
+    /// Simulate playback without injecting input and return the timeline.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Watchdog limits for this playback. Limits left unset fall back to the
+    /// defaults from `set_playback_limits`.
+    #[serde(default)]
+    pub limits: PlaybackLimits,
+    /// Fill in cursor moves between recorded ones instead of jumping.
+    #[serde(default)]
+    pub movement: Option<MouseMovement>,
+    /// Gap caps, as-fast-as-possible mode and per-range speeds.
+    #[serde(default)]
+    pub timing: PlaybackTiming,
+    /// Vary timing and click positions so the input is not perfectly regular.
+    #[serde(default)]
+    pub humanize: Option<Humanize>,
+    /// Seed for the randomness in `movement` and `humanize`, so a run can be
+    /// reproduced. A random seed is used when unset; either way the seed is
+    /// reported in the final playback status.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// How the cursor travels to each recorded `mouse-move` position. Extra moves
+/// are spread over the time between the previous event and the move, so the
+/// timing of the macro does not change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+pub struct MouseMovement {
+    pub model: MovementModel,
+    /// Interpolated moves per second, at most 1000.
+    #[serde(default = "default_movement_rate")]
+    pub rate_hz: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MovementModel {
+    /// Straight line at constant speed.
+    Linear,
+    /// Straight line that speeds up and slows down.
+    Eased,
+    /// Eased curve through randomized control points.
+    Bezier,
+}
+
+/// Controls over the delay before each event, on top of `playback_speed`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, JsonSchema)]
+pub struct PlaybackTiming {
+    /// Longest any single delay may last, in milliseconds after speed is
+    /// applied, so long idle stretches replay quickly.
+    #[serde(default)]
+    pub max_gap_ms: Option<u64>,
+    /// Ignore the recorded delays and play every event `min_gap_ms` after the
+    /// previous one.
+    #[serde(default)]
+    pub as_fast_as_possible: bool,
+    #[serde(default)]
+    pub min_gap_ms: u64,
+    /// Speeds for ranges of events, replacing `playback_speed` for the delay
+    /// before each event in the range. The first matching segment wins.
+    #[serde(default)]
+    pub segments: Vec<SpeedSegment>,
+}
+
+/// A range of events, by index into the request's `events`, with its own
+/// playback speed.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, JsonSchema)]
+pub struct SpeedSegment {
+    /// First event of the range (inclusive index).
+    pub start_event: usize,
+    /// Last event of the range (inclusive index).
+    pub end_event: usize,
+    pub playback_speed: f32,
+}
+
+/// Random variation applied while a macro plays. Every loop is varied anew.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, JsonSchema)]
+pub struct Humanize {
+    /// Jitter added to each delay between consecutive events. Events recorded
+    /// at the same moment stay together.
+    #[serde(default)]
+    pub delay: Option<DelayJitter>,
+    /// Standard deviation of the change to how long each key or button is
+    /// held, in milliseconds.
+    #[serde(default)]
+    pub hold_variance_ms: f64,
+    /// Most pixels a click may land away from the recorded cursor position.
+    #[serde(default)]
+    pub click_radius_px: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, JsonSchema)]
+#[serde(tag = "distribution", rename_all = "kebab-case")]
+pub enum DelayJitter {
+    /// Normally distributed with this standard deviation.
+    Gaussian { std_dev_ms: f64 },
+    /// Anywhere within plus or minus this many milliseconds.
+    Uniform { range_ms: f64 },
+}
+
+/// Watchdog limits that end a playback with the `limit-reached` status.
+/// `null` means no limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PlaybackLimits {
+    /// Longest a playback may run, in wall-clock milliseconds including pauses.
+    #[serde(default)]
+    pub max_duration_ms: Option<u64>,
+    /// Most inputs a playback may inject, across all loops. Every input sent
+    /// counts, including the lead-in of a range, interpolated cursor moves and
+    /// humanized click nudges; only the releases at the end of a loop do not.
+    #[serde(default)]
+    pub max_events: Option<u64>,
+    /// Most loops a playback may start.
+    #[serde(default)]
+    pub max_loops: Option<u32>,
+}
+
+impl PlaybackLimits {
+    /// These limits, with unset ones taken from `fallback`.
+    pub fn or(self, fallback: PlaybackLimits) -> Self {
+        Self {
+            max_duration_ms: self.max_duration_ms.or(fallback.max_duration_ms),
+            max_events: self.max_events.or(fallback.max_events),
+            max_loops: self.max_loops.or(fallback.max_loops),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct AutoClickerRequest {
-    pub button: Option<String>,
+    pub button: Option<MouseButton>,
     pub interval_ms: u64,
     pub jitter_ms: Option<u64>,
     pub burst: Option<u32>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Physical-input safeguard for playback and the autoclicker. Set
+/// `mouse_threshold_px` or `corner` to `null` to turn that trigger off.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KillSwitchSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Abort once the real cursor moves this far from where playback put it.
+    #[serde(default = "default_kill_switch_threshold")]
+    pub mouse_threshold_px: Option<u32>,
+    /// Abort when Escape is pressed by hand.
+    #[serde(default = "default_true")]
+    pub escape: bool,
+    /// Abort when the real cursor reaches this corner of the primary screen.
+    #[serde(default)]
+    pub corner: Option<ScreenCorner>,
+    /// How close to the corner counts as "in" it.
+    #[serde(default = "default_corner_size")]
+    pub corner_size_px: u32,
+}
+
+impl Default for KillSwitchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mouse_threshold_px: default_kill_switch_threshold(),
+            escape: true,
+            corner: None,
+            corner_size_px: default_corner_size(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl ScreenCorner {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreenCorner::TopLeft => "top-left",
+            ScreenCorner::TopRight => "top-right",
+            ScreenCorner::BottomLeft => "bottom-left",
+            ScreenCorner::BottomRight => "bottom-right",
+        }
+    }
+}
+
+/// Payload of `macro://kill-switch`. `source` is `playback` or `autoclicker`.
+#[derive(Debug, Serialize, Clone)]
+pub struct KillSwitchTripped {
+    pub source: String,
+    pub reason: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Deserialize, Clone, JsonSchema)]
 pub struct OverlayWindowDescriptor {
     pub id: String,
     pub title: Option<String>,
@@ -58,10 +285,218 @@ pub struct OverlayGeometryPayload {
 
 #[derive(Debug, Serialize, Clone)]
 pub struct MacroPlaybackStatus {
+    pub channel: String,
+    pub context_id: Option<String>,
+    pub state: String,
+    /// Keys and buttons the player let go of on the macro's behalf.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub released: Vec<String>,
+    /// With state `limit-reached`: `max-duration`, `max-events` or `max-loops`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<String>,
+    /// Seed of a randomized playback; pass it back as `seed` to repeat the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// A channel with a playback in progress, as listed by
+/// `get_playback_channels`. `state` is `playing`, `paused`, `halted` or
+/// `stopping`.
+#[derive(Debug, Serialize, Clone)]
+pub struct PlaybackChannelStatus {
+    pub channel: String,
     pub context_id: Option<String>,
     pub state: String,
 }
 
+/// Payload of `macro://progress`, emitted at most every 100 ms while a macro
+/// plays. `event_index` and `total_events` refer to the request's `events`;
+/// `current_loop` is one-based; `remaining_ms` is estimated at the request's
+/// `playback_speed`.
+#[derive(Debug, Serialize, Clone)]
+pub struct MacroPlaybackProgress {
+    pub context_id: Option<String>,
+    pub event_index: usize,
+    pub total_events: usize,
+    pub current_loop: u32,
+    pub loop_count: u32,
+    pub elapsed_ms: u64,
+    pub remaining_ms: u64,
+}
+
+/// Payload of `macro://debug`, emitted each time a debug playback halts.
+/// `event` is the event about to be injected, at index `event_index` of the
+/// request's `events`; `held` lists the keys and buttons the macro holds at
+/// that point.
+#[derive(Debug, Serialize, Clone)]
+pub struct MacroDebugHalt {
+    pub context_id: Option<String>,
+    pub current_loop: u32,
+    pub event_index: usize,
+    pub event: MacroEvent,
+    pub held: Vec<String>,
+}
+
+/// One input of a dry-run timeline. `at_ms` is measured from the start of
+/// playback at the request's `playback_speed`; `current_loop` is one-based.
+/// `event_index` points into the request's `events` and is `null` for inputs
+/// playback adds itself, such as the lead-in of a range or interpolated
+/// cursor moves.
+#[derive(Debug, Serialize, Clone)]
+pub struct ScheduledMacroEvent {
+    pub at_ms: f64,
+    pub current_loop: u32,
+    pub event_index: Option<usize>,
+    pub kind: MacroEventKind,
+}
+
+/// Result of `play_macro` with `dry_run` set.
+#[derive(Debug, Serialize, Clone)]
+pub struct MacroDryRun {
+    pub timeline: Vec<ScheduledMacroEvent>,
+    pub duration_ms: f64,
+    pub issues: Vec<MacroIssue>,
+}
+
+/// What an emergency stop interrupted. Returned by `trigger_emergency_stop`
+/// and emitted on `macro://emergency-stop` once held inputs are released.
+#[derive(Debug, Serialize, Clone)]
+pub struct EmergencyStopReport {
+    /// Whether a player was running when the stop was raised.
+    pub playback_stopped: bool,
+    pub autoclicker_stopped: bool,
+    pub recording_stopped: bool,
+    /// Scheduled runs that were waiting for the player and will not start.
+    pub waiting_runs_dropped: usize,
+}
+
+/// How the native playback queue runs its entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSettings {
+    /// Gap between the last event of one entry and the start of the next.
+    #[serde(default)]
+    pub padding_ms: u64,
+    /// Start the queue over once it has played every entry.
+    #[serde(default)]
+    pub loop_enabled: bool,
+    /// Gap before the queue starts over.
+    #[serde(default = "default_queue_loop_delay")]
+    pub loop_delay_ms: u64,
+}
+
+impl Default for QueueSettings {
+    fn default() -> Self {
+        Self {
+            padding_ms: 0,
+            loop_enabled: false,
+            loop_delay_ms: default_queue_loop_delay(),
+        }
+    }
+}
+
+/// Payload of `macro://queue-state`, emitted whenever the queue or its
+/// settings change and as the queue moves from one entry to the next.
+/// `current_index` points into `queue`; `cycle` counts passes through the
+/// queue from one.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroQueueState {
+    pub queue: Vec<String>,
+    pub padding_ms: u64,
+    pub loop_enabled: bool,
+    pub loop_delay_ms: u64,
+    pub running: bool,
+    pub current_index: Option<usize>,
+    pub cycle: u32,
+}
+
+/// A stored macro played by the backend on a timetable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroSchedule {
+    /// Left empty when creating a schedule; the backend assigns one.
+    #[serde(default)]
+    pub id: String,
+    pub macro_id: String,
+    pub trigger: ScheduleTrigger,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Defaults to the stored macro's playback speed.
+    #[serde(default)]
+    pub playback_speed: Option<f32>,
+    /// Defaults to the stored macro's loop count.
+    #[serde(default)]
+    pub loop_count: Option<u32>,
+    /// Unset limits fall back to the defaults from `set_playback_limits`.
+    #[serde(default)]
+    pub limits: PlaybackLimits,
+    #[serde(default)]
+    pub when_busy: BusyPolicy,
+    /// Unix time in milliseconds of the last run the schedule started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run_ms: Option<u64>,
+}
+
+/// When a schedule fires. Cron expressions use local time; `once` takes Unix
+/// time in milliseconds and disables the schedule after it fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ScheduleTrigger {
+    Cron {
+        expression: String,
+    },
+    Every {
+        minutes: u32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Once {
+        at_ms: u64,
+    },
+}
+
+/// What a schedule does when it fires while something is already playing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum BusyPolicy {
+    /// Drop this run.
+    #[default]
+    Skip,
+    /// Start the run as soon as the player is free.
+    Queue,
+}
+
+/// Payload of `macro://schedules` and result of `list_schedules`. `upcoming`
+/// holds the next run of every enabled schedule, soonest first; `waiting`
+/// lists schedules whose run is queued behind a busy player.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleOverview {
+    pub schedules: Vec<MacroSchedule>,
+    pub upcoming: Vec<UpcomingRun>,
+    pub waiting: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingRun {
+    pub schedule_id: String,
+    pub macro_id: String,
+    pub at_ms: u64,
+}
+
+/// Payload of `macro://schedule-run`, emitted each time a schedule fires.
+/// `action` is `started`, `skipped`, `queued`, `missed` or `failed`.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRun {
+    pub schedule_id: String,
+    pub macro_id: String,
+    pub action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct FrontendStatus {
     pub recording: bool,
@@ -76,3 +511,585 @@ pub fn default_speed() -> f32 {
 pub fn default_loops() -> u32 {
     1
 }
+
+pub fn default_true() -> bool {
+    true
+}
+
+pub fn default_kill_switch_threshold() -> Option<u32> {
+    Some(50)
+}
+
+pub fn default_corner_size() -> u32 {
+    4
+}
+
+pub fn default_queue_loop_delay() -> u64 {
+    1500
+}
+
+pub fn default_movement_rate() -> u32 {
+    120
+}
+
+/// Mouse button carried by macro events. Serialized as the lowercase names
+/// stored in `macroarc.macros.json` (`"left"`, `"right"`, `"middle"`, `"x1"`, `"x2"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+impl MouseButton {
+    pub const ALL: [MouseButton; 5] = [
+        MouseButton::Left,
+        MouseButton::Right,
+        MouseButton::Middle,
+        MouseButton::X1,
+        MouseButton::X2,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MouseButton::Left => "left",
+            MouseButton::Right => "right",
+            MouseButton::Middle => "middle",
+            MouseButton::X1 => "x1",
+            MouseButton::X2 => "x2",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "left" => Ok(MouseButton::Left),
+            "right" => Ok(MouseButton::Right),
+            "middle" => Ok(MouseButton::Middle),
+            "x1" | "back" => Ok(MouseButton::X1),
+            "x2" | "forward" => Ok(MouseButton::X2),
+            _ => Err(format!(
+                "unknown mouse button `{value}` (expected left, right, middle, x1 or x2)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MouseButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for MouseButton {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        MouseButton::parse(&value)
+    }
+}
+
+impl From<MouseButton> for String {
+    fn from(button: MouseButton) -> Self {
+        button.as_str().to_string()
+    }
+}
+
+impl JsonSchema for MouseButton {
+    fn schema_name() -> String {
+        "MouseButton".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(MouseButton::ALL.iter().map(|button| button.as_str().into()).collect()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub fn is_empty(&self) -> bool {
+        !(self.ctrl || self.shift || self.alt || self.meta)
+    }
+
+    /// Sets the modifier named by `segment`, accepting the names and aliases
+    /// of Tauri accelerators. `CommandOrControl` is `Meta` on macOS and `Ctrl`
+    /// elsewhere.
+    fn apply(&mut self, segment: &str) -> bool {
+        match segment.to_lowercase().as_str() {
+            "commandorcontrol" | "commandorctrl" | "cmdorcontrol" | "cmdorctrl" => {
+                if cfg!(target_os = "macos") {
+                    self.meta = true;
+                } else {
+                    self.ctrl = true;
+                }
+            }
+            "ctrl" | "control" => self.ctrl = true,
+            "shift" => self.shift = true,
+            "alt" | "altgr" | "option" => self.alt = true,
+            "meta" | "command" | "cmd" | "super" | "win" => self.meta = true,
+            _ => return false,
+        }
+        true
+    }
+
+    /// The modifier keys that are held, in `Ctrl`, `Shift`, `Alt`, `Meta` order.
+    pub fn keys(&self) -> Vec<KeyCode> {
+        let mut keys = Vec::new();
+        if self.ctrl {
+            keys.push(KeyCode::Ctrl);
+        }
+        if self.shift {
+            keys.push(KeyCode::Shift);
+        }
+        if self.alt {
+            keys.push(KeyCode::Alt);
+        }
+        if self.meta {
+            keys.push(KeyCode::Meta);
+        }
+        keys
+    }
+
+    fn labels(&self) -> Vec<&'static str> {
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.meta {
+            parts.push("Meta");
+        }
+        parts
+    }
+}
+
+/// Physical key identity, independent of the held modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Enter,
+    Tab,
+    Space,
+    Backspace,
+    Escape,
+    CapsLock,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+    Shift,
+    Ctrl,
+    Alt,
+    Meta,
+    Fn,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    NumLock,
+    /// Function key `F1`..=`F24`.
+    F(u8),
+    /// Numeric keypad key: a digit or one of `+ - * /`.
+    Numpad(char),
+    /// Layout key identified by the character it produces unshifted.
+    Char(char),
+    /// Platform scan code the recorder could not name.
+    Raw(u32),
+    /// A key label that does not parse, e.g. the empty label of a key event
+    /// the editor has not filled in yet. Never injected.
+    Unrecognized,
+}
+
+impl KeyCode {
+    pub fn is_modifier(&self) -> bool {
+        matches!(self, KeyCode::Shift | KeyCode::Ctrl | KeyCode::Alt | KeyCode::Meta)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            KeyCode::Enter => "Enter".into(),
+            KeyCode::Tab => "Tab".into(),
+            KeyCode::Space => "Space".into(),
+            KeyCode::Backspace => "Backspace".into(),
+            KeyCode::Escape => "Esc".into(),
+            KeyCode::CapsLock => "CapsLock".into(),
+            KeyCode::Home => "Home".into(),
+            KeyCode::End => "End".into(),
+            KeyCode::PageUp => "PageUp".into(),
+            KeyCode::PageDown => "PageDown".into(),
+            KeyCode::Insert => "Insert".into(),
+            KeyCode::Delete => "Delete".into(),
+            KeyCode::Up => "Up".into(),
+            KeyCode::Down => "Down".into(),
+            KeyCode::Left => "Left".into(),
+            KeyCode::Right => "Right".into(),
+            KeyCode::Shift => "Shift".into(),
+            KeyCode::Ctrl => "Ctrl".into(),
+            KeyCode::Alt => "Alt".into(),
+            KeyCode::Meta => "Meta".into(),
+            KeyCode::Fn => "Fn".into(),
+            KeyCode::PrintScreen => "PrintScreen".into(),
+            KeyCode::ScrollLock => "ScrollLock".into(),
+            KeyCode::Pause => "Pause".into(),
+            KeyCode::NumLock => "NumLock".into(),
+            KeyCode::F(index) => format!("F{index}"),
+            KeyCode::Numpad(symbol) => format!("NumPad{symbol}"),
+            KeyCode::Char(ch) => ch.to_ascii_uppercase().to_string(),
+            KeyCode::Raw(code) => format!("Unknown({code})"),
+            KeyCode::Unrecognized => "Unrecognized".into(),
+        }
+    }
+
+    /// Maps a single produced character back onto the key that types it.
+    pub fn from_char(ch: char) -> KeyCode {
+        match ch {
+            '\r' | '\n' => KeyCode::Enter,
+            '\t' => KeyCode::Tab,
+            ' ' => KeyCode::Space,
+            '\u{8}' => KeyCode::Backspace,
+            '\u{1b}' => KeyCode::Escape,
+            '\u{7f}' => KeyCode::Delete,
+            '\u{1}'..='\u{1a}' => KeyCode::Char((b'a' + ch as u8 - 1) as char),
+            _ => KeyCode::Char(ch.to_ascii_lowercase()),
+        }
+    }
+
+    pub fn parse(segment: &str) -> Result<KeyCode, String> {
+        let mut chars = segment.chars();
+        if let (Some(ch), None) = (chars.next(), chars.next()) {
+            return Ok(KeyCode::from_char(ch));
+        }
+
+        let normalized = segment.trim().to_lowercase();
+        let key = match normalized.as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Space,
+            "backspace" => KeyCode::Backspace,
+            "escape" | "esc" => KeyCode::Escape,
+            "capslock" => KeyCode::CapsLock,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "insert" => KeyCode::Insert,
+            "delete" => KeyCode::Delete,
+            "up" | "uparrow" => KeyCode::Up,
+            "down" | "downarrow" => KeyCode::Down,
+            "left" | "leftarrow" => KeyCode::Left,
+            "right" | "rightarrow" => KeyCode::Right,
+            "shift" => KeyCode::Shift,
+            "ctrl" | "control" => KeyCode::Ctrl,
+            "alt" | "altgr" => KeyCode::Alt,
+            "meta" | "command" | "cmd" | "super" => KeyCode::Meta,
+            "fn" | "function" => KeyCode::Fn,
+            "printscreen" => KeyCode::PrintScreen,
+            "scrolllock" => KeyCode::ScrollLock,
+            "pause" => KeyCode::Pause,
+            "numlock" => KeyCode::NumLock,
+            "backquote" => KeyCode::Char('`'),
+            "minus" => KeyCode::Char('-'),
+            "equal" => KeyCode::Char('='),
+            "leftbracket" => KeyCode::Char('['),
+            "rightbracket" => KeyCode::Char(']'),
+            "semicolon" => KeyCode::Char(';'),
+            "quote" => KeyCode::Char('\''),
+            "backslash" | "intlbackslash" => KeyCode::Char('\\'),
+            "comma" => KeyCode::Char(','),
+            "dot" => KeyCode::Char('.'),
+            "slash" => KeyCode::Char('/'),
+            _ => return KeyCode::parse_compound(&normalized, segment),
+        };
+        Ok(key)
+    }
+
+    fn parse_compound(normalized: &str, original: &str) -> Result<KeyCode, String> {
+        if let Some(index) = normalized.strip_prefix('f').and_then(|rest| rest.parse::<u8>().ok()) {
+            if (1..=24).contains(&index) {
+                return Ok(KeyCode::F(index));
+            }
+        }
+
+        if let Some(stripped) = normalized.strip_prefix("numpad") {
+            let mut chars = stripped.chars();
+            if let (Some(ch), None) = (chars.next(), chars.next()) {
+                if ch.is_ascii_digit() || matches!(ch, '+' | '-' | '*' | '/') {
+                    return Ok(KeyCode::Numpad(ch));
+                }
+            }
+        }
+
+        if let Some(code) = normalized
+            .strip_prefix("unknown(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|raw| raw.parse::<u32>().ok())
+        {
+            return Ok(KeyCode::Raw(code));
+        }
+
+        Err(format!("unknown key `{original}`"))
+    }
+}
+
+/// A key press as recorded: the physical key, the modifiers held alongside it
+/// and, when it differs from the key's own label, the text it produced.
+///
+/// Serialized as two fields of its key event: `key`, the `+`-joined label
+/// used by `macroarc.macros.json` (e.g. `"Ctrl+Shift+A"` or `"Shift+!"`), and
+/// `code`, the label of the physical key, written only when `key` alone would
+/// name a different key (`"1"` for `"Shift+!"`). A `key` that does not parse
+/// is kept verbatim with the code `Unrecognized`, so one bad event does not
+/// make the whole macro unreadable; validation reports it instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "KeyStrokeFields", into = "KeyStrokeFields")]
+pub struct KeyStroke {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+    pub text: Option<String>,
+}
+
+impl KeyStroke {
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: Modifiers::default(),
+            text: None,
+        }
+    }
+
+    /// Keeps a label that does not parse as it is.
+    pub fn unrecognized(label: String) -> Self {
+        Self {
+            code: KeyCode::Unrecognized,
+            modifiers: Modifiers::default(),
+            text: Some(label),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        if self.code == KeyCode::Unrecognized {
+            return self.text.clone().unwrap_or_default();
+        }
+        let mut parts = self.modifiers.labels();
+        let key_label = match &self.text {
+            Some(text) => text.clone(),
+            None => self.code.label(),
+        };
+        parts.push(&key_label);
+        parts.join("+")
+    }
+
+    pub fn parse(label: &str) -> Result<Self, String> {
+        if label.is_empty() {
+            return Err("empty key label".into());
+        }
+
+        // A trailing `+` belongs to the key itself (`Ctrl++`, `NumPad+`).
+        let (prefix, segment) = match label.strip_suffix('+') {
+            Some(head) => match head.rsplit_once('+') {
+                Some((prefix, _)) => (prefix, &label[prefix.len() + 1..]),
+                None => ("", label),
+            },
+            None => match label.rsplit_once('+') {
+                Some((prefix, segment)) => (prefix, segment),
+                None => ("", label),
+            },
+        };
+
+        let mut modifiers = Modifiers::default();
+        if !prefix.is_empty() {
+            for part in prefix.split('+') {
+                if !modifiers.apply(part.trim()) {
+                    return Err(format!("unknown modifier `{}` in key label `{label}`", part.trim()));
+                }
+            }
+        }
+
+        let segment = if segment.trim().is_empty() {
+            segment
+        } else {
+            segment.trim()
+        };
+        if segment.is_empty() {
+            return Err(format!("missing key in key label `{label}`"));
+        }
+
+        let code = KeyCode::parse(segment).map_err(|error| format!("{error} in key label `{label}`"))?;
+        let text = if segment.chars().count() == 1 && code.label() != segment {
+            Some(segment.to_string())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            code,
+            modifiers,
+            text,
+        })
+    }
+}
+
+impl fmt::Display for KeyStroke {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.label())
+    }
+}
+
+/// Serialized form of `KeyStroke`.
+#[derive(Serialize, Deserialize)]
+struct KeyStrokeFields {
+    key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+}
+
+impl From<KeyStrokeFields> for KeyStroke {
+    fn from(fields: KeyStrokeFields) -> Self {
+        let Ok(mut stroke) = KeyStroke::parse(&fields.key) else {
+            return KeyStroke::unrecognized(fields.key);
+        };
+        if let Some(code) = fields.code.and_then(|code| KeyCode::parse(&code).ok()) {
+            if code != stroke.code {
+                stroke.text = Some(stroke.text.take().unwrap_or_else(|| stroke.code.label()));
+                stroke.code = code;
+            }
+        }
+        stroke
+    }
+}
+
+impl From<KeyStroke> for KeyStrokeFields {
+    fn from(stroke: KeyStroke) -> Self {
+        let key = stroke.label();
+        let named = KeyStroke::parse(&key).is_ok_and(|parsed| parsed.code == stroke.code);
+        let code = (stroke.code != KeyCode::Unrecognized && !named).then(|| stroke.code.label());
+        Self { key, code }
+    }
+}
+
+impl JsonSchema for KeyStroke {
+    fn schema_name() -> String {
+        "KeyStroke".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let key = SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Key label: optional `Ctrl`, `Shift`, `Alt` or `Meta` modifiers and a key, joined with `+` (e.g. `Ctrl+Shift+S`, `Enter`, `F5`).".into(),
+                ),
+                examples: vec!["Ctrl+S".into(), "Shift+!".into(), "Enter".into()],
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            format: Some(KEY_STROKE_FORMAT.into()),
+            string: Some(Box::new(StringValidation {
+                min_length: Some(1),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let code = SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "Physical key, when the label names the text it produced instead (e.g. `1` for `Shift+!`).".into(),
+                ),
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(ObjectValidation {
+                properties: [("key".to_owned(), key.into()), ("code".to_owned(), code.into())]
+                    .into_iter()
+                    .collect(),
+                required: ["key".to_owned()].into_iter().collect(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn key_down(value: serde_json::Value) -> KeyStroke {
+        match serde_json::from_value(value).unwrap() {
+            MacroEventKind::KeyDown { key } => key,
+            other => panic!("expected a key-down, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn shifted_symbols_keep_their_physical_key() {
+        let stroke = KeyStroke {
+            code: KeyCode::Char('1'),
+            modifiers: Modifiers { shift: true, ..Modifiers::default() },
+            text: Some("!".into()),
+        };
+        let value = serde_json::to_value(MacroEventKind::KeyDown { key: stroke.clone() }).unwrap();
+
+        assert_eq!(value, json!({ "type": "key-down", "key": "Shift+!", "code": "1" }));
+        assert_eq!(key_down(value), stroke);
+    }
+
+    #[test]
+    fn plain_labels_are_written_without_a_code() {
+        let value = serde_json::to_value(MacroEventKind::KeyUp { key: KeyStroke::parse("Ctrl+S").unwrap() }).unwrap();
+
+        assert_eq!(value, json!({ "type": "key-up", "key": "Ctrl+S" }));
+    }
+
+    #[test]
+    fn accepts_accelerator_modifier_aliases() {
+        let stroke = key_down(json!({ "type": "key-down", "key": "CommandOrControl+Shift+M" }));
+
+        assert_eq!(stroke.code, KeyCode::Char('m'));
+        assert!(stroke.modifiers.shift);
+        assert_eq!(stroke.modifiers.meta, cfg!(target_os = "macos"));
+        assert_eq!(stroke.modifiers.ctrl, !cfg!(target_os = "macos"));
+    }
+
+    #[test]
+    fn keeps_labels_that_do_not_parse() {
+        for label in ["", "Hyper+Q"] {
+            let stroke = key_down(json!({ "type": "key-down", "key": label }));
+
+            assert_eq!(stroke.code, KeyCode::Unrecognized);
+            assert_eq!(
+                serde_json::to_value(MacroEventKind::KeyDown { key: stroke }).unwrap(),
+                json!({ "type": "key-down", "key": label })
+            );
+        }
+    }
+}
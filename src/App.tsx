@@ -7,6 +7,7 @@ import { MacroRecorderPanel } from './components/panels/MacroRecorderPanel'
 import { AutoClickerPanel } from './components/panels/AutoClickerPanel'
 import { MacroLibraryPanel } from './components/panels/MacroLibraryPanel'
 import { ActivityFeedPanel } from './components/panels/ActivityFeedPanel'
+import { SchedulePanel } from './components/panels/SchedulePanel'
 import { InsightGrid } from './components/panels/InsightGrid'
 import { OverlayToggle } from './components/layout/OverlayToggle'
 import { OverlayCanvas } from './components/overlay/OverlayCanvas'
@@ -14,6 +15,7 @@ import { OverlayPanelWindow } from './components/overlay/OverlayPanelWindow'
 import { ThemeProvider } from './hooks/useTheme'
 import { useMacroEngine } from './hooks/useMacroEngine'
 import { useAutoClicker } from './hooks/useAutoClicker'
+import { useMacroSchedules } from './hooks/useMacroSchedules'
 import {
 	DEFAULT_OVERLAY_BUBBLES,
 	useOverlayMode,
@@ -22,7 +24,8 @@ import {
 
 const buildPanelContent = (
 	macro: ReturnType<typeof useMacroEngine>,
-	auto: ReturnType<typeof useAutoClicker>
+	auto: ReturnType<typeof useAutoClicker>,
+	schedules: ReturnType<typeof useMacroSchedules>
 ) => ({
 	hero: (
 		<HeroBanner
@@ -85,6 +88,17 @@ const buildPanelContent = (
 		/>
 	),
 	activity: <ActivityFeedPanel entries={macro.activity} />,
+	schedules: (
+		<SchedulePanel
+			macros={macro.macros}
+			schedules={schedules.schedules}
+			upcoming={schedules.upcoming}
+			waiting={schedules.waiting}
+			error={schedules.error}
+			onSave={schedules.saveSchedule}
+			onDelete={schedules.deleteSchedule}
+		/>
+	),
 	insights: (
 		<InsightGrid macroStats={macro.stats} autoMetrics={auto.metrics} />
 	),
@@ -98,13 +112,15 @@ const buildPanelContent = (
 const Dashboard = () => {
 	const macro = useMacroEngine()
 	const auto = useAutoClicker()
+	const schedules = useMacroSchedules()
 	const overlay = useOverlayMode()
-	const panels = buildPanelContent(macro, auto)
+	const panels = buildPanelContent(macro, auto, schedules)
 	const heroPanel = panels.hero
 	const recorderPanel = panels.recorder
 	const autoPanel = panels.autoclicker
 	const libraryPanel = panels.library
 	const activityPanel = panels.activity
+	const schedulePanel = panels.schedules
 	const insightsPanel = panels.insights
 	const aboutPanelContent = panels.about
 	const overlayContent = panels
@@ -149,6 +165,9 @@ const Dashboard = () => {
 						<div className="order-5 lg:order-4 lg:col-span-1 lg:col-start-3">
 							{activityPanel}
 						</div>
+						<div className="order-6 lg:order-4 lg:col-span-1 lg:col-start-3">
+							{schedulePanel}
+						</div>
 						<div className="order-4 lg:order-5 lg:col-span-2 lg:col-start-1">
 							{insightsPanel}
 						</div>
@@ -198,7 +217,8 @@ const resolveOverlayPanelId = (): OverlayBubbleId | null => {
 const OverlayPanelApp = ({ panelId }: { panelId: OverlayBubbleId }) => {
 	const macro = useMacroEngine()
 	const auto = useAutoClicker()
-	const panels = buildPanelContent(macro, auto)
+	const schedules = useMacroSchedules()
+	const panels = buildPanelContent(macro, auto, schedules)
 	const content = panels[panelId]
 
 	if (!content) {
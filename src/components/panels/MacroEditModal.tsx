@@ -20,7 +20,8 @@ const buttonOptions: { label: string; value: MouseButton }[] = [
 	{ label: 'Left click', value: 'left' },
 	{ label: 'Right click', value: 'right' },
 	{ label: 'Middle click', value: 'middle' },
-	{ label: 'Unknown', value: 'unknown' },
+	{ label: 'Back (X1)', value: 'x1' },
+	{ label: 'Forward (X2)', value: 'x2' },
 ]
 
 type MacroEventType = MacroEvent['kind']['type']
@@ -221,10 +222,11 @@ export const MacroEditModal = ({
 			) {
 				return
 			}
+			// A typed label names its own key, so any recorded code is dropped.
 			updateEventAt(index, {
 				...current,
 				kind: {
-					...current.kind,
+					type: current.kind.type,
 					key: value?.trim() ?? '',
 				},
 			})
@@ -0,0 +1,233 @@
+import { motion } from 'framer-motion'
+import { useState } from 'react'
+import { CalendarClock, Trash2 } from 'lucide-react'
+import {
+	MacroSchedule,
+	MacroSequence,
+	ScheduleOverview,
+	ScheduleTrigger,
+} from '../../utils/macroTypes'
+import { NumericInputField } from '../shared/NumericInputField'
+import { PanelSurface } from '../shared/PanelSurface'
+import { SectionHeader } from '../shared/SectionHeader'
+
+interface SchedulePanelProps {
+	macros: MacroSequence[]
+	schedules: MacroSchedule[]
+	upcoming: ScheduleOverview['upcoming']
+	waiting: string[]
+	error: string | null
+	onSave: (schedule: MacroSchedule) => Promise<boolean>
+	onDelete: (id: string) => Promise<void>
+}
+
+type TriggerType = ScheduleTrigger['type']
+
+const triggerTypes: { type: TriggerType; label: string }[] = [
+	{ type: 'every', label: 'Every' },
+	{ type: 'cron', label: 'Cron' },
+	{ type: 'once', label: 'Once' },
+]
+
+const describeTrigger = (trigger: ScheduleTrigger) => {
+	switch (trigger.type) {
+		case 'every':
+			return `Every ${trigger.minutes} min`
+		case 'cron':
+			return `Cron ${trigger.expression}`
+		case 'once':
+			return `Once at ${new Date(trigger.atMs).toLocaleString()}`
+	}
+}
+
+export const SchedulePanel: React.FC<SchedulePanelProps> = ({
+	macros,
+	schedules,
+	upcoming,
+	waiting,
+	error,
+	onSave,
+	onDelete,
+}) => {
+	const [macroId, setMacroId] = useState('')
+	const [triggerType, setTriggerType] = useState<TriggerType>('every')
+	const [minutes, setMinutes] = useState<number | null>(30)
+	const [expression, setExpression] = useState('0 9 * * mon-fri')
+	const [onceAt, setOnceAt] = useState('')
+	const [whenBusy, setWhenBusy] = useState<MacroSchedule['whenBusy']>('skip')
+
+	const targetMacroId = macroId || macros[0]?.id || ''
+	const macroName = (id: string) =>
+		macros.find((macro) => macro.id === id)?.name ?? 'Missing macro'
+	const nextRun = (id: string) =>
+		upcoming.find((run) => run.scheduleId === id)?.atMs
+
+	const buildTrigger = (): ScheduleTrigger | null => {
+		switch (triggerType) {
+			case 'every':
+				return minutes ? { type: 'every', minutes } : null
+			case 'cron':
+				return expression.trim()
+					? { type: 'cron', expression: expression.trim() }
+					: null
+			case 'once': {
+				const atMs = new Date(onceAt).getTime()
+				return Number.isNaN(atMs) ? null : { type: 'once', atMs }
+			}
+		}
+	}
+
+	const addSchedule = async () => {
+		const trigger = buildTrigger()
+		if (!trigger || !targetMacroId) return
+		await onSave({
+			id: '',
+			macroId: targetMacroId,
+			trigger,
+			enabled: true,
+			whenBusy,
+		})
+	}
+
+	return (
+		<PanelSurface className="flex h-full flex-col gap-6" delay={0.12}>
+			<SectionHeader
+				eyebrow="Automation"
+				title="Schedules"
+				trailing={<CalendarClock className="text-white/50" size={24} />}
+			/>
+
+			<div className="grid gap-3 text-sm text-white/70">
+				<select
+					value={targetMacroId}
+					onChange={(event) => setMacroId(event.target.value)}
+					className="rounded-2xl border border-white/10 bg-black/30 px-4 py-2 text-white"
+					aria-label="Macro to schedule"
+				>
+					{macros.map((macro) => (
+						<option key={macro.id} value={macro.id}>
+							{macro.name}
+						</option>
+					))}
+				</select>
+
+				<div className="flex gap-2">
+					{triggerTypes.map(({ type, label }) => (
+						<motion.button
+							key={type}
+							type="button"
+							whileTap={{ scale: 0.95 }}
+							onClick={() => setTriggerType(type)}
+							className={`rounded-2xl border px-4 py-2 text-xs font-semibold uppercase tracking-[0.2em] ${
+								triggerType === type
+									? 'border-white bg-white/80 text-black'
+									: 'border-white/10 bg-white/5 text-white/60'
+							}`}
+						>
+							{label}
+						</motion.button>
+					))}
+				</div>
+
+				{triggerType === 'every' && (
+					<NumericInputField
+						value={minutes}
+						onChange={setMinutes}
+						suffix="min"
+						aria-label="Interval in minutes"
+					/>
+				)}
+				{triggerType === 'cron' && (
+					<input
+						value={expression}
+						onChange={(event) => setExpression(event.target.value)}
+						placeholder="minute hour day month weekday"
+						className="rounded-2xl border border-white/10 bg-black/30 px-4 py-2 font-mono text-white"
+						aria-label="Cron expression"
+					/>
+				)}
+				{triggerType === 'once' && (
+					<input
+						type="datetime-local"
+						value={onceAt}
+						onChange={(event) => setOnceAt(event.target.value)}
+						className="rounded-2xl border border-white/10 bg-black/30 px-4 py-2 text-white"
+						aria-label="Run at"
+					/>
+				)}
+
+				<label className="flex items-center gap-2 text-xs uppercase tracking-[0.3em] text-white/60">
+					<input
+						type="checkbox"
+						className="h-4 w-4 rounded border-white/30 bg-transparent text-brand-primary"
+						checked={whenBusy === 'queue'}
+						onChange={(event) =>
+							setWhenBusy(event.target.checked ? 'queue' : 'skip')
+						}
+					/>
+					Wait if something is playing
+				</label>
+
+				<motion.button
+					type="button"
+					whileTap={{ scale: 0.97 }}
+					disabled={!targetMacroId}
+					onClick={addSchedule}
+					className="rounded-2xl bg-brand-primary/70 px-4 py-2 font-semibold text-white disabled:opacity-40"
+				>
+					Add schedule
+				</motion.button>
+
+				{error && <p className="text-xs text-amber-300">{error}</p>}
+			</div>
+
+			<div className="space-y-3">
+				{schedules.map((schedule) => {
+					const at = nextRun(schedule.id)
+					return (
+						<div
+							key={schedule.id}
+							className="flex items-center justify-between gap-3 rounded-2xl border border-white/10 bg-black/30 px-4 py-3"
+						>
+							<div>
+								<p className="text-sm font-semibold text-white">
+									{macroName(schedule.macroId)}
+								</p>
+								<p className="text-xs text-white/60">
+									{describeTrigger(schedule.trigger)}
+									{waiting.includes(schedule.id)
+										? ' · waiting'
+										: at
+										? ` · next ${new Date(at).toLocaleString()}`
+										: ''}
+								</p>
+							</div>
+							<div className="flex items-center gap-3">
+								<input
+									type="checkbox"
+									className="h-4 w-4 rounded border-white/30 bg-transparent text-brand-primary"
+									checked={schedule.enabled}
+									onChange={(event) =>
+										void onSave({
+											...schedule,
+											enabled: event.target.checked,
+										})
+									}
+									aria-label="Schedule enabled"
+								/>
+								<button
+									type="button"
+									onClick={() => void onDelete(schedule.id)}
+									className="text-white/50 hover:text-white"
+									aria-label="Delete schedule"
+								>
+									<Trash2 size={16} />
+								</button>
+							</div>
+						</div>
+					)
+				})}
+			</div>
+		</PanelSurface>
+	)
+}
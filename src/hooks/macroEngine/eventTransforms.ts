@@ -1,18 +1,18 @@
-import {
-	MacroEvent,
-	MacroSequence,
-	MouseButton,
-} from '../../utils/macroTypes'
-import {
-	SCROLL_DELTA_MODE_NATIVE,
-	VALID_MOUSE_BUTTONS,
-} from './constants'
+import { MacroEvent, MouseButton } from '../../utils/macroTypes'
+import { VALID_MOUSE_BUTTONS } from './constants'
 import { ensureNumber } from './helpers'
 
-const coerceButton = (button: MouseButton): MouseButton =>
-	VALID_MOUSE_BUTTONS.includes(button) ? button : 'unknown'
+const checkButton = (button: MouseButton, index: number): MouseButton => {
+	if (!VALID_MOUSE_BUTTONS.includes(button)) {
+		throw new Error(`Event ${index} has the unknown mouse button "${button}"`)
+	}
+	return button
+}
 
-export const sanitizeMacroEvent = (event: MacroEvent): MacroEvent => {
+export const sanitizeMacroEvent = (
+	event: MacroEvent,
+	index = 0
+): MacroEvent => {
 	const offset = Math.max(0, Math.round(ensureNumber(event.offsetMs, 0)))
 	switch (event.kind.type) {
 		case 'mouse-move':
@@ -32,19 +32,22 @@ export const sanitizeMacroEvent = (event: MacroEvent): MacroEvent => {
 				offsetMs: offset,
 				kind: {
 					type: event.kind.type,
-					button: coerceButton(event.kind.button),
+					button: checkButton(event.kind.button, index),
 				},
 			}
 		case 'key-down':
 		case 'key-up': {
 			const key =
 				typeof event.kind.key === 'string' ? event.kind.key.trim() : ''
+			const code =
+				typeof event.kind.code === 'string' ? event.kind.code : undefined
 			return {
 				...event,
 				offsetMs: offset,
 				kind: {
 					type: event.kind.type,
 					key,
+					...(code ? { code } : {}),
 				},
 			}
 		}
@@ -82,24 +85,3 @@ const invertScroll = (value: MacroEvent): MacroEvent => {
 
 export const normalizeScrollEvents = (events: MacroEvent[]) =>
 	events.map((event) => (event.kind.type === 'scroll' ? invertScroll(event) : event))
-
-export const shouldNormalizeMacroScrolls = (
-	macro: MacroSequence | null | undefined
-) => {
-	if (!macro || macro.scrollDeltaMode === SCROLL_DELTA_MODE_NATIVE) {
-		return false
-	}
-	if (!Array.isArray(macro.tags) || !macro.tags.includes('capture')) {
-		return false
-	}
-	return macro.events.some((event) => event.kind.type === 'scroll')
-}
-
-export const normalizeMacroScrolls = (macro: MacroSequence) =>
-	shouldNormalizeMacroScrolls(macro)
-		? {
-				...macro,
-				events: normalizeScrollEvents(macro.events),
-				scrollDeltaMode: SCROLL_DELTA_MODE_NATIVE,
-		  }
-		: macro
@@ -1,3 +1,4 @@
+import { invoke } from '@tauri-apps/api/core'
 import { readTextFile, writeTextFile } from '@tauri-apps/plugin-fs'
 import { MacroSequence } from '../../utils/macroTypes'
 import { getAppLocalDataPath } from '../../utils/storage'
@@ -7,14 +8,12 @@ import {
 	LEGACY_QUEUE_HOTKEY_FILENAME,
 	LEGACY_RECORDER_HOTKEY_FILENAME,
 	MACRO_RECORD_SHORTCUT,
-	STORAGE_FILENAME,
 } from './constants'
 import {
 	clampLoopDelay,
 	clampPlaybackSpeed,
 	isMissingFileError,
 } from './helpers'
-import { normalizeMacroScrolls } from './eventTransforms'
 import { DEFAULT_LOOP_DELAY_MS } from './constants'
 
 type HotkeySettingsFile = {
@@ -38,17 +37,6 @@ const normalizeHotkeyValue = (value: unknown): string | null | undefined => {
 	return undefined
 }
 
-export const parseStoredMacros = (raw: string | null): MacroSequence[] | null => {
-	if (!raw) return null
-	try {
-		const parsed = JSON.parse(raw) as MacroSequence[]
-		return Array.isArray(parsed) ? parsed : null
-	} catch (error) {
-		console.warn('macro cache invalid', error)
-		return null
-	}
-}
-
 const parseHotkeysFile = (raw: string | null): HotkeySettingsFile | null => {
 	if (!raw) return null
 	try {
@@ -86,39 +74,43 @@ const readLegacyHotkeyFile = async (
 }
 
 export const hydrateStoredMacros = (macros: MacroSequence[]) =>
-	macros.map((macro) => {
-		const aligned = normalizeMacroScrolls(macro)
-		return {
-			...aligned,
-			hotkey: aligned.hotkey ?? null,
-			loopEnabled: Boolean(aligned.loopEnabled),
-			loopDelayMs: clampLoopDelay(
-				aligned.loopDelayMs ?? DEFAULT_LOOP_DELAY_MS,
-				DEFAULT_LOOP_DELAY_MS
-			),
-			playbackSpeed: clampPlaybackSpeed(aligned.playbackSpeed),
-		}
-	})
+	macros.map((macro) => ({
+		...macro,
+		hotkey: macro.hotkey ?? null,
+		loopEnabled: Boolean(macro.loopEnabled),
+		loopDelayMs: clampLoopDelay(
+			macro.loopDelayMs ?? DEFAULT_LOOP_DELAY_MS,
+			DEFAULT_LOOP_DELAY_MS
+		),
+		playbackSpeed: clampPlaybackSpeed(macro.playbackSpeed),
+	}))
 
 export const loadStoredMacros = async (): Promise<MacroSequence[] | null> => {
 	try {
-		const path = await getAppLocalDataPath(STORAGE_FILENAME)
-		const raw = await readTextFile(path)
-		return parseStoredMacros(raw)
+		return await invoke<MacroSequence[]>('list_macros')
 	} catch (error) {
-		if (!isMissingFileError(error)) {
-			console.warn('macro file read failed', error)
-		}
+		console.warn('macro library load failed', error)
+		return null
+	}
+}
+
+export const loadMacroLibraryError = async (): Promise<string | null> => {
+	try {
+		return await invoke<string | null>('macro_library_error')
+	} catch (error) {
+		console.warn('macro library status failed', error)
 		return null
 	}
 }
 
-export const persistStoredMacros = async (macros: MacroSequence[]) => {
+export const persistStoredMacros = async (
+	macros: MacroSequence[],
+	source: string
+) => {
 	try {
-		const path = await getAppLocalDataPath(STORAGE_FILENAME)
-		await writeTextFile(path, JSON.stringify(macros))
+		await invoke('replace_macros', { macros, source })
 	} catch (error) {
-		console.warn('macro file write failed', error)
+		console.warn('macro library write failed', error)
 	}
 }
 
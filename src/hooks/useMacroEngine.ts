@@ -21,10 +21,9 @@ import {
 	DEFAULT_QUEUE_HOTKEY,
 	DEFAULT_QUEUE_LOOP_DELAY_MS,
 	MACRO_RECORD_SHORTCUT,
-	MACRO_SYNC_CHANNEL,
+	MACRO_LIBRARY_CHANNEL,
 	QUEUE_HOTKEY_CHANNEL,
 	QUEUE_STATE_CHANNEL,
-	QUEUE_STATE_REQUEST_CHANNEL,
 	RECENT_EVENT_LIMIT,
 	SCROLL_DELTA_MODE_NATIVE,
 } from './macroEngine/constants'
@@ -45,6 +44,7 @@ import {
 import {
 	hydrateStoredMacros,
 	loadHotkeySettings,
+	loadMacroLibraryError,
 	loadStoredMacros,
 	persistHotkeySettings,
 	persistStoredMacros,
@@ -99,17 +99,21 @@ type CaptureBroadcastPayload = {
 	eventCount?: number
 }
 
-type MacroSyncPayload = {
-	source?: string
+type MacroLibraryPayload = {
+	source?: string | null
+	reason?: string
+	macroId?: string | null
 	macros?: MacroSequence[]
 }
 
-type QueueStateBroadcastPayload = {
-	source?: string
-	queue?: string[]
-	loopEnabled?: boolean
-	loopDelayMs?: number
-	running?: boolean
+type NativeQueueState = {
+	queue: string[]
+	paddingMs: number
+	loopEnabled: boolean
+	loopDelayMs: number
+	running: boolean
+	currentIndex: number | null
+	cycle: number
 }
 
 type QueueHotkeyBroadcastPayload = {
@@ -117,10 +121,6 @@ type QueueHotkeyBroadcastPayload = {
 	hotkey?: string | null
 }
 
-type QueueStateRequestPayload = {
-	source?: string
-}
-
 const pushEntry = (
 	updater: Dispatch<SetStateAction<ActivityEntry[]>>,
 	entry: ActivityEntry
@@ -214,12 +214,6 @@ export const useMacroEngine = () => {
 		return isOverlayPanelWindow()
 	}, [nativeRuntime, windowLabel])
 
-	useEffect(() => {
-		if (overlayPanelRuntime === false) {
-			queueBroadcastReadyRef.current = true
-		}
-	}, [overlayPanelRuntime])
-
 	const shouldAttachRealtimeStreams =
 		overlayPanelRuntime === false || documentVisible
 	const macroHotkeyBindings = useRef<Map<string, string>>(new Map())
@@ -232,34 +226,17 @@ export const useMacroEngine = () => {
 	const recordingOriginRef = useRef<'hotkey' | 'ui' | null>(null)
 	const recorderActiveRef = useRef(false)
 	const macrosRef = useRef<MacroSequence[]>([])
-	const macrosSyncSuppressedRef = useRef(false)
 	const macroLoopTimers = useRef<Map<string, ReturnType<typeof setTimeout>>>(
 		new Map()
 	)
-	const queueLoopTimerRef = useRef<ReturnType<typeof setTimeout> | null>(
-		null
-	)
-	const queueLoopEnabledRef = useRef(queueLoopEnabled)
-	const queueLoopDelayRef = useRef(queueLoopDelayMs)
 	const activeLoopMacrosRef = useRef<Set<string>>(new Set())
 	const queueRunningRef = useRef(queueRunning)
-	const queuePlaybackAbortRef = useRef<{ cancelled: boolean }>({
-		cancelled: false,
-	})
+	const queuePaddingRef = useRef(0)
 	const queueHotkeyHeldRef = useRef(false)
-	const queueRef = useRef<string[]>([])
 	const queueHotkeyResetTimerRef = useRef<ReturnType<
 		typeof setTimeout
 	> | null>(null)
-	const queueStateSyncSuppressedRef = useRef(false)
 	const queueHotkeySyncSuppressedRef = useRef(false)
-	const queueBroadcastReadyRef = useRef(!nativeRuntime)
-	const lastQueueSnapshotRef = useRef<{
-		queue: string[]
-		loopEnabled: boolean
-		loopDelayMs: number
-		running: boolean
-	} | null>(null)
 	const stopInFlightRef = useRef(false)
 	const instanceIdRef = useRef(nanoid())
 	const playbackResolversRef = useRef<
@@ -274,12 +251,6 @@ export const useMacroEngine = () => {
 			macroLoopTimers.current.delete(id)
 		}
 	}, [])
-	const clearQueueLoopTimer = useCallback(() => {
-		if (queueLoopTimerRef.current) {
-			clearTimeout(queueLoopTimerRef.current)
-			queueLoopTimerRef.current = null
-		}
-	}, [])
 	const requestStopPlayback = useCallback(async () => {
 		currentPlaybackContextRef.current = null
 		currentPlaybackMacroRef.current = null
@@ -352,58 +323,6 @@ export const useMacroEngine = () => {
 		queueRunningRef.current = queueRunning
 	}, [queueRunning])
 
-	useEffect(() => {
-		queueRef.current = queue
-	}, [queue])
-
-	useEffect(() => {
-		queueLoopEnabledRef.current = queueLoopEnabled
-		if (!queueLoopEnabled) {
-			clearQueueLoopTimer()
-		}
-	}, [queueLoopEnabled, clearQueueLoopTimer])
-
-	useEffect(() => {
-		queueLoopDelayRef.current = queueLoopDelayMs
-	}, [queueLoopDelayMs])
-
-	const emitMacroSync = useCallback(
-		async (snapshot: MacroSequence[]) => {
-			if (!nativeRuntime) {
-				return
-			}
-			try {
-				await emit<MacroSyncPayload>(MACRO_SYNC_CHANNEL, {
-					source: instanceIdRef.current,
-					macros: snapshot,
-				})
-			} catch (error) {
-				console.warn('macro sync broadcast failed', error)
-			}
-		},
-		[nativeRuntime]
-	)
-
-	const emitQueueState = useCallback(
-		async (snapshot: QueueStateBroadcastPayload) => {
-			if (!nativeRuntime) {
-				return
-			}
-			try {
-				await emit<QueueStateBroadcastPayload>(QUEUE_STATE_CHANNEL, {
-					source: instanceIdRef.current,
-					queue: snapshot.queue ?? [],
-					loopEnabled: snapshot.loopEnabled,
-					loopDelayMs: snapshot.loopDelayMs,
-					running: snapshot.running,
-				})
-			} catch (error) {
-				console.warn('queue state broadcast failed', error)
-			}
-		},
-		[nativeRuntime]
-	)
-
 	const emitQueueHotkey = useCallback(
 		async (hotkey: string | null) => {
 			if (!nativeRuntime) {
@@ -421,51 +340,6 @@ export const useMacroEngine = () => {
 		[nativeRuntime]
 	)
 
-	useEffect(() => {
-		if (!nativeRuntime) {
-			return
-		}
-		const snapshot = {
-			queue: [...queue],
-			loopEnabled: queueLoopEnabled,
-			loopDelayMs: queueLoopDelayMs,
-			running: queueRunning,
-		}
-		const previous = lastQueueSnapshotRef.current
-		if (queueStateSyncSuppressedRef.current) {
-			queueStateSyncSuppressedRef.current = false
-			lastQueueSnapshotRef.current = snapshot
-			return
-		}
-		if (!queueBroadcastReadyRef.current) {
-			lastQueueSnapshotRef.current = snapshot
-			return
-		}
-		let matchesPrevious = false
-		if (previous) {
-			matchesPrevious =
-				previous.queue.length === snapshot.queue.length &&
-				previous.queue.every(
-					(id, index) => id === snapshot.queue[index]
-				) &&
-				previous.loopEnabled === snapshot.loopEnabled &&
-				previous.loopDelayMs === snapshot.loopDelayMs &&
-				previous.running === snapshot.running
-		}
-		if (matchesPrevious) {
-			return
-		}
-		lastQueueSnapshotRef.current = snapshot
-		void emitQueueState(snapshot)
-	}, [
-		emitQueueState,
-		nativeRuntime,
-		queue,
-		queueLoopDelayMs,
-		queueLoopEnabled,
-		queueRunning,
-	])
-
 	useEffect(() => {
 		if (!nativeRuntime || !queueHotkeyHydrated) {
 			return
@@ -477,35 +351,49 @@ export const useMacroEngine = () => {
 		void emitQueueHotkey(queueHotkey)
 	}, [emitQueueHotkey, nativeRuntime, queueHotkey, queueHotkeyHydrated])
 
-	const applyMacrosUpdate = useCallback(
-		(updater: (prev: MacroSequence[]) => MacroSequence[]) => {
-			setMacros((prev) => {
-				const next = updater(prev)
-				if (!macrosSyncSuppressedRef.current) {
-					void emitMacroSync(next)
-				}
-				return next
-			})
+	// The backend owns the queue and broadcasts every change to all windows.
+	const applyQueueState = useCallback((state: NativeQueueState) => {
+		queuePaddingRef.current = state.paddingMs
+		setQueue(state.queue)
+		setQueueLoopEnabled(state.loopEnabled)
+		setQueueLoopDelayMs(
+			clampLoopDelay(state.loopDelayMs, DEFAULT_QUEUE_LOOP_DELAY_MS)
+		)
+		setQueueRunning(state.running)
+	}, [])
+
+	const invokeQueue = useCallback(
+		async (command: string, args?: Record<string, unknown>) => {
+			try {
+				applyQueueState(await invoke<NativeQueueState>(command, args))
+				return true
+			} catch (error) {
+				pushEntry(setActivity, {
+					id: nanoid(),
+					label: 'Queue update failed',
+					tone: 'warning',
+					meta: String(error),
+					timestamp: Date.now(),
+				})
+				return false
+			}
 		},
-		[emitMacroSync]
+		[applyQueueState]
 	)
 
-	const replaceMacros = useCallback(
-		(next: MacroSequence[], options?: { suppressSync?: boolean }) => {
-			const suppress = Boolean(options?.suppressSync)
-			if (suppress) {
-				macrosSyncSuppressedRef.current = true
-			}
-			setMacros(next)
-			if (suppress) {
-				macrosSyncSuppressedRef.current = false
-			} else if (!macrosSyncSuppressedRef.current) {
-				void emitMacroSync(next)
-			}
+	// Other windows learn about changes from the backend once the library
+	// store has written them (see the MACRO_LIBRARY_CHANNEL listener below).
+	const applyMacrosUpdate = useCallback(
+		(updater: (prev: MacroSequence[]) => MacroSequence[]) => {
+			setMacros(updater)
 		},
-		[emitMacroSync]
+		[]
 	)
 
+	const replaceMacros = useCallback((next: MacroSequence[]) => {
+		setMacros(next)
+	}, [])
+
 	useEffect(() => {
 		if (!nativeRuntime) {
 			return
@@ -518,7 +406,7 @@ export const useMacroEngine = () => {
 				const stored = await loadStoredMacros()
 				if (!cancelled && stored) {
 					const normalized = hydrateStoredMacros(stored)
-					replaceMacros(normalized, { suppressSync: true })
+					replaceMacros(normalized)
 					setSelectedMacroId((current) => {
 						if (
 							current &&
@@ -529,6 +417,16 @@ export const useMacroEngine = () => {
 						return normalized[0]?.id ?? null
 					})
 				}
+				const libraryError = await loadMacroLibraryError()
+				if (!cancelled && libraryError) {
+					pushEntry(setActivity, {
+						id: nanoid(),
+						label: 'Macro library unavailable, changes will not be saved',
+						tone: 'warning',
+						meta: libraryError,
+						timestamp: Date.now(),
+					})
+				}
 			} finally {
 				if (!cancelled) {
 					setMacrosHydrated(true)
@@ -580,7 +478,7 @@ export const useMacroEngine = () => {
 	useEffect(() => {
 		if (!nativeRuntime || !macrosHydrated) return
 
-		void persistStoredMacros(macros)
+		void persistStoredMacros(macros, instanceIdRef.current)
 	}, [macros, nativeRuntime, macrosHydrated])
 
 	useEffect(() => {
@@ -726,8 +624,8 @@ export const useMacroEngine = () => {
 		}
 		let unlistenSync: (() => void) | undefined
 		;(async () => {
-			unlistenSync = await listen<MacroSyncPayload>(
-				MACRO_SYNC_CHANNEL,
+			unlistenSync = await listen<MacroLibraryPayload>(
+				MACRO_LIBRARY_CHANNEL,
 				({ payload }) => {
 					if (
 						!payload ||
@@ -736,7 +634,7 @@ export const useMacroEngine = () => {
 					) {
 						return
 					}
-					replaceMacros(payload.macros, { suppressSync: true })
+					replaceMacros(payload.macros)
 				}
 			)
 		})()
@@ -752,36 +650,13 @@ export const useMacroEngine = () => {
 		}
 		let unlistenQueueState: (() => void) | undefined
 		let unlistenQueueHotkey: (() => void) | undefined
-		let unlistenQueueRequest: (() => void) | undefined
 		;(async () => {
 			try {
-				unlistenQueueState = await listen<QueueStateBroadcastPayload>(
+				unlistenQueueState = await listen<NativeQueueState>(
 					QUEUE_STATE_CHANNEL,
 					({ payload }) => {
-						if (
-							!payload ||
-							payload.source === instanceIdRef.current
-						) {
-							return
-						}
-						queueStateSyncSuppressedRef.current = true
-						queueBroadcastReadyRef.current = true
-						if (Array.isArray(payload.queue)) {
-							setQueue([...payload.queue])
-						}
-						if (typeof payload.loopEnabled === 'boolean') {
-							setQueueLoopEnabled(payload.loopEnabled)
-						}
-						if (typeof payload.loopDelayMs === 'number') {
-							setQueueLoopDelayMs(
-								clampLoopDelay(
-									payload.loopDelayMs,
-									DEFAULT_QUEUE_LOOP_DELAY_MS
-								)
-							)
-						}
-						if (typeof payload.running === 'boolean') {
-							setQueueRunning(payload.running)
+						if (payload) {
+							applyQueueState(payload)
 						}
 					}
 				)
@@ -802,23 +677,8 @@ export const useMacroEngine = () => {
 						}
 					)
 
-				unlistenQueueRequest = await listen<QueueStateRequestPayload>(
-					QUEUE_STATE_REQUEST_CHANNEL,
-					({ payload }) => {
-						if (
-							!payload ||
-							payload.source === instanceIdRef.current ||
-							!queueBroadcastReadyRef.current
-						) {
-							return
-						}
-						void emitQueueState({
-							queue: [...queueRef.current],
-							loopEnabled: queueLoopEnabledRef.current,
-							loopDelayMs: queueLoopDelayRef.current,
-							running: queueRunningRef.current,
-						})
-					}
+				applyQueueState(
+					await invoke<NativeQueueState>('get_queue_state')
 				)
 			} catch (error) {
 				console.warn('queue sync listener failed', error)
@@ -828,23 +688,8 @@ export const useMacroEngine = () => {
 		return () => {
 			unlistenQueueState?.()
 			unlistenQueueHotkey?.()
-			unlistenQueueRequest?.()
-		}
-	}, [emitQueueState, nativeRuntime, shouldAttachRealtimeStreams])
-
-	useEffect(() => {
-		if (!nativeRuntime || !shouldAttachRealtimeStreams) {
-			return
 		}
-		if (queueBroadcastReadyRef.current) {
-			return
-		}
-		void emit<QueueStateRequestPayload>(QUEUE_STATE_REQUEST_CHANNEL, {
-			source: instanceIdRef.current,
-		}).catch((error) => {
-			console.warn('queue state request failed', error)
-		})
-	}, [nativeRuntime, shouldAttachRealtimeStreams])
+	}, [applyQueueState, nativeRuntime, shouldAttachRealtimeStreams])
 
 	const startRecording = useCallback(
 		async (name?: string) => {
@@ -1489,23 +1334,15 @@ export const useMacroEngine = () => {
 		}
 	}, [captureName, macros, selectedMacroId])
 
-	const applyQueueState = useCallback(
-		(next: string[] | ((prev: string[]) => string[])) => {
-			queueBroadcastReadyRef.current = true
-			setQueue((prev) =>
-				typeof next === 'function'
-					? (next as (value: string[]) => string[])(prev)
-					: next
-			)
-		},
-		[]
-	)
-
 	const queueMacro = useCallback(
-		(id: string) => {
+		async (id: string) => {
 			const macro = macros.find((item) => item.id === id)
 			if (!macro) return
-			applyQueueState((current) => [...current, id])
+			if (nativeRuntime) {
+				if (!(await invokeQueue('enqueue_macro', { id }))) return
+			} else {
+				setQueue((current) => [...current, id])
+			}
 			pushEntry(setActivity, {
 				id: nanoid(),
 				label: `${macro.name} queued`,
@@ -1513,7 +1350,7 @@ export const useMacroEngine = () => {
 				timestamp: Date.now(),
 			})
 		},
-		[applyQueueState, macros]
+		[invokeQueue, macros, nativeRuntime]
 	)
 
 	const queuedMacros = useMemo(
@@ -1524,27 +1361,50 @@ export const useMacroEngine = () => {
 		[queue, macros]
 	)
 
+	const updateQueueLoopSettings = useCallback(
+		(settings: { enabled?: boolean; delayMs?: number }) => {
+			const delayMs =
+				typeof settings.delayMs === 'number' &&
+				!Number.isNaN(settings.delayMs)
+					? clampLoopDelay(
+							settings.delayMs,
+							DEFAULT_QUEUE_LOOP_DELAY_MS
+					  )
+					: queueLoopDelayMs
+			const loopEnabled = settings.enabled ?? queueLoopEnabled
+			if (!nativeRuntime) {
+				setQueueLoopDelayMs(delayMs)
+				setQueueLoopEnabled(loopEnabled)
+				return
+			}
+			void invokeQueue('set_queue_settings', {
+				settings: {
+					paddingMs: queuePaddingRef.current,
+					loopEnabled,
+					loopDelayMs: delayMs,
+				},
+			})
+		},
+		[invokeQueue, nativeRuntime, queueLoopDelayMs, queueLoopEnabled]
+	)
+
 	const stopQueuePlayback = useCallback(
-		(options?: {
+		async (options?: {
 			disableLoop?: boolean
 			silent?: boolean
 			reason?: string
 		}) => {
-			const isRunning =
-				queueRunningRef.current || Boolean(queueLoopTimerRef.current)
-			if (!isRunning) {
-				if (options?.disableLoop) {
-					setQueueLoopEnabled(false)
-				}
+			if (options?.disableLoop) {
+				updateQueueLoopSettings({ enabled: false })
+			}
+			if (!nativeRuntime || !queueRunningRef.current) {
 				return
 			}
-			queuePlaybackAbortRef.current.cancelled = true
-			clearQueueLoopTimer()
-			setQueueRunning(false)
-			if (options?.disableLoop) {
-				setQueueLoopEnabled(false)
+			try {
+				await invoke('stop_queue')
+			} catch (error) {
+				console.warn('queue stop failed', error)
 			}
-			void requestStopPlayback()
 			if (!options?.silent) {
 				pushEntry(setActivity, {
 					id: nanoid(),
@@ -1555,95 +1415,46 @@ export const useMacroEngine = () => {
 				})
 			}
 		},
-		[
-			clearQueueLoopTimer,
-			requestStopPlayback,
-			setQueueLoopEnabled,
-			setQueueRunning,
-		]
-	)
-
-	const runQueueSequence = useCallback(
-		async (macroIds: string[], controller: { cancelled: boolean }) => {
-			for (const id of macroIds) {
-				if (controller.cancelled) {
-					break
-				}
-				await playMacro(id, { suppressLoop: true })
-				if (controller.cancelled) {
-					break
-				}
-			}
-		},
-		[playMacro]
-	)
-
-	const scheduleQueueLoop = useCallback(
-		(macroIds: string[]) => {
-			clearQueueLoopTimer()
-			if (!queueLoopEnabledRef.current || !macroIds.length) {
-				return
-			}
-
-			const delay = clampLoopDelay(
-				queueLoopDelayRef.current,
-				DEFAULT_QUEUE_LOOP_DELAY_MS
-			)
-			queueLoopTimerRef.current = setTimeout(async () => {
-				queueLoopTimerRef.current = null
-				if (!queueLoopEnabledRef.current) {
-					return
-				}
-				applyQueueState(macroIds)
-				pushEntry(setActivity, {
-					id: nanoid(),
-					label: 'Repeating queue',
-					tone: 'info',
-					meta: `${macroIds.length} items`,
-					timestamp: Date.now(),
-				})
-				const controller = { cancelled: false }
-				queuePlaybackAbortRef.current = controller
-				setQueueRunning(true)
-				try {
-					await runQueueSequence(macroIds, controller)
-				} finally {
-					setQueueRunning(false)
-				}
-				if (!controller.cancelled && queueLoopEnabledRef.current) {
-					scheduleQueueLoop(macroIds)
-				}
-			}, delay)
-		},
-		[
-			applyQueueState,
-			clearQueueLoopTimer,
-			runQueueSequence,
-			setQueueRunning,
-		]
+		[nativeRuntime, updateQueueLoopSettings]
 	)
 
-	const clearQueue = useCallback(() => {
+	const clearQueue = useCallback(async () => {
 		if (!queue.length && !queueRunningRef.current) return
-		applyQueueState([])
-		stopQueuePlayback({
+		await stopQueuePlayback({
 			disableLoop: true,
 			silent: true,
 			reason: 'Queue cleared',
 		})
+		if (nativeRuntime) {
+			if (!(await invokeQueue('clear_queue'))) return
+		} else {
+			setQueue([])
+		}
 		pushEntry(setActivity, {
 			id: nanoid(),
 			label: 'Queue cleared',
 			tone: 'warning',
 			timestamp: Date.now(),
 		})
-	}, [applyQueueState, queue, stopQueuePlayback])
+	}, [invokeQueue, nativeRuntime, queue, stopQueuePlayback])
 
 	const updateMacroEvents = useCallback(
 		(id: string, events: MacroEvent[]) => {
 			const baseline = macrosRef.current.find((macro) => macro.id === id)
 			if (!baseline) return
-			const sanitized = sanitizeMacroEventList(events)
+			let sanitized: MacroEvent[]
+			try {
+				sanitized = sanitizeMacroEventList(events)
+			} catch (error) {
+				pushEntry(setActivity, {
+					id: nanoid(),
+					label: `${baseline.name} not updated`,
+					tone: 'warning',
+					meta: error instanceof Error ? error.message : String(error),
+					timestamp: Date.now(),
+				})
+				return
+			}
 			const wasLooping = activeLoopMacrosRef.current.has(id)
 			stopMacroLoop(id, { silent: true })
 			applyMacrosUpdate((prev) =>
@@ -1736,33 +1547,28 @@ export const useMacroEngine = () => {
 	)
 
 	const playQueuedMacros = useCallback(async () => {
-		if (!queuedMacros.length || queueRunningRef.current) return
-		const itemsToPlay = [...queuedMacros]
-		const macroIds = itemsToPlay.map((macro) => macro.id)
-		if (queueLoopEnabledRef.current) {
-			applyQueueState(macroIds)
-		} else {
-			applyQueueState([])
+		if (!nativeRuntime || !queuedMacros.length || queueRunningRef.current)
+			return
+		try {
+			await invoke('start_queue')
+		} catch (error) {
+			pushEntry(setActivity, {
+				id: nanoid(),
+				label: 'Queue playback failed',
+				tone: 'warning',
+				meta: String(error),
+				timestamp: Date.now(),
+			})
+			return
 		}
 		pushEntry(setActivity, {
 			id: nanoid(),
 			label: 'Playing queued macros',
 			tone: 'info',
-			meta: `${itemsToPlay.length} items`,
+			meta: `${queuedMacros.length} items`,
 			timestamp: Date.now(),
 		})
-		const controller = { cancelled: false }
-		queuePlaybackAbortRef.current = controller
-		setQueueRunning(true)
-		try {
-			await runQueueSequence(macroIds, controller)
-		} finally {
-			setQueueRunning(false)
-		}
-		if (queueLoopEnabledRef.current && !controller.cancelled) {
-			scheduleQueueLoop(macroIds)
-		}
-	}, [applyQueueState, queuedMacros, runQueueSequence, scheduleQueueLoop])
+	}, [nativeRuntime, queuedMacros])
 
 	useEffect(() => {
 		if (!nativeRuntime || overlayPanelRuntime !== false) return
@@ -1809,8 +1615,8 @@ export const useMacroEngine = () => {
 					}
 					queueHotkeyHeldRef.current = true
 					scheduleReleaseFallback()
-					if (queueRunningRef.current || queueLoopTimerRef.current) {
-						stopQueuePlayback({
+					if (queueRunningRef.current) {
+						await stopQueuePlayback({
 							disableLoop: true,
 							reason: 'Shortcut',
 						})
@@ -1840,26 +1646,6 @@ export const useMacroEngine = () => {
 		stopQueuePlayback,
 	])
 
-	const updateQueueLoopSettings = useCallback(
-		(settings: { enabled?: boolean; delayMs?: number }) => {
-			if (
-				typeof settings.delayMs === 'number' &&
-				!Number.isNaN(settings.delayMs)
-			) {
-				setQueueLoopDelayMs(
-					clampLoopDelay(
-						settings.delayMs,
-						DEFAULT_QUEUE_LOOP_DELAY_MS
-					)
-				)
-			}
-			if (typeof settings.enabled === 'boolean') {
-				setQueueLoopEnabled(settings.enabled)
-			}
-		},
-		[]
-	)
-
 	const updateQueueHotkey = useCallback((value: string | null) => {
 		const next = value?.trim() ? value.trim() : null
 		setQueueHotkey(next)
@@ -1929,11 +1715,9 @@ export const useMacroEngine = () => {
 			Array.from(activeLoopMacrosRef.current).forEach((id) => {
 				stopMacroLoop(id, { silent: true })
 			})
-			queuePlaybackAbortRef.current.cancelled = true
-			clearQueueLoopTimer()
 			void requestStopPlayback()
 		}
-	}, [clearQueueLoopTimer, requestStopPlayback, stopMacroLoop])
+	}, [requestStopPlayback, stopMacroLoop])
 
 	return {
 		macros,
@@ -0,0 +1,98 @@
+import { useCallback, useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
+import { MacroSchedule, ScheduleOverview } from '../utils/macroTypes'
+import { isTauri } from '../utils/bridge'
+
+const SCHEDULES_CHANNEL = 'macro://schedules'
+const SCHEDULE_RUN_CHANNEL = 'macro://schedule-run'
+
+type ScheduledRunPayload = {
+	scheduleId: string
+	macroId: string
+	action: 'started' | 'skipped' | 'queued' | 'missed' | 'failed'
+	detail?: string
+}
+
+const emptyOverview: ScheduleOverview = {
+	schedules: [],
+	upcoming: [],
+	waiting: [],
+}
+
+// Schedules live in the backend, which plays them and broadcasts every
+// change on `macro://schedules`.
+export const useMacroSchedules = () => {
+	const nativeRuntime = isTauri()
+	const [overview, setOverview] = useState<ScheduleOverview>(emptyOverview)
+	const [lastRun, setLastRun] = useState<ScheduledRunPayload | null>(null)
+	const [error, setError] = useState<string | null>(null)
+
+	useEffect(() => {
+		if (!nativeRuntime) return
+		let unlistenSchedules: (() => void) | undefined
+		let unlistenRuns: (() => void) | undefined
+		;(async () => {
+			try {
+				unlistenSchedules = await listen<ScheduleOverview>(
+					SCHEDULES_CHANNEL,
+					({ payload }) => {
+						if (payload) {
+							setOverview(payload)
+						}
+					}
+				)
+				unlistenRuns = await listen<ScheduledRunPayload>(
+					SCHEDULE_RUN_CHANNEL,
+					({ payload }) => setLastRun(payload ?? null)
+				)
+				setOverview(await invoke<ScheduleOverview>('list_schedules'))
+			} catch (error) {
+				console.warn('schedule listener failed', error)
+			}
+		})()
+
+		return () => {
+			unlistenSchedules?.()
+			unlistenRuns?.()
+		}
+	}, [nativeRuntime])
+
+	const saveSchedule = useCallback(
+		async (schedule: MacroSchedule) => {
+			if (!nativeRuntime) return false
+			try {
+				await invoke<MacroSchedule>('save_schedule', { schedule })
+				setError(null)
+				return true
+			} catch (error) {
+				setError(String(error))
+				return false
+			}
+		},
+		[nativeRuntime]
+	)
+
+	const deleteSchedule = useCallback(
+		async (id: string) => {
+			if (!nativeRuntime) return
+			try {
+				await invoke('delete_schedule', { id })
+				setError(null)
+			} catch (error) {
+				setError(String(error))
+			}
+		},
+		[nativeRuntime]
+	)
+
+	return {
+		schedules: overview.schedules,
+		upcoming: overview.upcoming,
+		waiting: overview.waiting,
+		lastRun,
+		error,
+		saveSchedule,
+		deleteSchedule,
+	}
+}
@@ -1,13 +1,13 @@
 import { nanoid } from "nanoid";
 
-export type MouseButton = "left" | "right" | "middle" | "unknown";
+export type MouseButton = "left" | "right" | "middle" | "x1" | "x2";
 
 export type MacroEventKind =
   | { type: "mouse-move"; x: number; y: number }
   | { type: "mouse-down"; button: MouseButton }
   | { type: "mouse-up"; button: MouseButton }
-  | { type: "key-down"; key: string }
-  | { type: "key-up"; key: string }
+  | { type: "key-down"; key: string; code?: string }
+  | { type: "key-up"; key: string; code?: string }
   | { type: "scroll"; delta_x: number; delta_y: number };
 
 export interface MacroEvent {
@@ -62,6 +62,28 @@ export interface AutoClickerMetrics {
   lastTick?: number | null;
 }
 
+export type ScheduleTrigger =
+	| { type: 'cron'; expression: string }
+	| { type: 'every'; minutes: number }
+	| { type: 'once'; atMs: number }
+
+export interface MacroSchedule {
+	id: string
+	macroId: string
+	trigger: ScheduleTrigger
+	enabled: boolean
+	playbackSpeed?: number | null
+	loopCount?: number | null
+	whenBusy: 'skip' | 'queue'
+	lastRunMs?: number
+}
+
+export interface ScheduleOverview {
+	schedules: MacroSchedule[]
+	upcoming: { scheduleId: string; macroId: string; atMs: number }[]
+	waiting: string[]
+}
+
 export interface MacroEventWire {
   offset_ms: number;
   kind: MacroEventKind;